@@ -2,12 +2,13 @@ use anyhow::{anyhow, Result};
 use clap::Parser;
 use solana_commitment_config::CommitmentConfig;
 use solana_pubkey::Pubkey;
-use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client::rpc_client::{RpcClient, RpcClientConfig};
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
 
 use crate::config::{Config, ConfigOverride};
+use anchor_client::Cluster;
 
 #[derive(Debug, Parser)]
 pub struct ShowAccountCommand {
@@ -26,19 +27,22 @@ pub struct ShowAccountCommand {
 
 pub fn show_account(cfg_override: &ConfigOverride, cmd: ShowAccountCommand) -> Result<()> {
     let config = Config::discover(cfg_override)?;
-    let url = match config {
-        Some(ref cfg) => cfg.provider.cluster.url().to_string(),
+    let cluster = match config {
+        Some(ref cfg) => cfg.provider.cluster.clone(),
         None => {
-            // If not in workspace, use cluster override or default to localhost
+            // If not in workspace, use cluster override or default to mainnet
             if let Some(ref cluster) = cfg_override.cluster {
-                cluster.url().to_string()
+                cluster.clone()
             } else {
-                "https://api.mainnet-beta.solana.com".to_string()
+                Cluster::Mainnet
             }
         }
     };
 
-    let rpc_client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+    let rpc_client = RpcClient::new_sender(
+        cluster.http_sender()?,
+        RpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+    );
 
     // Fetch the account
     let account = rpc_client
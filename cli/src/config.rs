@@ -61,7 +61,8 @@ pub trait Merge: Sized {
 
 #[derive(Default, Debug, Parser)]
 pub struct ConfigOverride {
-    /// Cluster override.
+    /// Cluster override: a cluster moniker, an RPC URL, `solana` for the cluster of the Solana CLI
+    /// config, or the name of a cluster of the registry (`~/.config/anchor/clusters.toml`).
     #[clap(global = true, long = "provider.cluster")]
     pub cluster: Option<Cluster>,
     /// Wallet override.
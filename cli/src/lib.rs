@@ -4,7 +4,7 @@ use crate::config::{
     SurfnetInfoResponse, SurfpoolConfig, TestValidator, ValidatorType, WithPath, SHUTDOWN_WAIT,
     STARTUP_WAIT, SURFPOOL_HOST,
 };
-use anchor_client::Cluster;
+use anchor_client::{median_prioritization_fee, Cluster, ClusterRegistry};
 use anchor_lang::prelude::UpgradeableLoaderState;
 use anchor_lang::solana_program::bpf_loader_upgradeable;
 use anchor_lang::AnchorDeserialize;
@@ -26,7 +26,7 @@ use solana_instruction::Instruction;
 use solana_keypair::Keypair;
 use solana_pubkey::Pubkey;
 use solana_pubsub_client::pubsub_client::{PubsubClient, PubsubClientSubscription};
-use solana_rpc_client::rpc_client::RpcClient;
+use solana_rpc_client::rpc_client::{RpcClient, RpcClientConfig};
 use solana_rpc_client_api::config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter};
 use solana_rpc_client_api::request::RpcRequest;
 use solana_rpc_client_api::response::{Response as RpcResponse, RpcLogsResponse};
@@ -754,7 +754,7 @@ pub enum IdlCommand {
 
 #[derive(Debug, Parser)]
 pub enum ClusterCommand {
-    /// Prints common cluster urls.
+    /// Prints common cluster urls and the named endpoints of the cluster registry.
     List,
 }
 
@@ -789,56 +789,56 @@ fn format_sol(lamports: u64) -> String {
 }
 
 /// Get cluster URL and wallet path from Anchor config, CLI overrides, or Solana CLI config
-fn get_cluster_and_wallet(cfg_override: &ConfigOverride) -> Result<(String, String)> {
+fn get_cluster_and_wallet(cfg_override: &ConfigOverride) -> Result<(Cluster, String)> {
     // Try to get from Anchor workspace config first
     if let Ok(Some(cfg)) = Config::discover(cfg_override) {
         return Ok((
-            cfg.provider.cluster.url().to_string(),
+            cfg.provider.cluster.clone(),
             cfg.provider.wallet.to_string(),
         ));
     }
 
     // Try to load Solana CLI config
-    let (cluster_url, wallet_path) =
-        if let Some(config_file) = solana_cli_config::CONFIG_FILE.as_ref() {
-            match SolanaCliConfig::load(config_file) {
-                Ok(cli_config) => (
-                    cli_config.json_rpc_url.clone(),
-                    cli_config.keypair_path.clone(),
-                ),
-                Err(_) => {
-                    // Fallback to defaults if Solana CLI config doesn't exist
-                    (
-                        "https://api.mainnet-beta.solana.com".to_string(),
-                        dirs::home_dir()
-                            .map(|home| {
-                                home.join(".config/solana/id.json")
-                                    .to_string_lossy()
-                                    .to_string()
-                            })
-                            .unwrap_or_else(|| "~/.config/solana/id.json".to_string()),
-                    )
-                }
+    let (cluster, wallet_path) = if let Some(config_file) = solana_cli_config::CONFIG_FILE.as_ref()
+    {
+        match SolanaCliConfig::load(config_file) {
+            Ok(cli_config) => (
+                Cluster::from_solana_config_file(config_file)?,
+                cli_config.keypair_path,
+            ),
+            Err(_) => {
+                // Fallback to defaults if Solana CLI config doesn't exist
+                (
+                    Cluster::Mainnet,
+                    dirs::home_dir()
+                        .map(|home| {
+                            home.join(".config/solana/id.json")
+                                .to_string_lossy()
+                                .to_string()
+                        })
+                        .unwrap_or_else(|| "~/.config/solana/id.json".to_string()),
+                )
             }
-        } else {
-            // If CONFIG_FILE is None, use defaults
-            (
-                "https://api.mainnet-beta.solana.com".to_string(),
-                dirs::home_dir()
-                    .map(|home| {
-                        home.join(".config/solana/id.json")
-                            .to_string_lossy()
-                            .to_string()
-                    })
-                    .unwrap_or_else(|| "~/.config/solana/id.json".to_string()),
-            )
-        };
+        }
+    } else {
+        // If CONFIG_FILE is None, use defaults
+        (
+            Cluster::Mainnet,
+            dirs::home_dir()
+                .map(|home| {
+                    home.join(".config/solana/id.json")
+                        .to_string_lossy()
+                        .to_string()
+                })
+                .unwrap_or_else(|| "~/.config/solana/id.json".to_string()),
+        )
+    };
 
     // Apply cluster override if provided
     let final_cluster = if let Some(cluster) = &cfg_override.cluster {
-        cluster.url().to_string()
+        cluster.clone()
     } else {
-        cluster_url
+        cluster
     };
 
     Ok((final_cluster, wallet_path))
@@ -846,23 +846,8 @@ fn get_cluster_and_wallet(cfg_override: &ConfigOverride) -> Result<(String, Stri
 
 /// Get the recommended priority fee from the RPC client
 pub fn get_recommended_micro_lamport_fee(client: &RpcClient) -> Result<u64> {
-    let mut fees = client.get_recent_prioritization_fees(&[])?;
-    if fees.is_empty() {
-        // Fees may be empty, e.g. on localnet
-        return Ok(0);
-    }
-
-    // Get the median fee from the most recent 150 slots' prioritization fee
-    fees.sort_unstable_by_key(|fee| fee.prioritization_fee);
-    let median_index = fees.len() / 2;
-
-    let median_priority_fee = if fees.len() % 2 == 0 {
-        (fees[median_index - 1].prioritization_fee + fees[median_index].prioritization_fee) / 2
-    } else {
-        fees[median_index].prioritization_fee
-    };
-
-    Ok(median_priority_fee)
+    let fees = client.get_recent_prioritization_fees(&[])?;
+    Ok(median_prioritization_fee(&fees))
 }
 
 /// Prepend a compute unit ix, if the priority fee is greater than 0.
@@ -2463,7 +2448,8 @@ fn idl_init(
     non_canonical: bool,
 ) -> Result<()> {
     // Get cluster URL and wallet path from Anchor config
-    let (cluster_url, wallet_path) = get_cluster_and_wallet(cfg_override)?;
+    let (cluster, wallet_path) = get_cluster_and_wallet(cfg_override)?;
+    let cluster_url = cluster.url().to_string();
 
     // Skip IDL initialization on localnet
     let is_localnet = cluster_url.contains("localhost") || cluster_url.contains("127.0.0.1");
@@ -2521,7 +2507,8 @@ fn idl_upgrade(
     priority_fee: Option<u64>,
 ) -> Result<()> {
     // Get cluster URL and wallet path from Anchor config
-    let (cluster_url, wallet_path) = get_cluster_and_wallet(cfg_override)?;
+    let (cluster, wallet_path) = get_cluster_and_wallet(cfg_override)?;
+    let cluster_url = cluster.url().to_string();
 
     // Skip IDL upgrade on localnet
     let is_localnet = cluster_url.contains("localhost") || cluster_url.contains("127.0.0.1");
@@ -2945,7 +2932,8 @@ fn account(
             .unwrap_or(Cluster::Localnet),
     };
 
-    let data = create_client(cluster.url()).get_account_data(&address)?;
+    let data = create_cluster_client(&cluster, CommitmentConfig::confirmed())?
+        .get_account_data(&address)?;
     let disc_len = idl
         .accounts
         .iter()
@@ -3816,10 +3804,8 @@ fn stream_solana_logs(config: &WithPath<Config>, rpc_url: &str) -> Result<Vec<Lo
         let url = format!("ws://127.0.0.1:{}", ws_port);
         url
     } else {
-        // Remote cluster: use same URL but replace http(s) with ws(s)
-        rpc_url
-            .replace("https://", "wss://")
-            .replace("http://", "ws://")
+        // Remote cluster: use the websocket URL of the cluster, e.g. of a named endpoint
+        config.provider.cluster.ws_url().to_string()
     };
 
     // Give the WebSocket endpoint a moment to be ready (especially for local validators)
@@ -4200,7 +4186,10 @@ fn deploy(
         let keypair = cfg.provider.wallet.to_string();
 
         // Augment the given solana args with recommended defaults.
-        let client = create_client(&url);
+        let client = match cfg.provider.cluster {
+            Cluster::Localnet => create_client(&url),
+            ref cluster => create_cluster_client(cluster, CommitmentConfig::confirmed())?,
+        };
         let solana_args = add_recommended_deployment_solana_args(&client, solana_args)?;
 
         cfg.run_hooks(HookType::PreDeploy)?;
@@ -4382,10 +4371,10 @@ fn set_workspace_dir_or_exit() {
 
 fn airdrop(cfg_override: &ConfigOverride, amount: f64, pubkey: Option<Pubkey>) -> Result<()> {
     // Get cluster URL and wallet path
-    let (cluster_url, wallet_path) = get_cluster_and_wallet(cfg_override)?;
+    let (cluster, wallet_path) = get_cluster_and_wallet(cfg_override)?;
 
     // Create RPC client
-    let client = RpcClient::new(cluster_url);
+    let client = create_cluster_client(&cluster, CommitmentConfig::finalized())?;
 
     // Determine recipient
     let recipient_pubkey = if let Some(pubkey) = pubkey {
@@ -4426,6 +4415,14 @@ fn cluster(_cmd: ClusterCommand) -> Result<()> {
     println!("* Mainnet - https://api.mainnet-beta.solana.com");
     println!("* Devnet  - https://api.devnet.solana.com");
     println!("* Testnet - https://api.testnet.solana.com");
+
+    let registry = ClusterRegistry::load_default()?;
+    if registry.clusters().next().is_some() {
+        println!("\nNamed Endpoints:\n");
+        for cluster in registry.clusters() {
+            println!("* {cluster} - {}", cluster.url());
+        }
+    }
     Ok(())
 }
 
@@ -4964,8 +4961,16 @@ fn create_client<U: ToString>(url: U) -> RpcClient {
     RpcClient::new_with_commitment(url, CommitmentConfig::confirmed())
 }
 
+/// Create a new [`RpcClient`] for the cluster, sending the cluster's headers with every request.
+fn create_cluster_client(cluster: &Cluster, commitment: CommitmentConfig) -> Result<RpcClient> {
+    Ok(RpcClient::new_sender(
+        cluster.http_sender()?,
+        RpcClientConfig::with_commitment(commitment),
+    ))
+}
+
 fn address(cfg_override: &ConfigOverride) -> Result<()> {
-    let (_cluster_url, wallet_path) = get_cluster_and_wallet(cfg_override)?;
+    let (_cluster, wallet_path) = get_cluster_and_wallet(cfg_override)?;
 
     // Load keypair and get pubkey
     let keypair = Keypair::read_from_file(&wallet_path)
@@ -4978,10 +4983,10 @@ fn address(cfg_override: &ConfigOverride) -> Result<()> {
 }
 
 fn balance(cfg_override: &ConfigOverride, pubkey: Option<Pubkey>, lamports: bool) -> Result<()> {
-    let (cluster_url, wallet_path) = get_cluster_and_wallet(cfg_override)?;
+    let (cluster, wallet_path) = get_cluster_and_wallet(cfg_override)?;
 
     // Create RPC client
-    let client = RpcClient::new(cluster_url);
+    let client = create_cluster_client(&cluster, CommitmentConfig::finalized())?;
 
     // Determine which account to check
     let account_pubkey = if let Some(pubkey) = pubkey {
@@ -5007,10 +5012,10 @@ fn balance(cfg_override: &ConfigOverride, pubkey: Option<Pubkey>, lamports: bool
 }
 
 fn epoch(cfg_override: &ConfigOverride) -> Result<()> {
-    let (cluster_url, _wallet_path) = get_cluster_and_wallet(cfg_override)?;
+    let (cluster, _wallet_path) = get_cluster_and_wallet(cfg_override)?;
 
     // Create RPC client
-    let client = RpcClient::new(cluster_url);
+    let client = create_cluster_client(&cluster, CommitmentConfig::finalized())?;
 
     // Get epoch info
     let epoch_info = client.get_epoch_info()?;
@@ -5022,10 +5027,10 @@ fn epoch(cfg_override: &ConfigOverride) -> Result<()> {
 }
 
 fn epoch_info(cfg_override: &ConfigOverride) -> Result<()> {
-    let (cluster_url, _wallet_path) = get_cluster_and_wallet(cfg_override)?;
+    let (cluster, _wallet_path) = get_cluster_and_wallet(cfg_override)?;
 
     // Create RPC client
-    let client = RpcClient::new(cluster_url);
+    let client = create_cluster_client(&cluster, CommitmentConfig::finalized())?;
 
     // Get epoch info
     let epoch_info = client.get_epoch_info()?;
@@ -5151,20 +5156,8 @@ fn logs_subscribe(
     include_votes: bool,
     address: Option<Vec<Pubkey>>,
 ) -> Result<()> {
-    let (cluster_url, _wallet_path) = get_cluster_and_wallet(cfg_override)?;
-
-    // Convert HTTP(S) URL to WebSocket URL
-    let ws_url = if cluster_url.contains("localhost") || cluster_url.contains("127.0.0.1") {
-        // Parse the URL to extract and increment the port
-        cluster_url
-            .replace("https://", "wss://")
-            .replace("http://", "ws://")
-            .replace(":8899", ":8900") // Default test validator ports
-    } else {
-        cluster_url
-            .replace("https://", "wss://")
-            .replace("http://", "ws://")
-    };
+    let (cluster, _wallet_path) = get_cluster_and_wallet(cfg_override)?;
+    let ws_url = cluster.ws_url();
 
     println!("Connecting to {}", ws_url);
 
@@ -5180,7 +5173,7 @@ fn logs_subscribe(
     };
 
     let (_client, receiver) = PubsubClient::logs_subscribe(
-        &ws_url,
+        ws_url,
         filter,
         RpcTransactionLogsConfig {
             commitment: cfg_override.commitment.map(|c| CommitmentConfig {
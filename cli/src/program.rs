@@ -444,14 +444,14 @@ pub fn program(cfg_override: &ConfigOverride, cmd: ProgramCommand) -> Result<()>
 
 fn get_rpc_client_and_config(
     cfg_override: &ConfigOverride,
-) -> Result<(RpcClient, Option<WithPath<Config>>)> {
+) -> Result<(Arc<RpcClient>, Option<WithPath<Config>>)> {
     // Try to discover Anchor config first
     let config = Config::discover(cfg_override)?;
 
-    let (url, _wallet_path) = crate::get_cluster_and_wallet(cfg_override)?;
-    let rpc_client = RpcClient::new_with_commitment(url, CommitmentConfig::confirmed());
+    let (cluster, _wallet_path) = crate::get_cluster_and_wallet(cfg_override)?;
+    let rpc_client = crate::create_cluster_client(&cluster, CommitmentConfig::confirmed())?;
 
-    Ok((rpc_client, config))
+    Ok((Arc::new(rpc_client), config))
 }
 
 /// Get payer keypair from either Anchor config or Solana CLI config
@@ -1769,7 +1769,7 @@ pub fn calculate_max_chunk_size(baseline_msg: Message) -> usize {
 
 #[allow(clippy::too_many_arguments)]
 pub fn send_deploy_messages(
-    rpc_client: &RpcClient,
+    rpc_client: &Arc<RpcClient>,
     initial_message: Option<Message>,
     write_messages: Vec<Message>,
     final_message: Option<Message>,
@@ -1820,7 +1820,6 @@ pub fn send_deploy_messages(
                 &write_messages,
                 &[fee_payer_signer, write_signer],
                 max_sign_attempts,
-                commitment,
                 send_transaction_config,
             )?;
         }
@@ -1852,7 +1851,7 @@ pub fn send_deploy_messages(
 /// Complete buffer writing implementation
 #[allow(clippy::too_many_arguments)]
 pub fn write_program_buffer(
-    rpc_client: &RpcClient,
+    rpc_client: &Arc<RpcClient>,
     payer: &dyn Signer,
     program_data: &[u8],
     buffer_authority: &Pubkey,
@@ -1944,21 +1943,15 @@ fn prepare_write_messages(
 
 /// Send messages in parallel
 fn send_messages_in_batches(
-    rpc_client: &RpcClient,
+    rpc_client: &Arc<RpcClient>,
     messages: &[Message],
     signers: &[&dyn Signer],
     max_sign_attempts: usize,
-    commitment: CommitmentConfig,
     send_config: RpcSendTransactionConfig,
 ) -> Result<()> {
-    // Use parallel send and confirm function
-    // Create a new RpcClient with the same URL and wrap in Arc for parallel processing
-    let url = rpc_client.url();
-    let new_rpc_client = RpcClient::new_with_commitment(url, commitment);
-    let rpc_client_arc = Arc::new(new_rpc_client);
-
+    // Use parallel send and confirm function, sharing the client and its cluster headers
     let transaction_errors = send_and_confirm_transactions_in_parallel_blocking_v2(
-        rpc_client_arc,
+        Arc::clone(rpc_client),
         None,
         messages,
         signers,
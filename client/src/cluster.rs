@@ -1,6 +1,12 @@
-use anyhow::{anyhow, Result};
+use crate::ClientError;
+use anyhow::{anyhow, Context, Result};
+use reqwest::header::{HeaderName, HeaderValue};
 use serde::{Deserialize, Serialize};
+use solana_rpc_client::http_sender::HttpSender;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 use url::Url;
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, Ord, PartialOrd)]
@@ -12,44 +18,109 @@ pub enum Cluster {
     Localnet,
     Debug,
     Custom(String, String),
+    /// Endpoint of the [`ClusterRegistry`] with the given name.
+    Named(String, Endpoint),
 }
 
+/// RPC endpoint of a named cluster.
+#[derive(Serialize, Deserialize, Clone, Default, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Endpoint {
+    pub url: String,
+    pub ws_url: String,
+    /// HTTP headers sent with every RPC request, e.g. authentication tokens.
+    ///
+    /// The headers are not sent with the websocket requests of subscriptions. They may contain
+    /// secrets, so they are not serialized.
+    #[serde(default, skip_serializing)]
+    pub headers: BTreeMap<String, String>,
+}
+
+// The header values may contain secrets, e.g. authentication tokens
+impl std::fmt::Debug for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let headers = self
+            .headers
+            .keys()
+            .map(|name| (name, "<redacted>"))
+            .collect::<BTreeMap<_, _>>();
+        f.debug_struct("Endpoint")
+            .field("url", &self.url)
+            .field("ws_url", &self.ws_url)
+            .field("headers", &headers)
+            .finish()
+    }
+}
+
+/// Parses a cluster moniker, a URL, or the cluster of the user's registry
+/// ([`ClusterRegistry::load_default`]) with the given name.
+///
+/// `solana` is the cluster of the Solana CLI config ([`Cluster::from_solana_config`]). The
+/// registry is only loaded for names that are neither a moniker nor a URL; use
+/// [`ClusterRegistry::resolve`] to resolve the names of another registry.
 impl FromStr for Cluster {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Cluster> {
-        match s.to_lowercase().as_str() {
-            "t" | "testnet" => Ok(Cluster::Testnet),
-            "m" | "mainnet" => Ok(Cluster::Mainnet),
-            "d" | "devnet" => Ok(Cluster::Devnet),
-            "l" | "localnet" => Ok(Cluster::Localnet),
-            "g" | "debug" => Ok(Cluster::Debug),
+        Cluster::parse_moniker(s).unwrap_or_else(|| {
+            ClusterRegistry::load_default()?
+                .get(s)
+                .ok_or_else(unknown_cluster)
+        })
+    }
+}
+
+impl Cluster {
+    /// Parses a cluster moniker or URL, returning `None` for other names.
+    fn parse_moniker(s: &str) -> Option<Result<Cluster>> {
+        let cluster = match s.to_lowercase().as_str() {
+            "t" | "testnet" => Cluster::Testnet,
+            "m" | "mainnet" => Cluster::Mainnet,
+            "d" | "devnet" => Cluster::Devnet,
+            "l" | "localnet" => Cluster::Localnet,
+            "g" | "debug" => Cluster::Debug,
             _ if s.starts_with("http") => {
-                let http_url = s;
-
-                // Taken from:
-                // https://github.com/solana-labs/solana/blob/aea8f0df1610248d29d8ca3bc0d60e9fabc99e31/web3.js/src/util/url.ts
-
-                let mut ws_url = Url::parse(http_url)?;
-                if let Some(port) = ws_url.port() {
-                    ws_url.set_port(Some(port + 1))
-                        .map_err(|_| anyhow!("Unable to set port"))?;
-                }
-                if ws_url.scheme() == "https" {
-                    ws_url.set_scheme("wss")
-                        .map_err(|_| anyhow!("Unable to set scheme"))?;
-                } else {
-                    ws_url.set_scheme("ws")
-                        .map_err(|_| anyhow!("Unable to set scheme"))?;
-                }
-
-
-                Ok(Cluster::Custom(http_url.to_string(), ws_url.to_string()))
+                return Some(
+                    ws_url_from_http(s).map(|ws_url| Cluster::Custom(s.to_string(), ws_url)),
+                )
             }
-            _ => Err(anyhow::Error::msg(
-                "Cluster must be one of [localnet, testnet, mainnet, devnet] or be an http or https url\n",
-            )),
-        }
+            SOLANA_CONFIG_CLUSTER => return Some(Cluster::from_solana_config()),
+            _ => return None,
+        };
+        Some(Ok(cluster))
+    }
+}
+
+fn unknown_cluster() -> anyhow::Error {
+    anyhow::Error::msg(
+        "Cluster must be one of [localnet, testnet, mainnet, devnet, solana], \
+        a cluster of the registry or be an http or https url\n",
+    )
+}
+
+/// Name of the cluster of the Solana CLI config.
+const SOLANA_CONFIG_CLUSTER: &str = "solana";
+
+/// Derives the websocket URL of an RPC endpoint from its HTTP URL.
+fn ws_url_from_http(http_url: &str) -> Result<String> {
+    // Taken from:
+    // https://github.com/solana-labs/solana/blob/aea8f0df1610248d29d8ca3bc0d60e9fabc99e31/web3.js/src/util/url.ts
+
+    let mut ws_url = Url::parse(http_url)?;
+    if let Some(port) = ws_url.port() {
+        ws_url
+            .set_port(Some(port + 1))
+            .map_err(|_| anyhow!("Unable to set port"))?;
     }
+    if ws_url.scheme() == "https" {
+        ws_url
+            .set_scheme("wss")
+            .map_err(|_| anyhow!("Unable to set scheme"))?;
+    } else {
+        ws_url
+            .set_scheme("ws")
+            .map_err(|_| anyhow!("Unable to set scheme"))?;
+    }
+
+    Ok(ws_url.to_string())
 }
 
 impl std::fmt::Display for Cluster {
@@ -61,6 +132,7 @@ impl std::fmt::Display for Cluster {
             Cluster::Localnet => "localnet",
             Cluster::Debug => "debug",
             Cluster::Custom(url, _ws_url) => url,
+            Cluster::Named(name, _endpoint) => name,
         };
         write!(f, "{clust_str}")
     }
@@ -75,6 +147,7 @@ impl Cluster {
             Cluster::Localnet => "http://127.0.0.1:8899",
             Cluster::Debug => "http://34.90.18.145:8899",
             Cluster::Custom(url, _ws_url) => url,
+            Cluster::Named(_name, endpoint) => &endpoint.url,
         }
     }
     pub fn ws_url(&self) -> &str {
@@ -85,8 +158,166 @@ impl Cluster {
             Cluster::Localnet => "ws://127.0.0.1:8900",
             Cluster::Debug => "ws://34.90.18.145:8900",
             Cluster::Custom(_url, ws_url) => ws_url,
+            Cluster::Named(_name, endpoint) => &endpoint.ws_url,
         }
     }
+
+    /// Returns the cluster of the Solana CLI config file, i.e. its `json_rpc_url` and
+    /// `websocket_url`.
+    pub fn from_solana_config() -> Result<Self> {
+        let path = solana_cli_config::CONFIG_FILE
+            .as_ref()
+            .ok_or_else(|| anyhow!("Solana CLI config file not found"))?;
+        Self::from_solana_config_file(path)
+    }
+
+    /// Same as [`Cluster::from_solana_config`], with the config file at the given path.
+    pub fn from_solana_config_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let config = solana_cli_config::Config::load(&path.to_string_lossy())
+            .with_context(|| format!("Unable to load the Solana CLI config {}", path.display()))?;
+        let ws_url = match config.websocket_url.as_str() {
+            "" => ws_url_from_http(&config.json_rpc_url)?,
+            ws_url => ws_url.to_string(),
+        };
+        Ok(Cluster::Custom(config.json_rpc_url, ws_url))
+    }
+
+    /// Returns the HTTP headers of the cluster's RPC requests.
+    pub fn headers(&self) -> Option<&BTreeMap<String, String>> {
+        match self {
+            Cluster::Named(_name, endpoint) => Some(&endpoint.headers),
+            _ => None,
+        }
+    }
+
+    /// Returns the sender of the RPC requests to the cluster, with the cluster's headers.
+    pub fn http_sender(&self) -> Result<HttpSender, ClientError> {
+        let headers = match self.headers() {
+            Some(headers) if !headers.is_empty() => headers,
+            _ => return Ok(HttpSender::new(self.url())),
+        };
+
+        let mut header_map = HttpSender::default_headers();
+        for (name, value) in headers {
+            let name =
+                HeaderName::from_str(name).map_err(|_| ClientError::InvalidHeader(name.clone()))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| ClientError::InvalidHeader(name.to_string()))?;
+            header_map.insert(name, value);
+        }
+        let timeout = Duration::from_secs(30);
+        let client = reqwest::Client::builder()
+            .default_headers(header_map)
+            .timeout(timeout)
+            .pool_idle_timeout(timeout)
+            .build()
+            .map_err(|e| ClientError::InvalidHeader(e.to_string()))?;
+        Ok(HttpSender::new_with_client(self.url(), client))
+    }
+}
+
+/// Registry of named RPC endpoints, loaded from a TOML file such as:
+///
+/// ```toml
+/// [clusters.my-rpc]
+/// url = "https://rpc.example.com"
+/// # Derived from `url` if not set
+/// ws_url = "wss://ws.example.com"
+/// headers = { Authorization = "Bearer <token>" }
+/// ```
+///
+/// [`Cluster::from_str`] resolves the names of the registry at [`ClusterRegistry::default_path`],
+/// and [`ClusterRegistry::resolve`] those of any registry.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ClusterRegistry {
+    endpoints: BTreeMap<String, Endpoint>,
+}
+
+#[derive(Deserialize)]
+struct RegistryFile {
+    #[serde(default)]
+    clusters: BTreeMap<String, RegistryEntry>,
+}
+
+#[derive(Deserialize)]
+struct RegistryEntry {
+    url: String,
+    ws_url: Option<String>,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+}
+
+impl ClusterRegistry {
+    /// Returns the path of the user's registry, `~/.config/anchor/clusters.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".config/anchor/clusters.toml"))
+    }
+
+    /// Loads the user's registry, which is empty if the registry file doesn't exist.
+    pub fn load_default() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        std::fs::read_to_string(path)?
+            .parse()
+            .with_context(|| format!("Invalid cluster registry {}", path.display()))
+    }
+
+    /// Returns the cluster with the given name.
+    pub fn get(&self, name: &str) -> Option<Cluster> {
+        self.endpoints
+            .get(name)
+            .map(|endpoint| Cluster::Named(name.to_string(), endpoint.clone()))
+    }
+
+    /// Parses a cluster moniker or URL like [`Cluster::from_str`], or returns the cluster of this
+    /// registry with the given name.
+    pub fn resolve(&self, s: &str) -> Result<Cluster> {
+        Cluster::parse_moniker(s).unwrap_or_else(|| self.get(s).ok_or_else(unknown_cluster))
+    }
+
+    /// Adds a named endpoint to the registry, replacing the existing one with the same name.
+    pub fn insert(&mut self, name: impl Into<String>, endpoint: Endpoint) {
+        self.endpoints.insert(name.into(), endpoint);
+    }
+
+    /// Returns the clusters of the registry, ordered by name.
+    pub fn clusters(&self) -> impl Iterator<Item = Cluster> + '_ {
+        self.endpoints
+            .iter()
+            .map(|(name, endpoint)| Cluster::Named(name.clone(), endpoint.clone()))
+    }
+}
+
+impl FromStr for ClusterRegistry {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let file: RegistryFile = toml::from_str(s)?;
+        let endpoints = file
+            .clusters
+            .into_iter()
+            .map(|(name, entry)| {
+                let ws_url = match entry.ws_url {
+                    Some(ws_url) => ws_url,
+                    None => ws_url_from_http(&entry.url)?,
+                };
+                let endpoint = Endpoint {
+                    url: entry.url,
+                    ws_url,
+                    headers: entry.headers,
+                };
+                Ok((name, endpoint))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { endpoints })
+    }
 }
 
 #[cfg(test)]
@@ -152,6 +383,61 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_registry() {
+        let registry: ClusterRegistry = r#"
+            [clusters.my-rpc]
+            url = "https://rpc.example.com/"
+            headers = { Authorization = "Bearer token" }
+
+            [clusters.other]
+            url = "http://127.0.0.1:7000/"
+            ws_url = "ws://127.0.0.1:9000/"
+        "#
+        .parse()
+        .unwrap();
+
+        let cluster = registry.get("my-rpc").unwrap();
+        assert_eq!(cluster.to_string(), "my-rpc");
+        assert_eq!(cluster.url(), "https://rpc.example.com/");
+        assert_eq!(cluster.ws_url(), "wss://rpc.example.com/");
+        assert_eq!(cluster.headers().unwrap()["Authorization"], "Bearer token");
+        assert!(cluster.http_sender().is_ok());
+        let debug = format!("{cluster:?}");
+        assert!(debug.contains("Authorization"));
+        assert!(!debug.contains("Bearer token"));
+        let serialized = serde_json::to_string(&cluster).unwrap();
+        assert!(serialized.contains("https://rpc.example.com/"));
+        assert!(!serialized.contains("Bearer token"));
+
+        let cluster = registry.get("other").unwrap();
+        assert_eq!(cluster.ws_url(), "ws://127.0.0.1:9000/");
+        assert!(cluster.headers().unwrap().is_empty());
+
+        assert!(registry.get("unknown").is_none());
+        assert_eq!(
+            registry.resolve("my-rpc").unwrap().url(),
+            "https://rpc.example.com/"
+        );
+        assert_eq!(registry.resolve("devnet").unwrap(), Cluster::Devnet);
+        let err = registry.resolve("unknown").unwrap_err();
+        assert!(err.to_string().starts_with("Cluster must be one of"));
+        assert_eq!(registry.clusters().count(), 2);
+    }
+
+    #[test]
+    fn test_invalid_header() {
+        let cluster = Cluster::Named(
+            "my-rpc".to_string(),
+            Endpoint {
+                url: "https://rpc.example.com/".to_string(),
+                ws_url: "wss://rpc.example.com/".to_string(),
+                headers: BTreeMap::from([("invalid name".to_string(), "value".to_string())]),
+            },
+        );
+        assert!(cluster.http_sender().is_err());
+    }
+
     #[test]
     fn test_upper_case() {
         let url = "http://my-url.com/FooBar";
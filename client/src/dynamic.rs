@@ -0,0 +1,351 @@
+use crate::idl::{decode_value, encode_value};
+use crate::{handle_program_log_with, parse_logs_with, ClientError, MAX_RETURN_DATA};
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang_idl::types::{Idl, IdlInstruction, IdlInstructionAccountItem, IdlType};
+use serde_json::Value;
+
+/// Account or event decoded into JSON with [`DynamicProgram`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedValue {
+    /// Name of the account or event type.
+    pub name: String,
+    /// Decoded data.
+    pub data: Value,
+}
+
+/// Interface of a program loaded from its IDL at runtime, for programs without a Rust crate to
+/// depend on.
+///
+/// Values are represented as JSON: structs as objects (arrays for tuple structs), enums as
+/// `{ "Variant": fields }` objects (unit variants can also be encoded from a string), public keys
+/// as base58 strings, and integers larger than 64 bits as decimal strings.
+///
+/// # Example
+///
+/// ```ignore
+/// let program = DynamicProgram::new(idl)?;
+/// let ix = program.instruction(
+///     "initialize",
+///     &[("authority", payer.pubkey()), ("counter", counter)],
+///     &json!({ "start": 1 }),
+/// )?;
+/// let signature = client
+///     .program(program.id())?
+///     .request()
+///     .instruction(ix)
+///     .send()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct DynamicProgram {
+    idl: Idl,
+    program_id: Pubkey,
+}
+
+impl DynamicProgram {
+    /// Creates the program interface with the program ID of the IDL.
+    pub fn new(idl: Idl) -> Result<Self, ClientError> {
+        let program_id = idl
+            .address
+            .parse()
+            .map_err(|_| coder_error(format!("invalid program address: {}", idl.address)))?;
+        Ok(Self { idl, program_id })
+    }
+
+    /// Sets the program ID, e.g. for programs deployed at a different address than the IDL's.
+    #[must_use]
+    pub fn with_program_id(mut self, program_id: Pubkey) -> Self {
+        self.program_id = program_id;
+        self
+    }
+
+    pub fn id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    pub fn idl(&self) -> &Idl {
+        &self.idl
+    }
+
+    /// Builds an instruction from its accounts by name and its JSON arguments object.
+    ///
+    /// Accounts of composite account structs are named with their path, e.g. `nested.vault`.
+    pub fn instruction(
+        &self,
+        name: &str,
+        accounts: &[(&str, Pubkey)],
+        args: &Value,
+    ) -> Result<Instruction, ClientError> {
+        Ok(Instruction {
+            program_id: self.program_id,
+            accounts: self.account_metas(name, accounts)?,
+            data: self.encode_args(name, args)?,
+        })
+    }
+
+    /// Encodes the instruction data, including the discriminator, from the JSON arguments
+    /// object.
+    pub fn encode_args(&self, name: &str, args: &Value) -> Result<Vec<u8>, ClientError> {
+        let ix = self.find_instruction(name)?;
+        let mut data = ix.discriminator.clone();
+        for arg in &ix.args {
+            let value = args
+                .get(&arg.name)
+                .ok_or_else(|| coder_error(format!("missing argument `{}`", arg.name)))?;
+            encode_value(&self.idl, &arg.ty, value, &mut data)?;
+        }
+        Ok(data)
+    }
+
+    /// Returns the account metas of the instruction from its accounts by name.
+    ///
+    /// Missing optional accounts are set to the program ID.
+    pub fn account_metas(
+        &self,
+        name: &str,
+        accounts: &[(&str, Pubkey)],
+    ) -> Result<Vec<AccountMeta>, ClientError> {
+        fn push_metas(
+            items: &[IdlInstructionAccountItem],
+            scope: &str,
+            accounts: &[(&str, Pubkey)],
+            program_id: Pubkey,
+            metas: &mut Vec<AccountMeta>,
+        ) -> Result<(), ClientError> {
+            for item in items {
+                match item {
+                    IdlInstructionAccountItem::Single(acc) => {
+                        let path = format!("{scope}{}", acc.name);
+                        let pubkey = accounts
+                            .iter()
+                            .find(|(name, _)| *name == path)
+                            .map(|(_, pubkey)| *pubkey);
+                        metas.push(match pubkey {
+                            Some(pubkey) => AccountMeta {
+                                pubkey,
+                                is_signer: acc.signer,
+                                is_writable: acc.writable,
+                            },
+                            None if acc.optional => AccountMeta::new_readonly(program_id, false),
+                            None => return Err(coder_error(format!("missing account `{path}`"))),
+                        });
+                    }
+                    IdlInstructionAccountItem::Composite(composite) => push_metas(
+                        &composite.accounts,
+                        &format!("{scope}{}.", composite.name),
+                        accounts,
+                        program_id,
+                        metas,
+                    )?,
+                }
+            }
+            Ok(())
+        }
+
+        let mut metas = vec![];
+        push_metas(
+            &self.find_instruction(name)?.accounts,
+            "",
+            accounts,
+            self.program_id,
+            &mut metas,
+        )?;
+        Ok(metas)
+    }
+
+    /// Decodes the data of an account of the program, with the account type detected from the
+    /// discriminator.
+    pub fn decode_account(&self, data: &[u8]) -> Result<DecodedValue, ClientError> {
+        let account = self
+            .idl
+            .accounts
+            .iter()
+            .find(|acc| data.starts_with(&acc.discriminator))
+            .ok_or_else(|| coder_error("unknown account discriminator".to_string()))?;
+        self.decode_type(&account.name, &data[account.discriminator.len()..])
+    }
+
+    /// Decodes the data of an event of the program, with the event type detected from the
+    /// discriminator.
+    pub fn decode_event(&self, data: &[u8]) -> Result<DecodedValue, ClientError> {
+        let event = self
+            .idl
+            .events
+            .iter()
+            .find(|ev| data.starts_with(&ev.discriminator))
+            .ok_or_else(|| coder_error("unknown event discriminator".to_string()))?;
+        self.decode_type(&event.name, &data[event.discriminator.len()..])
+    }
+
+    /// Decodes the return data of the given instruction.
+    pub fn decode_return(&self, name: &str, data: &[u8]) -> Result<Value, ClientError> {
+        let ty = self
+            .find_instruction(name)?
+            .returns
+            .as_ref()
+            .ok_or_else(|| coder_error(format!("instruction `{name}` has no return type")))?;
+        // Trailing zeros of the return data are truncated by the runtime
+        let mut data = data.to_vec();
+        data.resize(data.len().max(MAX_RETURN_DATA), 0);
+        decode_value(&self.idl, ty, &mut data.as_slice())
+    }
+
+    /// Parses the events emitted by the program with `emit!` from the transaction logs.
+    pub fn parse_events(&self, logs: &[String]) -> Result<Vec<DecodedValue>, ClientError> {
+        let program_id_str = self.program_id.to_string();
+        parse_logs_with(logs, &program_id_str, |l| {
+            handle_program_log_with(&program_id_str, l, |data| {
+                // Skip the logs that are not events, e.g. `Program log:` lines that happen to be
+                // valid base64
+                let is_event = self
+                    .idl
+                    .events
+                    .iter()
+                    .any(|ev| data.starts_with(&ev.discriminator));
+                is_event.then(|| self.decode_event(data)).transpose()
+            })
+        })
+    }
+
+    fn find_instruction(&self, name: &str) -> Result<&IdlInstruction, ClientError> {
+        self.idl
+            .instructions
+            .iter()
+            .find(|ix| ix.name == name)
+            .ok_or_else(|| coder_error(format!("instruction `{name}` not found in the IDL")))
+    }
+
+    fn decode_type(&self, name: &str, mut data: &[u8]) -> Result<DecodedValue, ClientError> {
+        let ty = IdlType::Defined {
+            name: name.to_owned(),
+            generics: vec![],
+        };
+        Ok(DecodedValue {
+            name: name.to_owned(),
+            data: decode_value(&self.idl, &ty, &mut data)?,
+        })
+    }
+}
+
+fn coder_error(msg: String) -> ClientError {
+    ClientError::IdlCoderError(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn idl() -> Idl {
+        serde_json::from_value(json!({
+            "address": "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "initialize",
+                "discriminator": [1, 1, 1, 1, 1, 1, 1, 1],
+                "accounts": [
+                    { "name": "authority", "writable": true, "signer": true },
+                    {
+                        "name": "nested",
+                        "accounts": [{ "name": "vault", "writable": true }]
+                    },
+                    { "name": "rent", "optional": true }
+                ],
+                "args": [
+                    { "name": "amount", "type": "u64" },
+                    { "name": "kind", "type": { "defined": { "name": "Kind" } } },
+                    { "name": "label", "type": { "option": "string" } }
+                ],
+                "returns": "u128"
+            }],
+            "accounts": [{ "name": "Counter", "discriminator": [2, 2, 2, 2, 2, 2, 2, 2] }],
+            "events": [{ "name": "Counted", "discriminator": [3, 3, 3, 3, 3, 3, 3, 3] }],
+            "types": [
+                {
+                    "name": "Counter",
+                    "type": {
+                        "kind": "struct",
+                        "fields": [
+                            { "name": "authority", "type": "pubkey" },
+                            { "name": "count", "type": "u64" }
+                        ]
+                    }
+                },
+                {
+                    "name": "Counted",
+                    "type": { "kind": "struct", "fields": ["u64", "bool"] }
+                },
+                {
+                    "name": "Kind",
+                    "type": {
+                        "kind": "enum",
+                        "variants": [
+                            { "name": "A" },
+                            { "name": "B", "fields": [{ "name": "x", "type": "u8" }] }
+                        ]
+                    }
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_instruction() {
+        let program = DynamicProgram::new(idl()).unwrap();
+        let authority = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let ix = program
+            .instruction(
+                "initialize",
+                &[("authority", authority), ("nested.vault", vault)],
+                &json!({ "amount": 5, "kind": { "B": { "x": 7 } }, "label": null }),
+            )
+            .unwrap();
+
+        assert_eq!(
+            ix.data,
+            [&[1; 8][..], &5u64.to_le_bytes(), &[1, 7], &[0]].concat()
+        );
+        assert_eq!(
+            ix.accounts,
+            vec![
+                AccountMeta::new(authority, true),
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(program.id(), false),
+            ]
+        );
+
+        // Missing account
+        assert!(program
+            .account_metas("initialize", &[("authority", authority)])
+            .is_err());
+        // Missing argument
+        assert!(program
+            .encode_args("initialize", &json!({ "amount": 5 }))
+            .is_err());
+    }
+
+    #[test]
+    fn test_decode() {
+        let program = DynamicProgram::new(idl()).unwrap();
+        let authority = Pubkey::new_unique();
+
+        let data = [&[2; 8][..], authority.as_ref(), &9u64.to_le_bytes()].concat();
+        let account = program.decode_account(&data).unwrap();
+        assert_eq!(account.name, "Counter");
+        assert_eq!(
+            account.data,
+            json!({ "authority": authority.to_string(), "count": 9 })
+        );
+
+        let data = [&[3; 8][..], &1u64.to_le_bytes(), &[1]].concat();
+        let event = program.decode_event(&data).unwrap();
+        assert_eq!(event.name, "Counted");
+        assert_eq!(event.data, json!([1, true]));
+
+        // Trailing zeros are truncated from the return data
+        let value = program.decode_return("initialize", &[1, 1]).unwrap();
+        assert_eq!(value, json!("257"));
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{parse_anchor_error, ClientError};
+use anchor_lang::error::{AnchorError, ComparedValues, ErrorCode, ErrorOrigin};
+use anchor_lang_idl::types::{Idl, IdlErrorCode};
+use solana_instruction::error::InstructionError;
+use solana_rpc_client_api::{
+    client_error::{Error as SolanaClientError, ErrorKind},
+    request::{RpcError, RpcResponseErrorData},
+    response::TransactionError,
+};
+use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::sync::Arc;
+
+/// Error of a failed transaction, decoded from the `AnchorError` logs or the custom error code
+/// of the failing instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedError {
+    /// Index of the failing instruction in the transaction.
+    pub instruction_index: Option<u8>,
+    /// Name of the error, e.g. `ConstraintHasOne`.
+    pub name: String,
+    /// Error code number.
+    pub number: u32,
+    /// Error message.
+    pub message: String,
+    /// Name of the account that caused the error.
+    pub account_name: Option<String>,
+    /// Values that were compared when the error was thrown.
+    pub compared_values: Option<ComparedValues>,
+    /// Transaction logs, empty if they were not available.
+    pub logs: Vec<String>,
+}
+
+impl Display for DecodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}): {}", self.name, self.number, self.message)?;
+        if let Some(account_name) = &self.account_name {
+            write!(f, " (account: {account_name})")?;
+        }
+        match &self.compared_values {
+            Some(ComparedValues::Values((left, right))) => {
+                write!(f, " (left: {left}, right: {right})")
+            }
+            Some(ComparedValues::Pubkeys((left, right))) => {
+                write!(f, " (left: {left}, right: {right})")
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl From<DecodedError> for anchor_lang::error::Error {
+    fn from(error: DecodedError) -> Self {
+        AnchorError {
+            error_name: error.name,
+            error_code_number: error.number,
+            error_msg: error.message,
+            error_origin: error.account_name.map(ErrorOrigin::AccountName),
+            compared_values: error.compared_values,
+        }
+        .into()
+    }
+}
+
+/// Function that decodes an error code into an error of a registered `#[error_code]` enum.
+type ErrorDecoder = Arc<dyn Fn(u32) -> Option<anchor_lang::error::Error> + Send + Sync>;
+
+/// Registry of the error definitions used to decode the custom error codes of failed
+/// transactions.
+///
+/// Anchor's built-in [`ErrorCode`]s are always decoded. Program errors can be registered either
+/// with their `#[error_code]` enum or with the `errors` list of the program's IDL.
+///
+/// # Example
+///
+/// ```ignore
+/// let errors = ErrorRegistry::new().with_error_code(my_program::ErrorCode::from_code);
+/// let program = client.program(my_program::ID)?.with_errors(errors);
+/// ```
+#[derive(Clone, Default)]
+pub struct ErrorRegistry {
+    decoders: Vec<ErrorDecoder>,
+    idl_errors: HashMap<u32, IdlErrorCode>,
+}
+
+impl fmt::Debug for ErrorRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorRegistry")
+            .field("decoders", &self.decoders.len())
+            .field("idl_errors", &self.idl_errors)
+            .finish()
+    }
+}
+
+impl ErrorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the errors of an `#[error_code]` enum with its generated `from_code` function.
+    #[must_use]
+    pub fn with_error_code<E>(mut self, from_code: fn(u32) -> Option<E>) -> Self
+    where
+        E: Into<anchor_lang::error::Error> + 'static,
+    {
+        self.decoders
+            .push(Arc::new(move |code| from_code(code).map(Into::into)));
+        self
+    }
+
+    /// Register the errors of the given IDL.
+    #[must_use]
+    pub fn with_idl(mut self, idl: &Idl) -> Self {
+        self.idl_errors
+            .extend(idl.errors.iter().map(|e| (e.code, e.clone())));
+        self
+    }
+
+    /// Returns the name and the message of the error with the given code.
+    ///
+    /// Registered errors take precedence over the built-in ones.
+    pub fn lookup(&self, code: u32) -> Option<(String, String)> {
+        let from_error = |error| match error {
+            anchor_lang::error::Error::AnchorError(error) => {
+                Some((error.error_name, error.error_msg))
+            }
+            anchor_lang::error::Error::ProgramError(_) => None,
+        };
+
+        self.decoders
+            .iter()
+            .find_map(|decode| decode(code))
+            .and_then(from_error)
+            .or_else(|| {
+                self.idl_errors
+                    .get(&code)
+                    .map(|e| (e.name.clone(), e.msg.clone().unwrap_or_default()))
+            })
+            .or_else(|| ErrorCode::from_code(code).and_then(|error| from_error(error.into())))
+    }
+
+    /// Decodes the error of a failed transaction.
+    ///
+    /// The `AnchorError` logged by the program is preferred since it includes the account name
+    /// and compared values. Otherwise, the custom error code of the failing instruction is looked
+    /// up in the registry. Note that custom error codes are not unique across programs, so an
+    /// error code returned from a CPI into a non-Anchor program may be decoded incorrectly.
+    pub fn decode(&self, err: Option<&TransactionError>, logs: &[String]) -> Option<DecodedError> {
+        let instruction_index = match err {
+            Some(TransactionError::InstructionError(index, _)) => Some(*index),
+            _ => None,
+        };
+
+        if let Some(error) = parse_anchor_error(logs) {
+            return Some(DecodedError {
+                instruction_index,
+                name: error.error_name,
+                number: error.error_code_number,
+                message: error.error_msg,
+                account_name: match error.error_origin {
+                    Some(ErrorOrigin::AccountName(account_name)) => Some(account_name),
+                    _ => None,
+                },
+                compared_values: error.compared_values,
+                logs: logs.to_vec(),
+            });
+        }
+
+        match err {
+            Some(TransactionError::InstructionError(_, InstructionError::Custom(code))) => {
+                let (name, message) = self.lookup(*code)?;
+                Some(DecodedError {
+                    instruction_index,
+                    name,
+                    number: *code,
+                    message,
+                    account_name: None,
+                    compared_values: None,
+                    logs: logs.to_vec(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts an RPC client error into [`ClientError::Anchor`] if the program error can be
+    /// decoded, otherwise into [`ClientError::SolanaClientError`].
+    pub(crate) fn decode_client_error(&self, error: SolanaClientError) -> ClientError {
+        let logs = match error.kind() {
+            ErrorKind::RpcError(RpcError::RpcResponseError {
+                data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+                ..
+            }) => result.logs.clone().unwrap_or_default(),
+            _ => vec![],
+        };
+
+        match self.decode(error.get_transaction_error().as_ref(), &logs) {
+            Some(decoded) => ClientError::Anchor(Box::new(decoded)),
+            None => ClientError::SolanaClientError(Box::new(error)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom_error(code: u32) -> TransactionError {
+        TransactionError::InstructionError(1, InstructionError::Custom(code))
+    }
+
+    #[test]
+    fn decode_builtin_error_code() {
+        let decoded = ErrorRegistry::new()
+            .decode(Some(&custom_error(2001)), &[])
+            .unwrap();
+        assert_eq!(decoded.instruction_index, Some(1));
+        assert_eq!(decoded.name, "ConstraintHasOne");
+        assert_eq!(decoded.message, "A has one constraint was violated");
+    }
+
+    #[anchor_lang::error_code]
+    pub enum MyError {
+        #[msg("Not allowed")]
+        Unauthorized,
+    }
+
+    #[test]
+    fn decode_registered_error_code() {
+        let registry = ErrorRegistry::new().with_error_code(MyError::from_code);
+        let decoded = registry.decode(Some(&custom_error(6000)), &[]).unwrap();
+        assert_eq!(decoded.name, "Unauthorized");
+        assert_eq!(decoded.message, "Not allowed");
+
+        let error: anchor_lang::error::Error = decoded.into();
+        assert_eq!(error, MyError::Unauthorized.into());
+    }
+
+    #[test]
+    fn decode_idl_error_code() {
+        let idl: Idl = serde_json::from_str(
+            r#"{
+                "address": "11111111111111111111111111111111",
+                "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+                "instructions": [],
+                "errors": [{ "code": 6000, "name": "Unauthorized", "msg": "Not allowed" }]
+            }"#,
+        )
+        .unwrap();
+        let registry = ErrorRegistry::new().with_idl(&idl);
+
+        let decoded = registry.decode(Some(&custom_error(6000)), &[]).unwrap();
+        assert_eq!(decoded.name, "Unauthorized");
+        assert_eq!(decoded.message, "Not allowed");
+        assert!(registry.decode(Some(&custom_error(6001)), &[]).is_none());
+    }
+
+    #[test]
+    fn decode_logs_over_error_code() {
+        let logs = vec![
+            "Program log: AnchorError caused by account: vault. Error Code: ConstraintSeeds. Error Number: 2006. Error Message: A seeds constraint was violated.".to_string(),
+        ];
+        let decoded = ErrorRegistry::new()
+            .decode(Some(&custom_error(2006)), &logs)
+            .unwrap();
+        assert_eq!(decoded.name, "ConstraintSeeds");
+        assert_eq!(decoded.account_name.as_deref(), Some("vault"));
+        assert_eq!(decoded.logs, logs);
+    }
+}
@@ -0,0 +1,319 @@
+use crate::logs::{parse_invoke, parse_status, LOG_TRUNCATED};
+use crate::{ClientError, PROGRAM_DATA, PROGRAM_LOG};
+use anchor_lang::__private::base64;
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::{AnchorDeserialize, Discriminator, Event};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_transaction_status_client_types::{
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction,
+    EncodedTransactionWithStatusMeta, UiInstruction, UiMessage, UiParsedInstruction,
+};
+use std::collections::BTreeMap;
+
+/// Parses the events of type `T` emitted by the given program in a confirmed transaction.
+///
+/// Both events emitted with `emit!` (logged as `Program data:`) and events emitted with
+/// `emit_cpi!` (stored in the data of the program's self-CPI instructions) are returned, in
+/// execution order. The order is recovered by walking the program invocations in the logs
+/// alongside the inner instructions of each instruction. If the logs were truncated, the
+/// `emit_cpi!` events of the remaining inner instructions are appended in instruction order.
+///
+/// Failed transactions have no effect, so no events are returned for them.
+///
+/// The transaction can be fetched with any encoding. Note that `emit_cpi!` events can only be
+/// parsed if the inner instructions are included in the transaction status meta.
+pub fn parse_transaction_events<T: Event>(
+    program_id: &Pubkey,
+    tx: &EncodedConfirmedTransactionWithStatusMeta,
+) -> Result<Vec<T>, ClientError> {
+    let meta = tx.transaction.meta.as_ref().ok_or_else(|| {
+        ClientError::TransactionParseError("Transaction status meta not found".to_string())
+    })?;
+    if meta.err.is_some() {
+        return Ok(vec![]);
+    }
+
+    let inner_instructions = meta
+        .inner_instructions
+        .as_ref()
+        .map_or(&[][..], Vec::as_slice);
+    let account_keys = if inner_instructions.is_empty() {
+        vec![]
+    } else {
+        transaction_account_keys(&tx.transaction)?
+    };
+    let parse_inner = |ix: &UiInstruction| -> Result<Option<T>, ClientError> {
+        let (ix_program_id, data) = match ix {
+            UiInstruction::Compiled(ix) => (
+                account_keys.get(ix.program_id_index as usize).copied(),
+                &ix.data,
+            ),
+            UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(ix)) => {
+                (ix.program_id.parse().ok(), &ix.data)
+            }
+            UiInstruction::Parsed(UiParsedInstruction::Parsed(_)) => return Ok(None),
+        };
+        if ix_program_id.as_ref() != Some(program_id) {
+            return Ok(None);
+        }
+
+        let data = bs58::decode(data)
+            .into_vec()
+            .map_err(|e| ClientError::TransactionParseError(e.to_string()))?;
+        parse_cpi_event(&data)
+    };
+
+    // Inner instructions of each instruction of the transaction, in invocation order
+    let mut inner_by_index = BTreeMap::new();
+    for ixs in inner_instructions {
+        inner_by_index.insert(ixs.index, ixs.instructions.iter());
+    }
+
+    let mut events = Vec::new();
+    let mut stack: Vec<Pubkey> = Vec::new();
+    let mut index = None;
+    let logs = meta.log_messages.as_ref().map_or(&[][..], Vec::as_slice);
+    for log in logs {
+        if log == LOG_TRUNCATED {
+            break;
+        }
+
+        if let Some((invoked_program_id, depth)) = parse_invoke(log) {
+            let invoked_program_id = invoked_program_id
+                .parse()
+                .map_err(|_| ClientError::LogParseError(log.to_string()))?;
+            if depth == 1 {
+                index = Some(index.map_or(0u8, |index: u8| index.wrapping_add(1)));
+            } else if let Some(ix) = index
+                .and_then(|index| inner_by_index.get_mut(&index))
+                .and_then(Iterator::next)
+            {
+                events.extend(parse_inner(ix)?);
+            }
+            stack.push(invoked_program_id);
+        } else if let Some(current) = stack.last() {
+            if let Some(data) = log
+                .strip_prefix(PROGRAM_DATA)
+                .or_else(|| log.strip_prefix(PROGRAM_LOG))
+            {
+                if current == program_id {
+                    events.extend(parse_log_event(data)?);
+                }
+            } else if parse_status(current, log).is_some() {
+                stack.pop();
+            }
+        }
+    }
+
+    // Inner instructions whose invocation is missing from the logs
+    for ix in inner_by_index.into_values().flatten() {
+        events.extend(parse_inner(ix)?);
+    }
+
+    Ok(events)
+}
+
+/// Parses an event of type `T` from the base64 data of a log made by `emit!`.
+fn parse_log_event<T: AnchorDeserialize + Discriminator>(
+    data: &str,
+) -> Result<Option<T>, ClientError> {
+    // Other logs of the program may not be valid base64
+    match STANDARD.decode(data) {
+        Ok(data) => parse_event(&data),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Parses an event of type `T` from its discriminator-prefixed data.
+fn parse_event<T: AnchorDeserialize + Discriminator>(
+    data: &[u8],
+) -> Result<Option<T>, ClientError> {
+    data.strip_prefix(T::DISCRIMINATOR)
+        .map(|mut data| {
+            T::deserialize(&mut data).map_err(|e| ClientError::LogParseError(e.to_string()))
+        })
+        .transpose()
+}
+
+/// Parses an event of type `T` from the instruction data of a self-CPI made by `emit_cpi!`.
+///
+/// Returns `None` if the data is not event CPI data, or if it belongs to another event type.
+/// The caller is responsible for checking the program ID of the instruction.
+pub fn parse_cpi_event<T: AnchorDeserialize + Discriminator>(
+    data: &[u8],
+) -> Result<Option<T>, ClientError> {
+    match data.strip_prefix(EVENT_IX_TAG_LE) {
+        Some(data) => parse_event(data),
+        None => Ok(None),
+    }
+}
+
+/// Returns the account keys of a transaction, including the ones loaded from address lookup
+/// tables, in the order the compiled instructions refer to them.
+fn transaction_account_keys(
+    tx: &EncodedTransactionWithStatusMeta,
+) -> Result<Vec<Pubkey>, ClientError> {
+    let parse_key = |key: &String| {
+        key.parse::<Pubkey>()
+            .map_err(|e| ClientError::TransactionParseError(e.to_string()))
+    };
+
+    let mut keys = match &tx.transaction {
+        EncodedTransaction::Json(ui_tx) => match &ui_tx.message {
+            UiMessage::Raw(message) => message
+                .account_keys
+                .iter()
+                .map(parse_key)
+                .collect::<Result<Vec<_>, _>>()?,
+            // Parsed messages already include the loaded addresses
+            UiMessage::Parsed(message) => {
+                return message
+                    .account_keys
+                    .iter()
+                    .map(|account| parse_key(&account.pubkey))
+                    .collect();
+            }
+        },
+        encoded_tx => encoded_tx
+            .decode()
+            .ok_or_else(|| {
+                ClientError::TransactionParseError("Unable to decode transaction".to_string())
+            })?
+            .message
+            .static_account_keys()
+            .to_vec(),
+    };
+
+    if let Some(loaded_addresses) = tx
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.loaded_addresses.as_ref().map(Clone::clone))
+    {
+        for key in loaded_addresses
+            .writable
+            .iter()
+            .chain(&loaded_addresses.readonly)
+        {
+            keys.push(parse_key(key)?);
+        }
+    }
+
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::*;
+
+    #[event]
+    pub struct MockEvent {
+        pub value: u64,
+    }
+
+    #[test]
+    fn test_parse_cpi_event() {
+        let event = MockEvent { value: 42 };
+        let data = [EVENT_IX_TAG_LE, &event.data()].concat();
+
+        let parsed = parse_cpi_event::<MockEvent>(&data).unwrap().unwrap();
+        assert_eq!(parsed.value, 42);
+
+        // Missing event instruction tag
+        assert!(parse_cpi_event::<MockEvent>(&event.data())
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_parse_transaction_events_in_execution_order() {
+        let program = Pubkey::new_unique();
+        let system = Pubkey::default();
+        let log_event = |value| {
+            format!(
+                "{PROGRAM_DATA}{}",
+                STANDARD.encode(MockEvent { value }.data())
+            )
+        };
+        let cpi_event = |value| {
+            let data = [EVENT_IX_TAG_LE, &MockEvent { value }.data()].concat();
+            serde_json::json!({
+                "programIdIndex": 1,
+                "accounts": [],
+                "data": bs58::encode(data).into_string(),
+                "stackHeight": 2,
+            })
+        };
+        let transfer = serde_json::json!({
+            "programIdIndex": 2,
+            "accounts": [],
+            "data": "",
+            "stackHeight": 2,
+        });
+        let tx = |err: serde_json::Value| -> EncodedConfirmedTransactionWithStatusMeta {
+            serde_json::from_value(serde_json::json!({
+                "slot": 0,
+                "blockTime": null,
+                "transaction": {
+                    "signatures": [],
+                    "message": {
+                        "header": {
+                            "numRequiredSignatures": 1,
+                            "numReadonlySignedAccounts": 0,
+                            "numReadonlyUnsignedAccounts": 2,
+                        },
+                        "accountKeys": [
+                            Pubkey::new_unique().to_string(),
+                            program.to_string(),
+                            system.to_string(),
+                        ],
+                        "recentBlockhash": Pubkey::default().to_string(),
+                        "instructions": [],
+                    },
+                },
+                "meta": {
+                    "err": err,
+                    "status": { "Ok": null },
+                    "fee": 0,
+                    "preBalances": [],
+                    "postBalances": [],
+                    "innerInstructions": [
+                        { "index": 0, "instructions": [cpi_event(2)] },
+                        { "index": 1, "instructions": [transfer, cpi_event(4), cpi_event(5)] },
+                    ],
+                    "logMessages": [
+                        format!("Program {program} invoke [1]"),
+                        log_event(1),
+                        format!("Program {program} invoke [2]"),
+                        format!("Program {program} success"),
+                        log_event(3),
+                        format!("Program {program} success"),
+                        format!("Program {program} invoke [1]"),
+                        format!("Program {system} invoke [2]"),
+                        // Not logged by the program
+                        log_event(6),
+                        format!("Program {system} success"),
+                        format!("Program {program} invoke [2]"),
+                        "Log truncated".to_string(),
+                    ],
+                },
+            }))
+            .unwrap()
+        };
+
+        let events = parse_transaction_events::<MockEvent>(&program, &tx(serde_json::Value::Null))
+            .unwrap()
+            .into_iter()
+            .map(|event| event.value)
+            .collect::<Vec<_>>();
+        assert_eq!(events, vec![1, 2, 3, 4, 5]);
+
+        // Failed transaction
+        let failed = tx(serde_json::json!({ "InstructionError": [1, "InvalidArgument"] }));
+        assert!(parse_transaction_events::<MockEvent>(&program, &failed)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,304 @@
+use crate::idl::{self, struct_fields, type_size};
+use crate::ClientError;
+use anchor_lang::{AnchorSerialize, Discriminator};
+use anchor_lang_idl::types::{Idl, IdlType, IdlTypeDef};
+use solana_rpc_client_api::filter::{Memcmp, RpcFilterType};
+use std::marker::PhantomData;
+
+/// Builder of `memcmp` filters for the fields of the account type `T`, with the byte offsets
+/// computed from the account's type definition in the IDL.
+///
+/// Offsets can only be computed for fields that are not preceded by a variable-size field
+/// (`String`, `Vec`, `Option`, or an enum with fields). The offsets of zero-copy types include
+/// the padding of their `#[repr(C)]` layout. Nested fields are separated with `.`, and the
+/// fields of tuple structs are referred to by their index.
+///
+/// # Example
+///
+/// ```ignore
+/// let filters = AccountFilters::<MyAccount>::new(&idl)?
+///     .filter_eq("authority", &authority)?
+///     .filter_eq("config.mint", &mint)?
+///     .build();
+/// let accounts = program.accounts::<MyAccount>(filters)?;
+/// ```
+pub struct AccountFilters<'a, T> {
+    idl: &'a Idl,
+    ty: &'a IdlTypeDef,
+    filters: Vec<RpcFilterType>,
+    _account: PhantomData<T>,
+}
+
+impl<'a, T: Discriminator> AccountFilters<'a, T> {
+    /// Creates a builder for the IDL account whose discriminator matches `T`.
+    pub fn new(idl: &'a Idl) -> Result<Self, ClientError> {
+        let account = idl
+            .accounts
+            .iter()
+            .find(|acc| acc.discriminator == T::DISCRIMINATOR)
+            .ok_or_else(|| invalid_filter("account not found in the IDL"))?;
+        let ty = find_type(idl, &account.name)?;
+
+        Ok(Self {
+            idl,
+            ty,
+            filters: vec![],
+            _account: PhantomData,
+        })
+    }
+
+    /// Adds a filter matching the accounts whose `field` is equal to `value`.
+    pub fn filter_eq<V: AnchorSerialize>(
+        mut self,
+        field: &str,
+        value: &V,
+    ) -> Result<Self, ClientError> {
+        let (offset, ty) = field_offset(self.idl, self.ty, field)?;
+        let mut bytes = vec![];
+        value.serialize(&mut bytes)?;
+        if let Some(size) = type_size(self.idl, ty) {
+            if bytes.len() != size {
+                return Err(invalid_filter(&format!(
+                    "value of `{field}` is {} bytes, expected {size}",
+                    bytes.len()
+                )));
+            }
+        }
+
+        self.filters
+            .push(RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                T::DISCRIMINATOR.len() + offset,
+                &bytes,
+            )));
+        Ok(self)
+    }
+
+    /// Returns the filters, to be used with e.g. [`Program::accounts`](crate::Program::accounts).
+    pub fn build(self) -> Vec<RpcFilterType> {
+        self.filters
+    }
+}
+
+fn invalid_filter(msg: &str) -> ClientError {
+    ClientError::InvalidAccountFilter(msg.to_string())
+}
+
+fn find_type<'a>(idl: &'a Idl, name: &str) -> Result<&'a IdlTypeDef, ClientError> {
+    idl::find_type(idl, name)
+        .ok_or_else(|| invalid_filter(&format!("type `{name}` not found in the IDL")))
+}
+
+/// Returns the offset of the given field from the start of the type, and the type of the field.
+fn field_offset<'a>(
+    idl: &'a Idl,
+    ty: &'a IdlTypeDef,
+    path: &str,
+) -> Result<(usize, &'a IdlType), ClientError> {
+    let mut offset = 0;
+    let mut ty = ty;
+    let mut segments = path.split('.').peekable();
+
+    while let Some(segment) = segments.next() {
+        if let Some(reason) = idl::unsupported_reason(ty) {
+            return Err(invalid_filter(&reason));
+        }
+        let fields = struct_fields(idl, ty)
+            .ok_or_else(|| invalid_filter(&format!("`{}` is not a struct with fields", ty.name)))?;
+
+        let mut field_ty = None;
+        for field in fields {
+            offset += field.padding;
+            if field.name == segment {
+                field_ty = Some(field.ty);
+                break;
+            }
+            offset += type_size(idl, field.ty).ok_or_else(|| {
+                let reason = match field.ty {
+                    IdlType::Defined { name, .. } => {
+                        idl::find_type(idl, name).and_then(idl::unsupported_reason)
+                    }
+                    _ => None,
+                };
+                invalid_filter(&reason.unwrap_or_else(|| {
+                    format!(
+                        "`{segment}` follows the variable-size field `{}`",
+                        field.name
+                    )
+                }))
+            })?;
+        }
+        let field_ty = field_ty.ok_or_else(|| {
+            invalid_filter(&format!("field `{segment}` not found in `{}`", ty.name))
+        })?;
+
+        if segments.peek().is_none() {
+            return Ok((offset, field_ty));
+        }
+        ty = match field_ty {
+            IdlType::Defined { name, .. } => find_type(idl, name)?,
+            _ => return Err(invalid_filter(&format!("`{segment}` has no fields"))),
+        };
+    }
+
+    Err(invalid_filter("empty field path"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang_idl::types::IdlSerialization;
+
+    struct MyAccount;
+
+    impl Discriminator for MyAccount {
+        const DISCRIMINATOR: &'static [u8] = &[1, 2, 3, 4, 5, 6, 7, 8];
+    }
+
+    fn idl() -> Idl {
+        serde_json::from_value(serde_json::json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [],
+            "accounts": [{ "name": "MyAccount", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8] }],
+            "types": [
+                {
+                    "name": "MyAccount",
+                    "type": {
+                        "kind": "struct",
+                        "fields": [
+                            { "name": "bump", "type": "u8" },
+                            { "name": "config", "type": { "defined": { "name": "Config" } } },
+                            { "name": "authority", "type": "pubkey" },
+                            { "name": "name", "type": "string" },
+                            { "name": "amount", "type": "u64" }
+                        ]
+                    }
+                },
+                {
+                    "name": "Config",
+                    "type": {
+                        "kind": "struct",
+                        "fields": [
+                            { "name": "kind", "type": { "defined": { "name": "Kind" } } },
+                            { "name": "values", "type": { "array": ["u16", 4] } },
+                            { "name": "mint", "type": "pubkey" }
+                        ]
+                    }
+                },
+                {
+                    "name": "Kind",
+                    "type": { "kind": "enum", "variants": [{ "name": "A" }, { "name": "B" }] }
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn memcmp(filter: &RpcFilterType) -> &Memcmp {
+        match filter {
+            RpcFilterType::Memcmp(memcmp) => memcmp,
+            _ => panic!("Expected memcmp filter"),
+        }
+    }
+
+    #[test]
+    fn test_account_filters() {
+        let idl = idl();
+        let pubkey = Pubkey::new_unique();
+        let filters = AccountFilters::<MyAccount>::new(&idl)
+            .unwrap()
+            .filter_eq("bump", &1u8)
+            .unwrap()
+            .filter_eq("config.mint", &pubkey)
+            .unwrap()
+            .filter_eq("authority", &pubkey)
+            .unwrap()
+            .filter_eq("name", &"test".to_string())
+            .unwrap()
+            .build();
+
+        let offsets = filters
+            .iter()
+            .map(|filter| memcmp(filter).offset())
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![8, 8 + 1 + 1 + 8, 8 + 1 + 41, 8 + 1 + 41 + 32]);
+        assert!(memcmp(&filters[2]).bytes_match(&[&[0; 50][..], pubkey.as_ref()].concat()));
+    }
+
+    #[test]
+    fn test_account_filters_errors() {
+        let idl = idl();
+        let filters = AccountFilters::<MyAccount>::new(&idl).unwrap();
+
+        // Field after a variable-size field
+        assert!(filters.filter_eq("amount", &1u64).is_err());
+
+        let filters = AccountFilters::<MyAccount>::new(&idl).unwrap();
+        // Value of the wrong size
+        assert!(filters.filter_eq("bump", &1u64).is_err());
+
+        let filters = AccountFilters::<MyAccount>::new(&idl).unwrap();
+        // Unknown field
+        assert!(filters.filter_eq("config.unknown", &1u8).is_err());
+    }
+
+    #[test]
+    fn test_account_filters_reject_unspecified_layout() {
+        let mut idl = idl();
+        idl.types[1].serialization = IdlSerialization::Bytemuck;
+
+        // Fields after a zero-copy type
+        let filters = AccountFilters::<MyAccount>::new(&idl).unwrap();
+        let err = filters
+            .filter_eq("authority", &Pubkey::default())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("zero-copy type `Config`"));
+
+        // Fields of a zero-copy type
+        let filters = AccountFilters::<MyAccount>::new(&idl).unwrap();
+        let err = filters
+            .filter_eq("config.mint", &Pubkey::default())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("zero-copy type `Config`"));
+
+        idl.types[0].serialization = IdlSerialization::Bytemuck;
+        let filters = AccountFilters::<MyAccount>::new(&idl).unwrap();
+        assert!(filters.filter_eq("bump", &1u8).is_err());
+    }
+
+    #[test]
+    fn test_account_filters_zero_copy() {
+        let mut idl = idl();
+        idl.types[0] = serde_json::from_value(serde_json::json!({
+            "name": "MyAccount",
+            "serialization": "bytemuck",
+            "repr": { "kind": "c" },
+            "type": {
+                "kind": "struct",
+                "fields": [
+                    { "name": "bump", "type": "u8" },
+                    { "name": "amount", "type": "u64" },
+                    { "name": "authority", "type": "pubkey" }
+                ]
+            }
+        }))
+        .unwrap();
+        let filters = AccountFilters::<MyAccount>::new(&idl)
+            .unwrap()
+            .filter_eq("amount", &1u64)
+            .unwrap()
+            .filter_eq("authority", &Pubkey::default())
+            .unwrap()
+            .build();
+
+        // `amount` is aligned to 8 bytes
+        let offsets = filters
+            .iter()
+            .map(|filter| memcmp(filter).offset())
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![8 + 8, 8 + 16]);
+    }
+}
@@ -0,0 +1,774 @@
+//! Helpers to work with the Borsh and zero-copy layouts of the types defined in an IDL.
+
+use crate::ClientError;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang_idl::types::{
+    Idl, IdlArrayLen, IdlDefinedFields, IdlRepr, IdlSerialization, IdlType, IdlTypeDef,
+    IdlTypeDefTy,
+};
+use serde_json::Value;
+
+/// Returns the type definition with the given name.
+pub(crate) fn find_type<'a>(idl: &'a Idl, name: &str) -> Option<&'a IdlTypeDef> {
+    idl.types.iter().find(|ty| ty.name == name)
+}
+
+/// Field of a struct or enum variant, with the padding bytes that precede it in zero-copy
+/// layouts.
+#[derive(Debug, Clone)]
+pub(crate) struct Field<'a> {
+    pub name: String,
+    pub ty: &'a IdlType,
+    pub padding: usize,
+}
+
+/// Fields of a type definition, and the padding bytes that follow them in zero-copy layouts.
+#[derive(Debug, Clone)]
+pub(crate) struct Layout<'a> {
+    pub fields: Vec<Field<'a>>,
+    pub trailing_padding: usize,
+    /// Alignment of the type, only meaningful for zero-copy types.
+    pub align: usize,
+}
+
+impl<'a> Layout<'a> {
+    /// Returns the Borsh layout of the given fields, which has no padding.
+    pub(crate) fn borsh(fields: Option<&'a IdlDefinedFields>) -> Self {
+        Self {
+            fields: fields.map(defined_fields).unwrap_or_default(),
+            trailing_padding: 0,
+            align: 1,
+        }
+    }
+}
+
+/// Returns the fields of the given fields definition, with tuple fields named by their index.
+pub(crate) fn defined_fields(fields: &IdlDefinedFields) -> Vec<Field<'_>> {
+    let field = |name, ty| Field {
+        name,
+        ty,
+        padding: 0,
+    };
+    match fields {
+        IdlDefinedFields::Named(fields) => fields
+            .iter()
+            .map(|f| field(f.name.clone(), &f.ty))
+            .collect(),
+        IdlDefinedFields::Tuple(fields) => fields
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| field(i.to_string(), ty))
+            .collect(),
+    }
+}
+
+/// Returns the fields of the given struct, or `None` if the type is not a struct with fields or
+/// its layout is not supported.
+pub(crate) fn struct_fields<'a>(idl: &'a Idl, ty: &'a IdlTypeDef) -> Option<Vec<Field<'a>>> {
+    match &ty.ty {
+        IdlTypeDefTy::Struct { fields: Some(_) } => struct_layout(idl, ty).map(|l| l.fields),
+        _ => None,
+    }
+}
+
+/// Returns the layout of the given struct, or `None` if the type is not a struct or its layout
+/// is not supported.
+///
+/// Zero-copy structs are laid out by their `repr`: `#[repr(C)]` fields are padded to their
+/// alignment, while packed and transparent structs have no padding.
+pub(crate) fn struct_layout<'a>(idl: &'a Idl, ty: &'a IdlTypeDef) -> Option<Layout<'a>> {
+    if !is_supported(ty) {
+        return None;
+    }
+    let IdlTypeDefTy::Struct { fields } = &ty.ty else {
+        return None;
+    };
+
+    let mut layout = Layout::borsh(fields.as_ref());
+    match (&ty.serialization, &ty.repr) {
+        (IdlSerialization::Borsh, _) => {}
+        (_, Some(IdlRepr::C(modifier))) if !modifier.packed => {
+            let mut offset = 0usize;
+            let mut align = modifier.align.unwrap_or(1);
+            for field in &mut layout.fields {
+                let field_align = type_align(idl, field.ty)?;
+                field.padding = offset.next_multiple_of(field_align) - offset;
+                offset += field.padding + type_size(idl, field.ty)?;
+                align = align.max(field_align);
+            }
+            layout.trailing_padding = offset.next_multiple_of(align) - offset;
+            layout.align = align;
+        }
+        (_, Some(IdlRepr::Transparent)) => {
+            layout.align = layout
+                .fields
+                .iter()
+                .map(|field| type_align(idl, field.ty))
+                .try_fold(1, |align, field_align| Some(align.max(field_align?)))?;
+        }
+        // Packed structs have no padding and are aligned to 1 byte
+        _ => {
+            for field in &layout.fields {
+                type_align(idl, field.ty)?;
+            }
+        }
+    }
+    Some(layout)
+}
+
+/// Returns the alignment of the given type in zero-copy layouts, as on the SBF target that
+/// programs are compiled for, or `None` if the type can't be part of a zero-copy layout.
+fn type_align(idl: &Idl, ty: &IdlType) -> Option<usize> {
+    let align = match ty {
+        IdlType::Bool | IdlType::U8 | IdlType::I8 | IdlType::Pubkey => 1,
+        IdlType::U16 | IdlType::I16 => 2,
+        IdlType::U32 | IdlType::I32 | IdlType::F32 => 4,
+        IdlType::U64 | IdlType::I64 | IdlType::F64 => 8,
+        IdlType::U128 | IdlType::I128 => 16,
+        IdlType::Array(ty, IdlArrayLen::Value(_)) => type_align(idl, ty)?,
+        IdlType::Defined { name, generics } if generics.is_empty() => {
+            let ty = find_type(idl, name).filter(|ty| is_supported(ty))?;
+            match (&ty.serialization, &ty.ty) {
+                (IdlSerialization::Borsh, IdlTypeDefTy::Type { alias }) => type_align(idl, alias)?,
+                (IdlSerialization::Borsh, _) => return None,
+                _ => struct_layout(idl, ty)?.align,
+            }
+        }
+        _ => return None,
+    };
+    Some(align)
+}
+
+/// Whether the layout of the given type can be computed from its definition.
+fn is_supported(ty: &IdlTypeDef) -> bool {
+    unsupported_reason(ty).is_none()
+}
+
+/// Returns why the layout of the given type can't be computed from its definition, if it can't.
+///
+/// Generic types are not supported, and neither are types with custom serialization. Zero-copy
+/// (`bytemuck`) types must be `#[repr(C)]`, packed or transparent, since the `#[repr(Rust)]`
+/// layout is unspecified.
+pub(crate) fn unsupported_reason(ty: &IdlTypeDef) -> Option<String> {
+    if !ty.generics.is_empty() {
+        return Some(format!("generic type `{}` is not supported", ty.name));
+    }
+    match (&ty.serialization, &ty.repr) {
+        (IdlSerialization::Borsh, _) => None,
+        (
+            IdlSerialization::Bytemuck | IdlSerialization::BytemuckUnsafe,
+            Some(IdlRepr::C(_) | IdlRepr::Transparent),
+        ) => None,
+        (
+            IdlSerialization::Bytemuck | IdlSerialization::BytemuckUnsafe,
+            Some(IdlRepr::Rust(modifier)),
+        ) if modifier.packed => None,
+        (IdlSerialization::Bytemuck | IdlSerialization::BytemuckUnsafe, _) => Some(format!(
+            "zero-copy type `{}` is not supported, its layout is unspecified without `#[repr(C)]`",
+            ty.name
+        )),
+        _ => Some(format!(
+            "type `{}` with custom serialization is not supported",
+            ty.name
+        )),
+    }
+}
+
+/// Returns the serialized size of the given type, or `None` if the size is variable.
+pub(crate) fn type_size(idl: &Idl, ty: &IdlType) -> Option<usize> {
+    let size = match ty {
+        IdlType::Bool | IdlType::U8 | IdlType::I8 => 1,
+        IdlType::U16 | IdlType::I16 => 2,
+        IdlType::U32 | IdlType::I32 | IdlType::F32 => 4,
+        IdlType::U64 | IdlType::I64 | IdlType::F64 => 8,
+        IdlType::U128 | IdlType::I128 => 16,
+        IdlType::U256 | IdlType::I256 | IdlType::Pubkey => 32,
+        IdlType::Array(ty, IdlArrayLen::Value(len)) => type_size(idl, ty)? * len,
+        IdlType::Defined { name, generics } if generics.is_empty() => {
+            let ty = find_type(idl, name).filter(|ty| is_supported(ty))?;
+            match &ty.ty {
+                IdlTypeDefTy::Struct { .. } => {
+                    let layout = struct_layout(idl, ty)?;
+                    layout
+                        .fields
+                        .iter()
+                        .map(|field| Some(field.padding + type_size(idl, field.ty)?))
+                        .sum::<Option<usize>>()?
+                        + layout.trailing_padding
+                }
+                // Only enums without fields have a fixed size
+                IdlTypeDefTy::Enum { variants } => match ty.serialization {
+                    IdlSerialization::Borsh if variants.iter().all(|v| v.fields.is_none()) => 1,
+                    _ => return None,
+                },
+                IdlTypeDefTy::Type { alias } => type_size(idl, alias)?,
+            }
+        }
+        _ => return None,
+    };
+    Some(size)
+}
+
+/// Advances `data` past a serialized value of the given type.
+///
+/// Returns `None` if the data is too short or the type is not supported.
+pub(crate) fn skip_value(idl: &Idl, ty: &IdlType, data: &mut &[u8]) -> Option<()> {
+    fn advance(data: &mut &[u8], len: usize) -> Option<()> {
+        *data = data.get(len..)?;
+        Some(())
+    }
+
+    fn read_len(data: &mut &[u8]) -> Option<usize> {
+        let len = u32::from_le_bytes(data.get(..4)?.try_into().ok()?);
+        advance(data, 4)?;
+        Some(len as usize)
+    }
+
+    if let Some(size) = type_size(idl, ty) {
+        return advance(data, size);
+    }
+
+    match ty {
+        IdlType::Bytes | IdlType::String => {
+            let len = read_len(data)?;
+            advance(data, len)
+        }
+        IdlType::Option(ty) => {
+            let is_some = *data.first()?;
+            advance(data, 1)?;
+            match is_some {
+                0 => Some(()),
+                _ => skip_value(idl, ty, data),
+            }
+        }
+        IdlType::Vec(ty) => {
+            let len = read_len(data)?;
+            (0..len).try_for_each(|_| skip_value(idl, ty, data))
+        }
+        IdlType::Array(ty, IdlArrayLen::Value(len)) => {
+            (0..*len).try_for_each(|_| skip_value(idl, ty, data))
+        }
+        IdlType::Defined { name, generics } if generics.is_empty() => {
+            let ty = find_type(idl, name).filter(|ty| is_supported(ty))?;
+            match &ty.ty {
+                IdlTypeDefTy::Struct { .. } => {
+                    let layout = struct_layout(idl, ty)?;
+                    for field in layout.fields {
+                        advance(data, field.padding)?;
+                        skip_value(idl, field.ty, data)?;
+                    }
+                    advance(data, layout.trailing_padding)
+                }
+                IdlTypeDefTy::Enum { variants } => {
+                    let variant = variants.get(*data.first()? as usize)?;
+                    advance(data, 1)?;
+                    variant
+                        .fields
+                        .iter()
+                        .flat_map(defined_fields)
+                        .try_for_each(|field| skip_value(idl, field.ty, data))
+                }
+                IdlTypeDefTy::Type { alias } => skip_value(idl, alias, data),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns the serialized bytes and the type of the field at the given path, e.g. `a.b.c`,
+/// within the given serialized fields.
+pub(crate) fn field_bytes<'a, 'd>(
+    idl: &'a Idl,
+    fields: Vec<Field<'a>>,
+    path: &str,
+    data: &'d [u8],
+) -> Option<(&'d [u8], &'a IdlType)> {
+    let (name, subpath) = match path.split_once('.') {
+        Some((name, subpath)) => (name, Some(subpath)),
+        None => (path, None),
+    };
+
+    let mut data = data;
+    for Field {
+        name: field_name,
+        ty,
+        padding,
+    } in fields
+    {
+        data = data.get(padding..)?;
+        let start = data;
+        skip_value(idl, ty, &mut data)?;
+        if field_name != name {
+            continue;
+        }
+
+        let value = &start[..start.len() - data.len()];
+        return match subpath {
+            None => Some((value, ty)),
+            Some(subpath) => match ty {
+                IdlType::Defined { name, .. } => {
+                    let fields = struct_fields(idl, find_type(idl, name)?)?;
+                    field_bytes(idl, fields, subpath, value)
+                }
+                _ => None,
+            },
+        };
+    }
+
+    None
+}
+
+/// Serializes the JSON value of the given type.
+pub(crate) fn encode_value(
+    idl: &Idl,
+    ty: &IdlType,
+    value: &Value,
+    data: &mut Vec<u8>,
+) -> Result<(), ClientError> {
+    macro_rules! encode_int {
+        ($ty:ty) => {{
+            let n = match value {
+                Value::Number(n) => n.to_string().parse::<$ty>().ok(),
+                Value::String(s) => s.parse::<$ty>().ok(),
+                _ => None,
+            }
+            .ok_or_else(|| invalid_value(ty, value))?;
+            data.extend_from_slice(&n.to_le_bytes());
+        }};
+    }
+
+    match ty {
+        IdlType::Bool => data.push(value.as_bool().ok_or_else(|| invalid_value(ty, value))? as u8),
+        IdlType::U8 => encode_int!(u8),
+        IdlType::I8 => encode_int!(i8),
+        IdlType::U16 => encode_int!(u16),
+        IdlType::I16 => encode_int!(i16),
+        IdlType::U32 => encode_int!(u32),
+        IdlType::I32 => encode_int!(i32),
+        IdlType::U64 => encode_int!(u64),
+        IdlType::I64 => encode_int!(i64),
+        IdlType::U128 => encode_int!(u128),
+        IdlType::I128 => encode_int!(i128),
+        IdlType::F32 => {
+            let n = value.as_f64().ok_or_else(|| invalid_value(ty, value))?;
+            data.extend_from_slice(&(n as f32).to_le_bytes());
+        }
+        IdlType::F64 => {
+            let n = value.as_f64().ok_or_else(|| invalid_value(ty, value))?;
+            data.extend_from_slice(&n.to_le_bytes());
+        }
+        IdlType::String => {
+            let s = value.as_str().ok_or_else(|| invalid_value(ty, value))?;
+            data.extend_from_slice(&(s.len() as u32).to_le_bytes());
+            data.extend_from_slice(s.as_bytes());
+        }
+        IdlType::Bytes => {
+            let bytes = serde_json::from_value::<Vec<u8>>(value.clone())
+                .map_err(|_| invalid_value(ty, value))?;
+            data.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            data.extend_from_slice(&bytes);
+        }
+        IdlType::Pubkey => {
+            let pubkey = value
+                .as_str()
+                .and_then(|s| s.parse::<Pubkey>().ok())
+                .ok_or_else(|| invalid_value(ty, value))?;
+            data.extend_from_slice(pubkey.as_ref());
+        }
+        IdlType::Option(inner) => match value {
+            Value::Null => data.push(0),
+            value => {
+                data.push(1);
+                encode_value(idl, inner, value, data)?;
+            }
+        },
+        IdlType::Vec(inner) => {
+            let values = value.as_array().ok_or_else(|| invalid_value(ty, value))?;
+            data.extend_from_slice(&(values.len() as u32).to_le_bytes());
+            for value in values {
+                encode_value(idl, inner, value, data)?;
+            }
+        }
+        IdlType::Array(inner, IdlArrayLen::Value(len)) => {
+            let values = value
+                .as_array()
+                .filter(|values| values.len() == *len)
+                .ok_or_else(|| invalid_value(ty, value))?;
+            for value in values {
+                encode_value(idl, inner, value, data)?;
+            }
+        }
+        IdlType::Defined { name, generics } if generics.is_empty() => {
+            let ty_def = find_type(idl, name).ok_or_else(|| unsupported_type(ty))?;
+            if let Some(reason) = unsupported_reason(ty_def) {
+                return Err(coder_error(reason));
+            }
+            match &ty_def.ty {
+                IdlTypeDefTy::Struct { fields } => {
+                    let layout = struct_layout(idl, ty_def).ok_or_else(|| unsupported_type(ty))?;
+                    encode_fields(idl, fields.as_ref(), &layout, value, data)?;
+                }
+                IdlTypeDefTy::Enum { variants } => {
+                    // Unit variants can be specified by name only
+                    let (variant_name, fields_value) = match value {
+                        Value::String(variant_name) => (variant_name, &Value::Null),
+                        Value::Object(object) if object.len() == 1 => object.iter().next().unwrap(),
+                        _ => return Err(invalid_value(ty, value)),
+                    };
+                    let (index, variant) = variants
+                        .iter()
+                        .enumerate()
+                        .find(|(_, variant)| &variant.name == variant_name)
+                        .ok_or_else(|| invalid_value(ty, value))?;
+                    data.push(index as u8);
+                    let fields = variant.fields.as_ref();
+                    encode_fields(idl, fields, &Layout::borsh(fields), fields_value, data)?;
+                }
+                IdlTypeDefTy::Type { alias } => encode_value(idl, alias, value, data)?,
+            }
+        }
+        _ => return Err(unsupported_type(ty)),
+    }
+
+    Ok(())
+}
+
+/// Serializes the JSON value of the given fields, an object for named fields or an array for
+/// tuple fields, with the padding of the given layout.
+pub(crate) fn encode_fields(
+    idl: &Idl,
+    fields: Option<&IdlDefinedFields>,
+    layout: &Layout,
+    value: &Value,
+    data: &mut Vec<u8>,
+) -> Result<(), ClientError> {
+    let values = match fields {
+        None => vec![],
+        Some(IdlDefinedFields::Named(_)) => layout
+            .fields
+            .iter()
+            .map(|field| {
+                value
+                    .get(&field.name)
+                    .ok_or_else(|| coder_error(format!("missing field `{}`", field.name)))
+            })
+            .collect::<Result<_, _>>()?,
+        Some(IdlDefinedFields::Tuple(types)) => value
+            .as_array()
+            .filter(|values| values.len() == types.len())
+            .ok_or_else(|| coder_error(format!("expected {} tuple fields", types.len())))?
+            .iter()
+            .collect(),
+    };
+
+    for (field, value) in layout.fields.iter().zip(values) {
+        data.resize(data.len() + field.padding, 0);
+        encode_value(idl, field.ty, value, data)?;
+    }
+    data.resize(data.len() + layout.trailing_padding, 0);
+    Ok(())
+}
+
+/// Deserializes a value of the given type into JSON.
+///
+/// Integers larger than 64 bits are returned as strings so that no precision is lost.
+pub(crate) fn decode_value(
+    idl: &Idl,
+    ty: &IdlType,
+    data: &mut &[u8],
+) -> Result<Value, ClientError> {
+    fn take<'d>(data: &mut &'d [u8], len: usize) -> Result<&'d [u8], ClientError> {
+        let bytes = data
+            .get(..len)
+            .ok_or_else(|| coder_error("unexpected end of data".to_string()))?;
+        *data = &data[len..];
+        Ok(bytes)
+    }
+
+    macro_rules! decode_int {
+        ($ty:ty) => {{
+            let bytes = take(data, std::mem::size_of::<$ty>())?;
+            <$ty>::from_le_bytes(bytes.try_into().unwrap())
+        }};
+    }
+
+    let value = match ty {
+        IdlType::Bool => Value::Bool(decode_int!(u8) != 0),
+        IdlType::U8 => decode_int!(u8).into(),
+        IdlType::I8 => decode_int!(i8).into(),
+        IdlType::U16 => decode_int!(u16).into(),
+        IdlType::I16 => decode_int!(i16).into(),
+        IdlType::U32 => decode_int!(u32).into(),
+        IdlType::I32 => decode_int!(i32).into(),
+        IdlType::U64 => decode_int!(u64).into(),
+        IdlType::I64 => decode_int!(i64).into(),
+        IdlType::U128 => decode_int!(u128).to_string().into(),
+        IdlType::I128 => decode_int!(i128).to_string().into(),
+        IdlType::F32 => decode_int!(f32).into(),
+        IdlType::F64 => decode_int!(f64).into(),
+        IdlType::String => {
+            let len = decode_int!(u32) as usize;
+            String::from_utf8(take(data, len)?.to_vec())
+                .map_err(|e| coder_error(e.to_string()))?
+                .into()
+        }
+        IdlType::Bytes => {
+            let len = decode_int!(u32) as usize;
+            take(data, len)?.to_vec().into()
+        }
+        IdlType::Pubkey => Pubkey::try_from(take(data, 32)?)
+            .unwrap()
+            .to_string()
+            .into(),
+        IdlType::Option(inner) => match decode_int!(u8) {
+            0 => Value::Null,
+            _ => decode_value(idl, inner, data)?,
+        },
+        IdlType::Vec(inner) => {
+            let len = decode_int!(u32);
+            (0..len)
+                .map(|_| decode_value(idl, inner, data))
+                .collect::<Result<_, _>>()?
+        }
+        IdlType::Array(inner, IdlArrayLen::Value(len)) => (0..*len)
+            .map(|_| decode_value(idl, inner, data))
+            .collect::<Result<_, _>>()?,
+        IdlType::Defined { name, generics } if generics.is_empty() => {
+            let ty_def = find_type(idl, name).ok_or_else(|| unsupported_type(ty))?;
+            if let Some(reason) = unsupported_reason(ty_def) {
+                return Err(coder_error(reason));
+            }
+            match &ty_def.ty {
+                IdlTypeDefTy::Struct { fields } => {
+                    let layout = struct_layout(idl, ty_def).ok_or_else(|| unsupported_type(ty))?;
+                    decode_fields(idl, fields.as_ref(), &layout, data)?
+                }
+                IdlTypeDefTy::Enum { variants } => {
+                    let index = decode_int!(u8);
+                    let variant = variants.get(index as usize).ok_or_else(|| {
+                        coder_error(format!("invalid variant index {index} of `{name}`"))
+                    })?;
+                    let fields = variant.fields.as_ref();
+                    let fields = decode_fields(idl, fields, &Layout::borsh(fields), data)?;
+                    serde_json::json!({ variant.name.clone(): fields })
+                }
+                IdlTypeDefTy::Type { alias } => decode_value(idl, alias, data)?,
+            }
+        }
+        _ => return Err(unsupported_type(ty)),
+    };
+
+    Ok(value)
+}
+
+/// Deserializes the given fields into JSON, an object for named fields or an array for tuple
+/// fields, skipping the padding of the given layout.
+pub(crate) fn decode_fields(
+    idl: &Idl,
+    fields: Option<&IdlDefinedFields>,
+    layout: &Layout,
+    data: &mut &[u8],
+) -> Result<Value, ClientError> {
+    fn skip_padding(data: &mut &[u8], len: usize) -> Result<(), ClientError> {
+        *data = data
+            .get(len..)
+            .ok_or_else(|| coder_error("unexpected end of data".to_string()))?;
+        Ok(())
+    }
+
+    let mut values = Vec::with_capacity(layout.fields.len());
+    for field in &layout.fields {
+        skip_padding(data, field.padding)?;
+        values.push((field.name.clone(), decode_value(idl, field.ty, data)?));
+    }
+    skip_padding(data, layout.trailing_padding)?;
+
+    Ok(match fields {
+        Some(IdlDefinedFields::Tuple(_)) => {
+            Value::Array(values.into_iter().map(|(_, value)| value).collect())
+        }
+        _ => Value::Object(values.into_iter().collect()),
+    })
+}
+
+fn coder_error(msg: String) -> ClientError {
+    ClientError::IdlCoderError(msg)
+}
+
+fn invalid_value(ty: &IdlType, value: &Value) -> ClientError {
+    coder_error(format!("invalid value for type {ty:?}: {value}"))
+}
+
+fn unsupported_type(ty: &IdlType) -> ClientError {
+    coder_error(format!("unsupported type {ty:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AnchorSerialize;
+
+    #[test]
+    fn test_field_bytes() {
+        let idl: Idl = serde_json::from_value(serde_json::json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [],
+            "types": [
+                {
+                    "name": "Kind",
+                    "type": {
+                        "kind": "enum",
+                        "variants": [{ "name": "A" }, { "name": "B", "fields": ["u16"] }]
+                    }
+                },
+                {
+                    "name": "Inner",
+                    "type": { "kind": "struct", "fields": [{ "name": "key", "type": "pubkey" }] }
+                }
+            ]
+        }))
+        .unwrap();
+        let fields = [
+            ("name", IdlType::String),
+            ("maybe", IdlType::Option(Box::new(IdlType::U8))),
+            ("list", IdlType::Vec(Box::new(IdlType::U32))),
+            (
+                "kind",
+                IdlType::Defined {
+                    name: "Kind".into(),
+                    generics: vec![],
+                },
+            ),
+            (
+                "inner",
+                IdlType::Defined {
+                    name: "Inner".into(),
+                    generics: vec![],
+                },
+            ),
+        ];
+        let fields = || {
+            fields
+                .iter()
+                .map(|(name, ty)| Field {
+                    name: name.to_string(),
+                    ty,
+                    padding: 0,
+                })
+                .collect()
+        };
+
+        let key = Pubkey::new_unique();
+        let mut data = vec![];
+        ("abc", Some(1u8), vec![1u32, 2], 1u8, 5u16, key)
+            .serialize(&mut data)
+            .unwrap();
+
+        let (bytes, ty) = field_bytes(&idl, fields(), "name", &data).unwrap();
+        assert_eq!(bytes, [3, 0, 0, 0, b'a', b'b', b'c']);
+        assert_eq!(ty, &IdlType::String);
+        let (bytes, _) = field_bytes(&idl, fields(), "kind", &data).unwrap();
+        assert_eq!(bytes, [1, 5, 0]);
+        let (bytes, ty) = field_bytes(&idl, fields(), "inner.key", &data).unwrap();
+        assert_eq!(bytes, key.as_ref());
+        assert_eq!(ty, &IdlType::Pubkey);
+
+        assert!(field_bytes(&idl, fields(), "unknown", &data).is_none());
+        assert!(field_bytes(&idl, fields(), "inner.key", &data[..data.len() - 1]).is_none());
+    }
+
+    fn zero_copy_idl(repr: Value) -> Idl {
+        serde_json::from_value(serde_json::json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [],
+            "types": [
+                {
+                    "name": "Padded",
+                    "serialization": "bytemuck",
+                    "repr": repr,
+                    "type": {
+                        "kind": "struct",
+                        "fields": [
+                            { "name": "a", "type": "u8" },
+                            { "name": "b", "type": "u64" },
+                            { "name": "c", "type": "u16" }
+                        ]
+                    }
+                },
+                {
+                    "name": "Wrapper",
+                    "serialization": "bytemuck",
+                    "repr": { "kind": "transparent" },
+                    "type": { "kind": "struct", "fields": [{ "defined": { "name": "Padded" } }] }
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn defined(name: &str) -> IdlType {
+        IdlType::Defined {
+            name: name.into(),
+            generics: vec![],
+        }
+    }
+
+    #[test]
+    fn test_zero_copy_c_layout() {
+        let idl = zero_copy_idl(serde_json::json!({ "kind": "c" }));
+        let ty = defined("Padded");
+        let value = serde_json::json!({ "a": 1, "b": 2, "c": 3 });
+
+        // `b` is aligned to 8 bytes, and the size is a multiple of the alignment
+        let mut data = vec![];
+        encode_value(&idl, &ty, &value, &mut data).unwrap();
+        let mut expected = [0u8; 24];
+        expected[0] = 1;
+        expected[8] = 2;
+        expected[16] = 3;
+        assert_eq!(data, expected);
+        assert_eq!(type_size(&idl, &ty), Some(24));
+        assert_eq!(decode_value(&idl, &ty, &mut &data[..]).unwrap(), value);
+
+        let fields = struct_fields(&idl, &idl.types[0]).unwrap();
+        let (bytes, _) = field_bytes(&idl, fields, "c", &data).unwrap();
+        assert_eq!(bytes, [3, 0]);
+
+        // Transparent wrappers have the layout of their field
+        let wrapper = defined("Wrapper");
+        assert_eq!(type_size(&idl, &wrapper), Some(24));
+        let decoded = decode_value(&idl, &wrapper, &mut &data[..]).unwrap();
+        assert_eq!(decoded, serde_json::json!([value]));
+
+        // `align` raises the alignment of the struct
+        let idl = zero_copy_idl(serde_json::json!({ "kind": "c", "align": 32 }));
+        assert_eq!(type_size(&idl, &ty), Some(32));
+    }
+
+    #[test]
+    fn test_zero_copy_packed_layout() {
+        let idl = zero_copy_idl(serde_json::json!({ "kind": "rust", "packed": true }));
+        let ty = defined("Padded");
+        let value = serde_json::json!({ "a": 1, "b": 2, "c": 3 });
+
+        let mut data = vec![];
+        encode_value(&idl, &ty, &value, &mut data).unwrap();
+        assert_eq!(data, [1, 2, 0, 0, 0, 0, 0, 0, 0, 3, 0]);
+        assert_eq!(type_size(&idl, &ty), Some(11));
+        assert_eq!(decode_value(&idl, &ty, &mut &data[..]).unwrap(), value);
+    }
+
+    #[test]
+    fn test_zero_copy_unspecified_layout_is_rejected() {
+        let idl = zero_copy_idl(serde_json::json!({ "kind": "rust" }));
+        let ty = defined("Padded");
+
+        assert!(type_size(&idl, &ty).is_none());
+        let err = decode_value(&idl, &ty, &mut &[0; 24][..]).unwrap_err();
+        assert!(err.to_string().contains("zero-copy type `Padded`"));
+        let value = serde_json::json!({ "a": 1, "b": 2, "c": 3 });
+        assert!(encode_value(&idl, &ty, &value, &mut vec![]).is_err());
+    }
+}
@@ -68,53 +68,110 @@
 //! useful for mocking RPC responses, e.g. via [`RpcClient::new_mock`].
 //!
 //! [`RpcClient::new_mock`]: https://docs.rs/solana-rpc-client/3.0.0/solana_rpc_client/rpc_client/struct.RpcClient.html#method.new_mock
+//!
+//! ## `surfnet`
+//!
+//! This feature adds the `surfnet_*` methods to [`Program`], which call the cheatcode RPC methods
+//! of [Surfpool] local validators to set up the state of tests without sending transactions, e.g.
+//! setting accounts and token balances, or moving the clock forward.
+//!
+//! [Surfpool]: https://github.com/txtx/surfpool
 
+use anchor_lang::error::{AnchorError, ComparedValues, ErrorCode, ErrorOrigin};
 use anchor_lang::solana_program::program_error::ProgramError;
 use anchor_lang::solana_program::pubkey::Pubkey;
-use anchor_lang::{AccountDeserialize, Discriminator, InstructionData, ToAccountMetas};
-use futures::{Future, StreamExt};
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::{
+    AccountDeserialize, AnchorDeserialize, AnchorSerialize, Discriminator, InstructionData,
+    ToAccountMetas, ZeroCopy,
+};
+use anchor_lang_idl::types::Idl;
+use futures::{Future, Stream, StreamExt, TryStreamExt};
 use regex::Regex;
+use resolver::AccountResolver;
+use solana_account::Account;
 use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_address_lookup_table_interface::state::AddressLookupTable;
 use solana_commitment_config::CommitmentConfig;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
 use solana_instruction::{AccountMeta, Instruction};
+use solana_message::{v0, AddressLookupTableAccount, CompileError, Message, VersionedMessage};
 use solana_program::hash::Hash;
 use solana_pubsub_client::nonblocking::pubsub_client::{PubsubClient, PubsubClientError};
-use solana_rpc_client::nonblocking::rpc_client::RpcClient as AsyncRpcClient;
+use solana_rpc_client::{
+    nonblocking::rpc_client::RpcClient as AsyncRpcClient,
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+};
 use solana_rpc_client_api::{
     client_error::Error as SolanaClientError,
     config::{
         RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSendTransactionConfig,
-        RpcTransactionLogsConfig, RpcTransactionLogsFilter,
+        RpcSimulateTransactionConfig, RpcTransactionConfig, RpcTransactionLogsConfig,
+        RpcTransactionLogsFilter,
     },
     filter::{Memcmp, RpcFilterType},
-    response::{Response as RpcResponse, RpcLogsResponse},
+    request::{MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT, MAX_MULTIPLE_ACCOUNTS},
+    response::{
+        Response as RpcResponse, RpcLogsResponse, RpcPrioritizationFee,
+        RpcSimulateTransactionResult, TransactionError, UiTransactionReturnData,
+    },
 };
+use solana_rpc_client_nonce_utils::nonblocking as nonce_utils;
 use solana_signature::Signature;
 use solana_signer::{Signer, SignerError};
-use solana_transaction::Transaction;
+use solana_transaction::{versioned::VersionedTransaction, Transaction};
+use solana_transaction_status_client_types::{TransactionStatus, UiTransactionEncoding};
 use std::iter::Map;
 use std::marker::PhantomData;
-use std::ops::Deref;
+use std::mem;
+use std::ops::{Deref, Range};
 use std::pin::Pin;
 use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 use std::vec::IntoIter;
 use thiserror::Error;
 use tokio::{
     runtime::Handle,
     sync::{
         mpsc::{unbounded_channel, UnboundedReceiver},
-        RwLock,
+        oneshot, RwLock,
     },
     task::JoinHandle,
 };
+use transport::{SharedSender, TransportSender};
 
 pub use anchor_lang;
-pub use cluster::Cluster;
+pub use cluster::{Cluster, ClusterRegistry, Endpoint};
+pub use dynamic::{DecodedValue, DynamicProgram};
+pub use error::{DecodedError, ErrorRegistry};
+pub use events::{parse_cpi_event, parse_transaction_events};
+pub use filter::AccountFilters;
+pub use logs::{parse_invocations, Invocation, InvocationStatus};
 #[cfg(feature = "async")]
 pub use nonblocking::ThreadSafeSigner;
+pub use offline::{
+    decode_transaction, encode_transaction, merge_signature_files, merge_signatures,
+    missing_signers, TransactionEncoding,
+};
+pub use resolver::ResolvedAccounts;
 pub use solana_account_decoder;
+#[cfg(feature = "surfnet")]
+pub use surfnet::TimeTravel;
+pub use transport::Transport;
 
 mod cluster;
+mod dynamic;
+mod error;
+mod events;
+mod filter;
+mod idl;
+mod logs;
+mod offline;
+mod resolver;
+#[cfg(feature = "surfnet")]
+mod surfnet;
+mod transport;
 
 #[cfg(not(feature = "async"))]
 mod blocking;
@@ -123,6 +180,18 @@ mod nonblocking;
 
 const PROGRAM_LOG: &str = "Program log: ";
 const PROGRAM_DATA: &str = "Program data: ";
+/// Maximum size of the data a program can return via `set_return_data`.
+const MAX_RETURN_DATA: usize = 1024;
+/// Maximum compute unit limit a transaction can request.
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// Maximum number of accounts `getRecentPrioritizationFees` accepts.
+const MAX_PRIORITIZATION_FEE_ACCOUNTS: usize = 128;
+/// Discriminator of the `SetComputeUnitLimit` compute budget instruction.
+const SET_COMPUTE_UNIT_LIMIT_DISCRIMINATOR: u8 = 2;
+/// Discriminator of the `SetComputeUnitPrice` compute budget instruction.
+const SET_COMPUTE_UNIT_PRICE_DISCRIMINATOR: u8 = 3;
+/// Maximum size of a serialized transaction, i.e. the packet data size.
+const MAX_TRANSACTION_SIZE: usize = 1232;
 
 type UnsubscribeFn = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
 /// Client defines the base configuration for building RPC clients to
@@ -171,6 +240,22 @@ impl<C: Clone + Deref<Target = impl Signer>> Client<C> {
             rpc_client,
         )
     }
+
+    /// Creates a program client whose RPC requests are served by the given transport instead of
+    /// the cluster, e.g. to run tests in-process, see [`Transport`].
+    pub fn program_with_transport(
+        &self,
+        program_id: Pubkey,
+        transport: impl Transport,
+    ) -> Result<Program<C>, ClientError> {
+        let cfg = Config {
+            cluster: self.cfg.cluster.clone(),
+            options: self.cfg.options,
+            payer: self.cfg.payer.clone(),
+        };
+
+        Program::new_with_transport(program_id, cfg, transport)
+    }
 }
 
 /// Auxiliary data structure to align the types of the Solana CLI utils with Anchor client.
@@ -226,6 +311,100 @@ impl EventUnsubscriber<'_> {
     }
 }
 
+/// Reconnection configuration of an [`EventStream`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventStreamConfig {
+    /// Delay before the first resubscription attempt after the subscription is interrupted.
+    pub initial_backoff: Duration,
+    /// Maximum delay between resubscription attempts. The delay doubles after each failed
+    /// attempt until it reaches this value.
+    pub max_backoff: Duration,
+}
+
+impl Default for EventStreamConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Order of the transactions returned by [`Program::backfill_events`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BackfillOrder {
+    /// From the newest to the oldest transaction, as the signatures are paged. Events are
+    /// returned as soon as their page is fetched.
+    #[default]
+    NewestFirst,
+    /// From the oldest to the newest transaction, to replay the events chronologically. All the
+    /// signatures of the range are paged before the first transaction is fetched, so the range
+    /// should be bounded with `until` or `min_slot`.
+    OldestFirst,
+}
+
+/// Range, order and concurrency of the transaction history searched by
+/// [`Program::backfill_events`].
+///
+/// Transactions are searched from `before` (or the latest transaction) back to `until` (or the
+/// first transaction of the program), and only the ones within the slot range are included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackfillConfig {
+    /// Search the transactions before this signature, exclusive.
+    pub before: Option<Signature>,
+    /// Search the transactions until this signature, exclusive.
+    pub until: Option<Signature>,
+    /// Minimum slot of the included transactions, inclusive.
+    pub min_slot: Option<u64>,
+    /// Maximum slot of the included transactions, inclusive.
+    pub max_slot: Option<u64>,
+    /// Order of the returned transactions.
+    pub order: BackfillOrder,
+    /// Maximum number of transactions fetched concurrently.
+    pub concurrency: usize,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self {
+            before: None,
+            until: None,
+            min_slot: None,
+            max_slot: None,
+            order: BackfillOrder::NewestFirst,
+            concurrency: 8,
+        }
+    }
+}
+
+/// Stream of the events of a program, with items of type `Result<(EventContext, T), ClientError>`.
+///
+/// The stream resubscribes to the program logs with exponential backoff whenever the
+/// subscription is interrupted, and it never ends on its own. Each interruption is reported with
+/// a [`ClientError::EventStreamGap`] item, since the events emitted until the subscription is
+/// restored are missed. Errors that occur while parsing the logs of a transaction are also
+/// reported as items without ending the stream.
+///
+/// The subscription is closed when the stream is dropped.
+pub struct EventStream<T> {
+    rx: UnboundedReceiver<Result<(EventContext, T), ClientError>>,
+    handle: JoinHandle<()>,
+}
+
+impl<T> Stream for EventStream<T> {
+    type Item = Result<(EventContext, T), ClientError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().rx.poll_recv(cx)
+    }
+}
+
+impl<T> Drop for EventStream<T> {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
 /// Program is the primary client handle to be used to build and send requests.
 pub struct Program<C> {
     program_id: Pubkey,
@@ -234,6 +413,9 @@ pub struct Program<C> {
     #[cfg(not(feature = "async"))]
     rt: tokio::runtime::Runtime,
     internal_rpc_client: AsyncRpcClient,
+    #[cfg(not(feature = "mock"))]
+    sender: SharedSender,
+    errors: ErrorRegistry,
 }
 
 impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
@@ -245,6 +427,14 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
         self.program_id
     }
 
+    /// Set the error definitions used to decode the errors of failed transactions into
+    /// [`ClientError::Anchor`].
+    #[must_use]
+    pub fn with_errors(mut self, errors: ErrorRegistry) -> Self {
+        self.errors = errors;
+        self
+    }
+
     #[cfg(feature = "mock")]
     pub fn internal_rpc(&self) -> &AsyncRpcClient {
         &self.internal_rpc_client
@@ -265,36 +455,327 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
         T::try_deserialize(&mut data).map_err(Into::into)
     }
 
+    /// Returns the accounts at the given addresses in order, in chunks of at most
+    /// [`MAX_MULTIPLE_ACCOUNTS`] per `getMultipleAccounts` request.
+    async fn multiple_accounts_internal(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, ClientError> {
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let response = self
+                .internal_rpc_client
+                .get_multiple_accounts_with_commitment(chunk, CommitmentConfig::processed())
+                .await
+                .map_err(Box::new)?;
+            accounts.extend(response.value);
+        }
+        Ok(accounts)
+    }
+
+    async fn accounts_by_keys_internal<T: AccountDeserialize>(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<Option<T>>, ClientError> {
+        self.multiple_accounts_internal(addresses)
+            .await?
+            .into_iter()
+            .map(|account| {
+                account
+                    .map(|account| T::try_deserialize(&mut account.data.as_slice()))
+                    .transpose()
+                    .map_err(Into::into)
+            })
+            .collect()
+    }
+
+    async fn zero_copy_accounts_by_keys_internal<T: ZeroCopy>(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<Option<T>>, ClientError> {
+        self.multiple_accounts_internal(addresses)
+            .await?
+            .into_iter()
+            .map(|account| {
+                account
+                    .map(|account| load_zero_copy(&account.data))
+                    .transpose()
+            })
+            .collect()
+    }
+
+    async fn dynamic_account_internal(
+        &self,
+        program: &DynamicProgram,
+        address: Pubkey,
+    ) -> Result<DecodedValue, ClientError> {
+        let account = self
+            .internal_rpc_client
+            .get_account_with_commitment(&address, CommitmentConfig::processed())
+            .await
+            .map_err(Box::new)?
+            .value
+            .ok_or(ClientError::AccountNotFound)?;
+        program.decode_account(&account.data)
+    }
+
     async fn accounts_lazy_internal<T: AccountDeserialize + Discriminator>(
         &self,
         filters: Vec<RpcFilterType>,
     ) -> Result<ProgramAccountsIterator<T>, ClientError> {
+        let config = self.program_accounts_config::<T>(filters);
+
+        Ok(ProgramAccountsIterator {
+            inner: self
+                .internal_rpc_client
+                .get_program_ui_accounts_with_config(&self.id(), config)
+                .await
+                .map_err(Box::new)?
+                .into_iter()
+                .map(|(key, account)| Ok((key, deserialize_ui_account(&account)?))),
+        })
+    }
+
+    /// Commitment used to query the transaction history, which does not support the `processed`
+    /// commitment level.
+    fn history_commitment(&self) -> CommitmentConfig {
+        self.cfg
+            .options
+            .filter(CommitmentConfig::is_at_least_confirmed)
+            .unwrap_or_else(CommitmentConfig::confirmed)
+    }
+
+    async fn transaction_events_internal<T: anchor_lang::Event>(
+        &self,
+        signature: &Signature,
+    ) -> Result<Vec<T>, ClientError> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(self.history_commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+        let tx = self
+            .internal_rpc_client
+            .get_transaction_with_config(signature, config)
+            .await
+            .map_err(Box::new)?;
+        parse_transaction_events(&self.program_id, &tx)
+    }
+
+    fn backfill_events_internal<T: anchor_lang::Event>(
+        &self,
+        config: BackfillConfig,
+    ) -> impl Stream<Item = Result<(EventContext, T), ClientError>> + '_ {
+        // The state is `None` once the last page was fetched
+        let pages = futures::stream::try_unfold(Some(config.before), move |before| async move {
+            let Some(before) = before else {
+                return Ok::<_, ClientError>(None);
+            };
+            let page = self
+                .internal_rpc_client
+                .get_signatures_for_address_with_config(
+                    &self.program_id,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until: config.until,
+                        limit: Some(MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT),
+                        commitment: Some(self.history_commitment()),
+                    },
+                )
+                .await
+                .map_err(Box::new)?;
+            let Some(last) = page.last() else {
+                return Ok(None);
+            };
+
+            let reached_min_slot = config.min_slot.is_some_and(|slot| last.slot < slot);
+            let next = (!reached_min_slot)
+                .then(|| parse_signature(&last.signature).map(Some))
+                .transpose()?;
+            let contexts = page
+                .iter()
+                // Events of failed transactions are not part of the program state
+                .filter(|status| status.err.is_none())
+                .filter(|status| config.max_slot.is_none_or(|slot| status.slot <= slot))
+                .filter(|status| config.min_slot.is_none_or(|slot| status.slot >= slot))
+                .map(|status| {
+                    Ok(EventContext {
+                        signature: parse_signature(&status.signature)?,
+                        slot: status.slot,
+                    })
+                })
+                .collect::<Result<Vec<_>, ClientError>>()?;
+            Ok(Some((
+                futures::stream::iter(contexts).map(Ok::<_, ClientError>),
+                next,
+            )))
+        });
+
+        let contexts = pages.try_flatten();
+        let contexts = match config.order {
+            BackfillOrder::NewestFirst => contexts.left_stream(),
+            BackfillOrder::OldestFirst => futures::stream::once(contexts.try_collect::<Vec<_>>())
+                .map_ok(|contexts| {
+                    futures::stream::iter(contexts.into_iter().rev()).map(Ok::<_, ClientError>)
+                })
+                .try_flatten()
+                .right_stream(),
+        };
+
+        contexts
+            .map_ok(move |ctx| async move {
+                let events = self
+                    .transaction_events_internal::<T>(&ctx.signature)
+                    .await?;
+                Ok::<_, ClientError>((ctx, events))
+            })
+            .try_buffered(config.concurrency.max(1))
+            .map_ok(|(ctx, events): (EventContext, Vec<T>)| {
+                futures::stream::iter(events).map(move |event| Ok((ctx.clone(), event)))
+            })
+            .try_flatten()
+    }
+
+    async fn resolve_accounts_internal<A: AnchorSerialize>(
+        &self,
+        idl: &Idl,
+        instruction: &str,
+        known: &[(&str, Pubkey)],
+        args: &A,
+    ) -> Result<ResolvedAccounts, ClientError> {
+        let mut args_data = vec![];
+        args.serialize(&mut args_data)?;
+        AccountResolver::new(&self.internal_rpc_client, idl, self.program_id, &args_data)
+            .resolve(instruction, known)
+            .await
+    }
+
+    /// Returns the `getProgramAccounts` and `programSubscribe` config of the accounts of type
+    /// `T` matching the given filters.
+    fn program_accounts_config<T: Discriminator>(
+        &self,
+        filters: Vec<RpcFilterType>,
+    ) -> RpcProgramAccountsConfig {
         let account_type_filter =
             RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, T::DISCRIMINATOR));
-        let config = RpcProgramAccountsConfig {
+        RpcProgramAccountsConfig {
             filters: Some([vec![account_type_filter], filters].concat()),
             account_config: RpcAccountInfoConfig {
                 encoding: Some(UiAccountEncoding::Base64),
                 ..RpcAccountInfoConfig::default()
             },
             ..RpcProgramAccountsConfig::default()
+        }
+    }
+
+    async fn send_transaction_internal(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> Result<Signature, ClientError> {
+        let missing_signers = missing_signers(tx);
+        if !missing_signers.is_empty() {
+            return Err(ClientError::OfflineTransactionError(format!(
+                "Missing signatures of {missing_signers:?}"
+            )));
+        }
+
+        self.internal_rpc_client
+            .send_and_confirm_transaction(tx)
+            .await
+            .map_err(|e| self.errors.decode_client_error(e))
+    }
+
+    async fn subscribe_account_internal<T: AccountDeserialize + Send + 'static>(
+        &self,
+        address: Pubkey,
+    ) -> Result<AccountStream<T>, ClientError> {
+        let client = PubsubClient::new(self.cfg.cluster.ws_url())
+            .await
+            .map_err(Box::new)?;
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: self.cfg.options,
+            ..RpcAccountInfoConfig::default()
         };
+        let (tx, rx) = unbounded_channel();
+        let (subscribed_tx, subscribed_rx) = oneshot::channel();
 
-        Ok(ProgramAccountsIterator {
-            inner: self
-                .internal_rpc_client
-                .get_program_ui_accounts_with_config(&self.id(), config)
-                .await
-                .map_err(Box::new)?
-                .into_iter()
-                .map(|(key, account)| {
-                    let data = account
-                        .data
-                        .decode()
-                        .expect("account was fetched with binary encoding");
-                    Ok((key, T::try_deserialize(&mut data.as_slice())?))
-                }),
-        })
+        let handle = tokio::spawn(async move {
+            let (mut notifications, unsubscribe) =
+                match client.account_subscribe(&address, Some(config)).await {
+                    Ok(subscription) => {
+                        let _ = subscribed_tx.send(Ok(()));
+                        subscription
+                    }
+                    Err(e) => {
+                        let _ = subscribed_tx.send(Err(e));
+                        return;
+                    }
+                };
+
+            while let Some(response) = notifications.next().await {
+                let update = deserialize_ui_account(&response.value).map(|account| AccountUpdate {
+                    slot: response.context.slot,
+                    pubkey: address,
+                    account,
+                });
+                let _ = tx.send(update);
+            }
+
+            unsubscribe().await;
+        });
+
+        wait_for_subscription(subscribed_rx).await?;
+        Ok(AccountStream { rx, handle })
+    }
+
+    async fn subscribe_program_accounts_internal<T>(
+        &self,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<AccountStream<T>, ClientError>
+    where
+        T: AccountDeserialize + Discriminator + Send + 'static,
+    {
+        let client = PubsubClient::new(self.cfg.cluster.ws_url())
+            .await
+            .map_err(Box::new)?;
+        let program_id = self.program_id;
+        let mut config = self.program_accounts_config::<T>(filters);
+        config.account_config.commitment = self.cfg.options;
+        let (tx, rx) = unbounded_channel();
+        let (subscribed_tx, subscribed_rx) = oneshot::channel();
+
+        let handle = tokio::spawn(async move {
+            let (mut notifications, unsubscribe) =
+                match client.program_subscribe(&program_id, Some(config)).await {
+                    Ok(subscription) => {
+                        let _ = subscribed_tx.send(Ok(()));
+                        subscription
+                    }
+                    Err(e) => {
+                        let _ = subscribed_tx.send(Err(e));
+                        return;
+                    }
+                };
+
+            while let Some(response) = notifications.next().await {
+                let Ok(pubkey) = response.value.pubkey.parse() else {
+                    continue;
+                };
+                let update =
+                    deserialize_ui_account(&response.value.account).map(|account| AccountUpdate {
+                        slot: response.context.slot,
+                        pubkey,
+                        account,
+                    });
+                let _ = tx.send(update);
+            }
+
+            unsubscribe().await;
+        });
+
+        wait_for_subscription(subscribed_rx).await?;
+        Ok(AccountStream { rx, handle })
     }
 
     async fn init_sub_client_if_needed(&self) -> Result<(), ClientError> {
@@ -363,6 +844,169 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
 
         Ok((handle, rx))
     }
+
+    async fn event_stream_internal<T>(
+        &self,
+        config: EventStreamConfig,
+    ) -> Result<EventStream<T>, ClientError>
+    where
+        T: anchor_lang::Event + anchor_lang::AnchorDeserialize + Send + 'static,
+    {
+        let ws_url = self.cfg.cluster.ws_url().to_string();
+        let logs_config = RpcTransactionLogsConfig {
+            commitment: self.cfg.options,
+        };
+        let program_id_str = self.program_id.to_string();
+        let filter = RpcTransactionLogsFilter::Mentions(vec![program_id_str.clone()]);
+
+        // Connect before returning so that an invalid endpoint is reported to the caller
+        let client = PubsubClient::new(&ws_url).await.map_err(Box::new)?;
+        let (tx, rx) = unbounded_channel();
+
+        let handle = tokio::spawn(async move {
+            let mut client = Some(client);
+            let mut last_context = None;
+            let mut backoff = config.initial_backoff;
+            let mut gap_reported = false;
+
+            loop {
+                let client = match client.take() {
+                    Some(client) => Ok(client),
+                    None => PubsubClient::new(&ws_url).await,
+                };
+                let reason = match client {
+                    Ok(client) => {
+                        match client
+                            .logs_subscribe(filter.clone(), logs_config.clone())
+                            .await
+                        {
+                            Ok((mut notifications, unsubscribe)) => {
+                                backoff = config.initial_backoff;
+                                gap_reported = false;
+
+                                while let Some(logs) = notifications.next().await {
+                                    let Ok(signature) = logs.value.signature.parse() else {
+                                        continue;
+                                    };
+                                    let ctx = EventContext {
+                                        signature,
+                                        slot: logs.context.slot,
+                                    };
+                                    last_context = Some(ctx.clone());
+
+                                    match parse_logs_response::<T>(logs, &program_id_str) {
+                                        Ok(events) => {
+                                            for event in events {
+                                                let _ = tx.send(Ok((ctx.clone(), event)));
+                                            }
+                                        }
+                                        Err(e) => {
+                                            let _ = tx.send(Err(e));
+                                        }
+                                    }
+                                }
+
+                                unsubscribe().await;
+                                "Subscription closed".to_string()
+                            }
+                            Err(e) => e.to_string(),
+                        }
+                    }
+                    Err(e) => e.to_string(),
+                };
+
+                // Report each interruption once, not every failed resubscription attempt
+                if !gap_reported {
+                    gap_reported = true;
+                    let _ = tx.send(Err(ClientError::EventStreamGap {
+                        last_context: last_context.clone(),
+                        reason,
+                    }));
+                }
+
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(config.max_backoff);
+            }
+        });
+
+        Ok(EventStream { rx, handle })
+    }
+}
+
+/// Update of an account received from an account subscription.
+#[derive(Debug, Clone)]
+pub struct AccountUpdate<T> {
+    /// Slot of the update.
+    pub slot: u64,
+    /// Address of the account.
+    pub pubkey: Pubkey,
+    /// Deserialized account.
+    pub account: T,
+}
+
+/// Stream of account updates, with items of type `Result<AccountUpdate<T>, ClientError>`.
+///
+/// Accounts that can't be deserialized, e.g. because they were closed, are reported as error
+/// items without ending the stream. The stream ends when the subscription is closed by the node.
+///
+/// The subscription is closed when the stream is dropped.
+pub struct AccountStream<T> {
+    rx: UnboundedReceiver<Result<AccountUpdate<T>, ClientError>>,
+    handle: JoinHandle<()>,
+}
+
+impl<T> Stream for AccountStream<T> {
+    type Item = Result<AccountUpdate<T>, ClientError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().rx.poll_recv(cx)
+    }
+}
+
+impl<T> Drop for AccountStream<T> {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Reads a zero-copy account from the given account data, after checking its discriminator.
+fn load_zero_copy<T: ZeroCopy>(data: &[u8]) -> Result<T, ClientError> {
+    let disc = T::DISCRIMINATOR;
+    if data.len() < disc.len() {
+        return Err(
+            anchor_lang::error::Error::from(ErrorCode::AccountDiscriminatorNotFound).into(),
+        );
+    }
+    if !data.starts_with(disc) {
+        return Err(
+            anchor_lang::error::Error::from(ErrorCode::AccountDiscriminatorMismatch).into(),
+        );
+    }
+
+    // Account data is not guaranteed to be aligned for `T`
+    data.get(disc.len()..disc.len() + mem::size_of::<T>())
+        .map(bytemuck::pod_read_unaligned)
+        .ok_or_else(|| anchor_lang::error::Error::from(ErrorCode::AccountDidNotDeserialize).into())
+}
+
+fn deserialize_ui_account<T: AccountDeserialize>(account: &UiAccount) -> Result<T, ClientError> {
+    let data = account
+        .data
+        .decode()
+        .expect("account was fetched with binary encoding");
+    T::try_deserialize(&mut data.as_slice()).map_err(Into::into)
+}
+
+/// Waits for the subscription task to report whether the subscription request succeeded.
+async fn wait_for_subscription(
+    rx: oneshot::Receiver<Result<(), PubsubClientError>>,
+) -> Result<(), ClientError> {
+    match rx.await {
+        Ok(result) => result.map_err(|e| Box::new(e).into()),
+        Err(_) => Err(ClientError::SolanaClientPubsubError(Box::new(
+            PubsubClientError::ConnectionClosed("Subscription task stopped".to_string()),
+        ))),
+    }
 }
 
 /// Iterator with items of type (Pubkey, T). Used to lazily deserialize account structs.
@@ -382,9 +1026,32 @@ impl<T> Iterator for ProgramAccountsIterator<T> {
     }
 }
 
+fn parse_signature(signature: &str) -> Result<Signature, ClientError> {
+    signature
+        .parse()
+        .map_err(|_| ClientError::TransactionParseError(signature.to_string()))
+}
+
 pub fn handle_program_log<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(
     self_program_str: &str,
     l: &str,
+) -> Result<(Option<T>, Option<String>, bool), ClientError> {
+    handle_program_log_with(self_program_str, l, |log_bytes| {
+        log_bytes
+            .starts_with(T::DISCRIMINATOR)
+            .then(|| {
+                let mut data = &log_bytes[T::DISCRIMINATOR.len()..];
+                T::deserialize(&mut data).map_err(|e| ClientError::LogParseError(e.to_string()))
+            })
+            .transpose()
+    })
+}
+
+/// Same as [`handle_program_log`], with the events decoded from the log data by `decode`.
+fn handle_program_log_with<T>(
+    self_program_str: &str,
+    l: &str,
+    decode: impl Fn(&[u8]) -> Result<Option<T>, ClientError>,
 ) -> Result<(Option<T>, Option<String>, bool), ClientError> {
     use anchor_lang::__private::base64;
     use base64::engine::general_purpose::STANDARD;
@@ -404,15 +1071,7 @@ pub fn handle_program_log<T: anchor_lang::Event + anchor_lang::AnchorDeserialize
             }
         };
 
-        let event = log_bytes
-            .starts_with(T::DISCRIMINATOR)
-            .then(|| {
-                let mut data = &log_bytes[T::DISCRIMINATOR.len()..];
-                T::deserialize(&mut data).map_err(|e| ClientError::LogParseError(e.to_string()))
-            })
-            .transpose()?;
-
-        Ok((event, None, false))
+        Ok((decode(&log_bytes)?, None, false))
     }
     // System log.
     else {
@@ -421,6 +1080,82 @@ pub fn handle_program_log<T: anchor_lang::Event + anchor_lang::AnchorDeserialize
     }
 }
 
+/// Parses the first [`AnchorError`] logged in the given transaction logs, including the compared
+/// values logged after it (if any).
+///
+/// Errors thrown with a source location (e.g. via `err!`) are returned without an origin since
+/// [`anchor_lang::error::Source`] can only hold static file names.
+pub fn parse_anchor_error(logs: &[String]) -> Option<AnchorError> {
+    let (index, log) = logs.iter().enumerate().find_map(|(i, l)| {
+        l.strip_prefix(PROGRAM_LOG)
+            .filter(|l| l.starts_with("AnchorError "))
+            .map(|l| (i, l))
+    })?;
+
+    let (error_origin, log) = if let Some(log) = log.strip_prefix("AnchorError occurred. ") {
+        (None, log)
+    } else if let Some(log) = log.strip_prefix("AnchorError caused by account: ") {
+        let (account_name, log) = log.split_once(". ")?;
+        (
+            Some(ErrorOrigin::AccountName(account_name.to_string())),
+            log,
+        )
+    } else {
+        let (_source, log) = log
+            .strip_prefix("AnchorError thrown in ")?
+            .split_once(". ")?;
+        (None, log)
+    };
+
+    let (error_name, log) = log
+        .strip_prefix("Error Code: ")?
+        .split_once(". Error Number: ")?;
+    let (error_code_number, error_msg) = log.split_once(". Error Message: ")?;
+
+    let mut next_logs = logs[index + 1..]
+        .iter()
+        .filter_map(|l| l.strip_prefix(PROGRAM_LOG));
+    let compared_values = match (next_logs.next(), next_logs.next(), next_logs.next()) {
+        (Some("Left:"), Some(left), Some("Right:")) => {
+            let left = left.parse().ok()?;
+            let right = next_logs.next()?.parse().ok()?;
+            Some(ComparedValues::Pubkeys((left, right)))
+        }
+        (Some(left), Some(right), _) => left
+            .strip_prefix("Left: ")
+            .zip(right.strip_prefix("Right: "))
+            .map(|(left, right)| ComparedValues::Values((left.to_string(), right.to_string()))),
+        _ => None,
+    };
+
+    Some(AnchorError {
+        error_name: error_name.to_string(),
+        error_code_number: error_code_number.parse().ok()?,
+        error_msg: error_msg.strip_suffix('.').unwrap_or(error_msg).to_string(),
+        error_origin,
+        compared_values,
+    })
+}
+
+/// Returns the median of the given prioritization fees, or 0 if there are none (e.g. on
+/// localnet).
+pub fn median_prioritization_fee(fees: &[RpcPrioritizationFee]) -> u64 {
+    let mut fees: Vec<u64> = fees.iter().map(|fee| fee.prioritization_fee).collect();
+    if fees.is_empty() {
+        return 0;
+    }
+
+    fees.sort_unstable();
+    let median_index = fees.len() / 2;
+    if fees.len().is_multiple_of(2) {
+        let (a, b) = (fees[median_index - 1], fees[median_index]);
+        // Average without overflowing
+        a / 2 + b / 2 + (a % 2 + b % 2) / 2
+    } else {
+        fees[median_index]
+    }
+}
+
 pub fn handle_system_log(this_program_str: &str, log: &str) -> (Option<String>, bool) {
     if log.starts_with(&format!("Program {this_program_str} log:")) {
         (Some(this_program_str.to_string()), false)
@@ -477,21 +1212,134 @@ impl Execution {
     }
 }
 
+/// Data set by a program via `set_return_data`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReturnData {
+    pub program_id: Pubkey,
+    pub data: Vec<u8>,
+}
+
+impl TryFrom<UiTransactionReturnData> for ReturnData {
+    type Error = ClientError;
+
+    fn try_from(return_data: UiTransactionReturnData) -> Result<Self, Self::Error> {
+        use anchor_lang::__private::base64;
+        use base64::engine::general_purpose::STANDARD;
+        use base64::Engine;
+
+        let program_id = return_data
+            .program_id
+            .parse()
+            .map_err(|_| ClientError::LogParseError(return_data.program_id.clone()))?;
+        let data = STANDARD
+            .decode(&return_data.data.0)
+            .map_err(|e| ClientError::LogParseError(e.to_string()))?;
+        Ok(Self { program_id, data })
+    }
+}
+
+/// Result of simulating the transaction of a [`RequestBuilder`].
 #[derive(Debug)]
-pub struct EventContext {
-    pub signature: Signature,
+pub struct Simulation {
+    /// Slot the simulation was run at.
     pub slot: u64,
+    /// Error of the transaction, if it failed.
+    pub err: Option<TransactionError>,
+    /// Decoded error of the failing instruction, if any.
+    pub decoded_error: Option<DecodedError>,
+    /// Compute units consumed by the transaction.
+    pub units_consumed: Option<u64>,
+    /// Raw transaction logs.
+    pub logs: Vec<String>,
+    /// Data set by the last program that called `set_return_data`.
+    pub return_data: Option<ReturnData>,
+    program_id: Pubkey,
 }
 
-#[derive(Debug, Error)]
-pub enum ClientError {
-    #[error("Account not found")]
-    AccountNotFound,
-    #[error("{0}")]
-    AnchorError(#[from] anchor_lang::error::Error),
-    #[error("{0}")]
-    ProgramError(#[from] ProgramError),
-    #[error("{0}")]
+impl Simulation {
+    fn new(
+        program_id: Pubkey,
+        response: RpcResponse<RpcSimulateTransactionResult>,
+        errors: &ErrorRegistry,
+    ) -> Result<Self, ClientError> {
+        let result = response.value;
+        let err = result.err.map(Into::into);
+        let logs = result.logs.unwrap_or_default();
+        Ok(Self {
+            slot: response.context.slot,
+            decoded_error: errors.decode(err.as_ref(), &logs),
+            err,
+            units_consumed: result.units_consumed,
+            logs,
+            return_data: result.return_data.map(TryInto::try_into).transpose()?,
+            program_id,
+        })
+    }
+
+    /// Returns whether the simulated transaction succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.err.is_none()
+    }
+
+    /// Converts the simulation into an error if the simulated transaction failed.
+    pub fn into_result(self) -> Result<Self, ClientError> {
+        match self.err {
+            Some(err) => Err(match self.decoded_error {
+                Some(decoded_error) => ClientError::Anchor(Box::new(decoded_error)),
+                None => err.into(),
+            }),
+            None => Ok(self),
+        }
+    }
+
+    /// Deserializes the data returned by the request's program as `T`.
+    ///
+    /// Returns an error if the simulated transaction failed or the program did not return data.
+    pub fn into_return_value<T: AnchorDeserialize>(self) -> Result<T, ClientError> {
+        let simulation = self.into_result()?;
+        let return_data = simulation
+            .return_data
+            .filter(|return_data| return_data.program_id == simulation.program_id)
+            .ok_or(ClientError::ReturnDataNotFound(simulation.program_id))?;
+
+        // Trailing zero bytes are truncated from the return data by the runtime, so they need
+        // to be added back before deserializing.
+        let mut data = return_data.data;
+        data.resize(data.len().max(MAX_RETURN_DATA), 0);
+        T::deserialize(&mut data.as_slice()).map_err(Into::into)
+    }
+
+    /// Parses the events of type `T` emitted by the request's program from the simulation logs.
+    pub fn events<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(
+        &self,
+    ) -> Result<Vec<T>, ClientError> {
+        parse_logs(&self.logs, &self.program_id.to_string())
+    }
+
+    /// Parses the program invocations of the simulated transaction from its logs.
+    pub fn invocations(&self) -> Result<Vec<Invocation>, ClientError> {
+        parse_invocations(&self.logs)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EventContext {
+    pub signature: Signature,
+    pub slot: u64,
+}
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("Account not found")]
+    AccountNotFound,
+    #[error("{0}")]
+    AnchorError(#[from] anchor_lang::error::Error),
+    /// Error of a failed transaction decoded from its logs or custom error code.
+    #[error("{0}")]
+    Anchor(Box<DecodedError>),
+    #[error("{0}")]
+    ProgramError(#[from] ProgramError),
+    #[error("{0}")]
     SolanaClientError(#[from] Box<SolanaClientError>),
     #[error("{0}")]
     SolanaClientPubsubError(#[from] Box<PubsubClientError>),
@@ -501,6 +1349,164 @@ pub enum ClientError {
     IOError(#[from] std::io::Error),
     #[error("{0}")]
     SignerError(#[from] SignerError),
+    #[error("{0}")]
+    CompileError(#[from] CompileError),
+    #[error("Invalid address lookup table: {0}")]
+    InvalidLookupTable(Pubkey),
+    #[error("{0}")]
+    TransactionError(#[from] TransactionError),
+    #[error("Return data not found for program: {0}")]
+    ReturnDataNotFound(Pubkey),
+    #[error("Unable to parse transaction: {0}")]
+    TransactionParseError(String),
+    #[error("Invalid RPC header: {0}")]
+    InvalidHeader(String),
+    #[error("Invalid account filter: {0}")]
+    InvalidAccountFilter(String),
+    #[error("Unable to resolve accounts: {0}")]
+    AccountResolutionError(String),
+    #[error("Unable to encode or decode IDL value: {0}")]
+    IdlCoderError(String),
+    #[error("Invalid offline transaction: {0}")]
+    OfflineTransactionError(String),
+    #[error("Transaction expired before it was confirmed: {0}")]
+    TransactionExpired(Signature),
+    #[error("Unable to batch instructions: {0}")]
+    BatchError(String),
+    #[error(
+        "Event subscription interrupted, events after {last_context:?} may be missed: {reason}"
+    )]
+    EventStreamGap {
+        /// Context of the last transaction received before the interruption.
+        last_context: Option<EventContext>,
+        /// Reason of the interruption.
+        reason: String,
+    },
+}
+
+/// Configuration of the compute budget instructions added by [`RequestBuilder::compute_budget`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComputeBudgetConfig {
+    /// Margin added on top of the simulated compute units, in percent.
+    pub unit_limit_margin: u64,
+    /// Compute unit price in micro-lamports.
+    ///
+    /// If `None`, the median of the recent prioritization fees paid for the writable accounts of
+    /// the transaction is used.
+    pub unit_price: Option<u64>,
+}
+
+impl Default for ComputeBudgetConfig {
+    fn default() -> Self {
+        Self {
+            unit_limit_margin: 10,
+            unit_price: None,
+        }
+    }
+}
+
+/// Configuration of the sender used by [`RequestBuilder::send_with_config`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendConfig {
+    /// Commitment to confirm the transaction with. Defaults to the commitment of the request.
+    pub commitment: Option<CommitmentConfig>,
+    /// Interval between the broadcasts of the transaction until it lands or expires.
+    pub rebroadcast_interval: Duration,
+    /// Interval between the checks of the transaction status.
+    pub poll_interval: Duration,
+    /// Maximum number of times the transaction is signed again with a new blockhash after it
+    /// expired.
+    ///
+    /// An expired transaction can no longer land, so it's safe to replay. Transactions with a
+    /// durable nonce or a blockhash set with [`RequestBuilder::blockhash`] are never signed again.
+    /// Transactions with a durable nonce don't expire until the nonce is advanced, so they're
+    /// broadcast until they land or the nonce is advanced by another transaction.
+    pub max_resigns: usize,
+    /// Configuration of the `sendTransaction` requests. Preflight checks are only run on the
+    /// first broadcast of each signed transaction, with the commitment of the confirmation unless
+    /// a preflight commitment is set.
+    pub rpc_config: RpcSendTransactionConfig,
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        Self {
+            commitment: None,
+            rebroadcast_interval: Duration::from_secs(2),
+            poll_interval: Duration::from_millis(500),
+            max_resigns: 0,
+            rpc_config: RpcSendTransactionConfig::default(),
+        }
+    }
+}
+
+/// Outcome of a transaction sent with [`RequestBuilder::send_with_config`].
+#[derive(Debug)]
+pub struct SendOutcome {
+    /// Signature of the last signed transaction.
+    pub signature: Signature,
+    /// Slot the transaction landed in, if it landed.
+    pub slot: Option<u64>,
+    /// Whether the last signed transaction expired before it was confirmed.
+    pub expired: bool,
+    /// Error of the transaction, if it landed and failed.
+    pub error: Option<ClientError>,
+    /// Number of times the transaction was signed again after it expired.
+    pub resigns: usize,
+}
+
+impl SendOutcome {
+    /// Returns whether the transaction was confirmed without error.
+    pub fn is_ok(&self) -> bool {
+        self.slot.is_some() && self.error.is_none()
+    }
+
+    /// Returns the signature of the confirmed transaction, or an error if it failed or expired.
+    pub fn into_result(self) -> Result<Signature, ClientError> {
+        match self.error {
+            Some(error) => Err(error),
+            None if self.expired => Err(ClientError::TransactionExpired(self.signature)),
+            None => Ok(self.signature),
+        }
+    }
+}
+
+/// Configuration of [`RequestBuilder::send_batched`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchConfig {
+    /// Configuration of the sender used for each transaction.
+    pub send: SendConfig,
+    /// Send the transactions concurrently instead of one after the other.
+    ///
+    /// Transactions sent sequentially stop at the first one that fails or expires, since the
+    /// next ones may depend on it. Concurrent transactions are all sent and may land in any
+    /// order.
+    pub concurrent: bool,
+}
+
+/// Outcome of one of the transactions sent by [`RequestBuilder::send_batched`].
+#[derive(Debug)]
+pub struct BatchOutcome {
+    /// Range of the instructions of the transaction in [`RequestBuilder::instructions`].
+    pub instructions: Range<usize>,
+    /// Outcome of the transaction, or the error that prevented sending it.
+    pub result: Result<SendOutcome, ClientError>,
+}
+
+impl BatchOutcome {
+    /// Returns whether the transaction was confirmed without error.
+    pub fn is_ok(&self) -> bool {
+        self.result.as_ref().is_ok_and(SendOutcome::is_ok)
+    }
+}
+
+/// Returns whether the instructions already include a compute budget instruction with the given
+/// discriminator.
+fn has_compute_budget_ix(instructions: &[Instruction], discriminator: u8) -> bool {
+    instructions.iter().any(|ix| {
+        solana_compute_budget_interface::check_id(&ix.program_id)
+            && ix.data.first() == Some(&discriminator)
+    })
 }
 
 pub trait AsSigner {
@@ -513,6 +1519,24 @@ impl AsSigner for Box<dyn Signer + '_> {
     }
 }
 
+/// Condition after which a transaction that's not found can no longer land.
+#[derive(Clone, Copy)]
+enum Expiry<'a> {
+    /// The block height is past the last valid block height of the blockhash.
+    BlockHeight(u64),
+    /// The blockhash set with [`RequestBuilder::blockhash`] is no longer valid.
+    Blockhash(Hash),
+    /// The durable nonce stored in the nonce account is no longer the given one.
+    Nonce(&'a DurableNonce, Hash),
+}
+
+/// Durable nonce set with [`RequestBuilder::nonce`].
+#[derive(Debug, Clone, Copy)]
+struct DurableNonce {
+    account: Pubkey,
+    authority: Pubkey,
+}
+
 /// `RequestBuilder` provides a builder interface to create and send
 /// transactions to a cluster.
 pub struct RequestBuilder<'a, C, S: 'a> {
@@ -524,6 +1548,13 @@ pub struct RequestBuilder<'a, C, S: 'a> {
     payer: C,
     instruction_data: Option<Vec<u8>>,
     signers: Vec<S>,
+    lookup_tables: Vec<AddressLookupTableAccount>,
+    lookup_table_addresses: Vec<Pubkey>,
+    compute_budget: Option<ComputeBudgetConfig>,
+    errors: ErrorRegistry,
+    atomic_groups: Vec<Range<usize>>,
+    nonce: Option<DurableNonce>,
+    blockhash: Option<Hash>,
     #[cfg(not(feature = "async"))]
     handle: &'a Handle,
     internal_rpc_client: &'a AsyncRpcClient,
@@ -550,6 +1581,16 @@ impl<C: Deref<Target = impl Signer> + Clone, S: AsSigner> RequestBuilder<'_, C,
         self
     }
 
+    /// Add instructions that must be sent in the same transaction when the request is split
+    /// into several transactions by [`Self::send_batched`].
+    #[must_use]
+    pub fn atomic_instructions(mut self, ixs: impl IntoIterator<Item = Instruction>) -> Self {
+        let start = self.instructions.len();
+        self.instructions.extend(ixs);
+        self.atomic_groups.push(start..self.instructions.len());
+        self
+    }
+
     #[must_use]
     pub fn program(mut self, program_id: Pubkey) -> Self {
         self.program_id = program_id;
@@ -605,6 +1646,72 @@ impl<C: Deref<Target = impl Signer> + Clone, S: AsSigner> RequestBuilder<'_, C,
         self
     }
 
+    /// Add an address lookup table to compile the transaction message with.
+    ///
+    /// Transactions are built with a v0 message when at least one lookup table is set, otherwise
+    /// a legacy message is used.
+    #[must_use]
+    pub fn lookup_table(mut self, table: AddressLookupTableAccount) -> Self {
+        self.lookup_tables.push(table);
+        self
+    }
+
+    /// Add the address lookup table stored at the given address.
+    ///
+    /// The table is fetched from the cluster each time the transaction is built. Use
+    /// [`Self::lookup_table`] to pass an already resolved table instead.
+    #[must_use]
+    pub fn lookup_table_address(mut self, address: Pubkey) -> Self {
+        self.lookup_table_addresses.push(address);
+        self
+    }
+
+    /// Size the compute budget of the transaction automatically.
+    ///
+    /// Before building the transaction, it's simulated to set the compute unit limit to the
+    /// consumed units plus the configured margin, and a compute unit price is set based on the
+    /// configuration. Compute budget instructions that were already added to the request are
+    /// left unchanged.
+    #[must_use]
+    pub fn compute_budget(mut self, config: ComputeBudgetConfig) -> Self {
+        self.compute_budget = Some(config);
+        self
+    }
+
+    /// Set the error definitions used to decode the errors of failed transactions into
+    /// [`ClientError::Anchor`].
+    #[must_use]
+    pub fn errors(mut self, errors: ErrorRegistry) -> Self {
+        self.errors = errors;
+        self
+    }
+
+    /// Use the durable nonce stored in `nonce_account` instead of a recent blockhash, so that the
+    /// transaction doesn't expire, e.g. to be signed offline.
+    ///
+    /// An instruction to advance the nonce, signed by `nonce_authority`, is prepended to the
+    /// transaction. The nonce is fetched from the nonce account unless it's set with
+    /// [`Self::blockhash`].
+    #[must_use]
+    pub fn nonce(mut self, nonce_account: Pubkey, nonce_authority: Pubkey) -> Self {
+        self.nonce = Some(DurableNonce {
+            account: nonce_account,
+            authority: nonce_authority,
+        });
+        self
+    }
+
+    /// Set the blockhash of the transaction, or the durable nonce when used with [`Self::nonce`],
+    /// instead of fetching it from the cluster.
+    ///
+    /// This allows signing transactions offline, as long as neither
+    /// [`Self::lookup_table_address`] nor [`Self::compute_budget`] are used.
+    #[must_use]
+    pub fn blockhash(mut self, blockhash: Hash) -> Self {
+        self.blockhash = Some(blockhash);
+        self
+    }
+
     pub fn instructions(&self) -> Vec<Instruction> {
         let mut instructions = self.instructions.clone();
         if let Some(ix_data) = &self.instruction_data {
@@ -620,16 +1727,167 @@ impl<C: Deref<Target = impl Signer> + Clone, S: AsSigner> RequestBuilder<'_, C,
 
     fn signed_transaction_with_blockhash(
         &self,
+        instructions: &[Instruction],
         latest_hash: Hash,
-    ) -> Result<Transaction, ClientError> {
-        let signers: Vec<&dyn Signer> = self.signers.iter().map(|s| s.as_signer()).collect();
-        let mut all_signers = signers;
-        all_signers.push(&*self.payer);
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<VersionedTransaction, ClientError> {
+        let message = self.message_with_blockhash(instructions, latest_hash, lookup_tables)?;
+        self.sign_message(message, false)
+    }
 
-        let mut tx = self.transaction();
-        tx.try_sign(&all_signers, latest_hash)?;
+    /// Signs the transaction with the available signers, leaving the signatures of the other
+    /// signers empty.
+    fn partially_signed_transaction_with_blockhash(
+        &self,
+        instructions: &[Instruction],
+        latest_hash: Hash,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<VersionedTransaction, ClientError> {
+        let message = self.message_with_blockhash(instructions, latest_hash, lookup_tables)?;
+        self.sign_message(message, true)
+    }
 
-        Ok(tx)
+    fn message_with_blockhash(
+        &self,
+        instructions: &[Instruction],
+        latest_hash: Hash,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<VersionedMessage, ClientError> {
+        let payer = self.payer.pubkey();
+        let message = if lookup_tables.is_empty() {
+            VersionedMessage::Legacy(Message::new_with_blockhash(
+                instructions,
+                Some(&payer),
+                &latest_hash,
+            ))
+        } else {
+            VersionedMessage::V0(v0::Message::try_compile(
+                &payer,
+                instructions,
+                lookup_tables,
+                latest_hash,
+            )?)
+        };
+
+        Ok(message)
+    }
+
+    /// Prepends the instruction advancing the durable nonce, which must be the first instruction
+    /// of the transaction.
+    fn prepend_nonce_ix(&self, instructions: &mut Vec<Instruction>) {
+        if let Some(nonce) = &self.nonce {
+            instructions.insert(
+                0,
+                system_instruction::advance_nonce_account(&nonce.account, &nonce.authority),
+            );
+        }
+    }
+
+    async fn blockhash_internal(&self) -> Result<Hash, ClientError> {
+        if let Some(blockhash) = self.blockhash {
+            return Ok(blockhash);
+        }
+        match &self.nonce {
+            Some(nonce) => self.nonce_internal(nonce).await,
+            None => self
+                .internal_rpc_client
+                .get_latest_blockhash()
+                .await
+                .map_err(|e| Box::new(e).into()),
+        }
+    }
+
+    /// Returns the durable nonce stored in the nonce account, checking its authority.
+    async fn nonce_internal(&self, nonce: &DurableNonce) -> Result<Hash, ClientError> {
+        let nonce_error = |e: &dyn std::fmt::Display| {
+            ClientError::OfflineTransactionError(format!(
+                "Invalid nonce account {}: {e}",
+                nonce.account
+            ))
+        };
+        let account = nonce_utils::get_account_with_commitment(
+            self.internal_rpc_client,
+            &nonce.account,
+            self.options,
+        )
+        .await
+        .map_err(|e| nonce_error(&e))?;
+        let data = nonce_utils::data_from_account(&account).map_err(|e| nonce_error(&e))?;
+        if data.authority != nonce.authority {
+            return Err(nonce_error(&format!(
+                "expected authority {}, found {}",
+                nonce.authority, data.authority
+            )));
+        }
+
+        Ok(data.blockhash())
+    }
+
+    /// Signs the message with the payer and the signers of the request. When `partial` is set,
+    /// the signatures of the signers that are not available are left empty instead of failing.
+    fn sign_message(
+        &self,
+        message: VersionedMessage,
+        partial: bool,
+    ) -> Result<VersionedTransaction, ClientError> {
+        let num_signatures = message.header().num_required_signatures as usize;
+        let signer_keys = &message.static_account_keys()[..num_signatures];
+
+        // Versioned transactions require exactly one signer per signature, so the same key
+        // passed more than once (e.g. the payer also added via `signer`) is only kept once.
+        // Signers that the message doesn't require, e.g. signers of the instructions of another
+        // batch of `send_batched`, are skipped.
+        let mut all_signers: Vec<&dyn Signer> = vec![&*self.payer];
+        for signer in self.signers.iter().map(|s| s.as_signer()) {
+            if signer_keys.contains(&signer.pubkey())
+                && all_signers.iter().all(|s| s.pubkey() != signer.pubkey())
+            {
+                all_signers.push(signer);
+            }
+        }
+        if !partial {
+            return VersionedTransaction::try_new(message, &all_signers).map_err(Into::into);
+        }
+
+        let message_data = message.serialize();
+        let signatures = signer_keys
+            .iter()
+            .map(
+                |key| match all_signers.iter().find(|s| s.pubkey() == *key) {
+                    Some(signer) => signer.try_sign_message(&message_data),
+                    None => Ok(Signature::default()),
+                },
+            )
+            .collect::<Result<_, _>>()?;
+
+        Ok(VersionedTransaction {
+            signatures,
+            message,
+        })
+    }
+
+    async fn lookup_tables_internal(&self) -> Result<Vec<AddressLookupTableAccount>, ClientError> {
+        let mut tables = self.lookup_tables.clone();
+        if self.lookup_table_addresses.is_empty() {
+            return Ok(tables);
+        }
+
+        let accounts = self
+            .internal_rpc_client
+            .get_multiple_accounts(&self.lookup_table_addresses)
+            .await
+            .map_err(Box::new)?;
+        for (key, account) in self.lookup_table_addresses.iter().zip(accounts) {
+            let account = account.ok_or(ClientError::AccountNotFound)?;
+            let table = AddressLookupTable::deserialize(&account.data)
+                .map_err(|_| ClientError::InvalidLookupTable(*key))?;
+            tables.push(AddressLookupTableAccount {
+                key: *key,
+                addresses: table.addresses.to_vec(),
+            });
+        }
+
+        Ok(tables)
     }
 
     pub fn transaction(&self) -> Transaction {
@@ -637,41 +1895,484 @@ impl<C: Deref<Target = impl Signer> + Clone, S: AsSigner> RequestBuilder<'_, C,
         Transaction::new_with_payer(instructions, Some(&self.payer.pubkey()))
     }
 
-    async fn signed_transaction_internal(&self) -> Result<Transaction, ClientError> {
-        let latest_hash = self
+    /// Returns the instructions to simulate, raising the compute unit limit to the maximum when
+    /// the compute budget is sized automatically so that the simulation doesn't run out of it.
+    fn simulation_instructions(&self, mut instructions: Vec<Instruction>) -> Vec<Instruction> {
+        if self.compute_budget.is_some()
+            && !has_compute_budget_ix(&instructions, SET_COMPUTE_UNIT_LIMIT_DISCRIMINATOR)
+        {
+            instructions.insert(
+                0,
+                ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+            );
+        }
+        self.prepend_nonce_ix(&mut instructions);
+
+        instructions
+    }
+
+    async fn transaction_instructions_internal(
+        &self,
+        mut instructions: Vec<Instruction>,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<Vec<Instruction>, ClientError> {
+        let Some(config) = self.compute_budget else {
+            self.prepend_nonce_ix(&mut instructions);
+            return Ok(instructions);
+        };
+
+        let mut budget_ixs = vec![];
+        if !has_compute_budget_ix(&instructions, SET_COMPUTE_UNIT_LIMIT_DISCRIMINATOR) {
+            let simulation = self
+                .simulate_instructions_internal(instructions.clone(), lookup_tables)
+                .await?
+                .into_result()?;
+            let units = simulation
+                .units_consumed
+                .map(|units| units.saturating_mul(100 + config.unit_limit_margin) / 100)
+                .map_or(MAX_COMPUTE_UNIT_LIMIT, |units| {
+                    units.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32
+                });
+            budget_ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+        if !has_compute_budget_ix(&instructions, SET_COMPUTE_UNIT_PRICE_DISCRIMINATOR) {
+            let unit_price = match config.unit_price {
+                Some(unit_price) => unit_price,
+                None => self.recommended_unit_price_internal(&instructions).await?,
+            };
+            if unit_price > 0 {
+                budget_ixs.push(ComputeBudgetInstruction::set_compute_unit_price(unit_price));
+            }
+        }
+
+        instructions.splice(0..0, budget_ixs);
+        self.prepend_nonce_ix(&mut instructions);
+        Ok(instructions)
+    }
+
+    /// Returns the median of the recent prioritization fees paid for the writable accounts of the
+    /// given instructions.
+    async fn recommended_unit_price_internal(
+        &self,
+        instructions: &[Instruction],
+    ) -> Result<u64, ClientError> {
+        let mut writable_accounts = vec![];
+        for meta in instructions.iter().flat_map(|ix| &ix.accounts) {
+            if meta.is_writable && !writable_accounts.contains(&meta.pubkey) {
+                writable_accounts.push(meta.pubkey);
+            }
+        }
+        writable_accounts.truncate(MAX_PRIORITIZATION_FEE_ACCOUNTS);
+
+        let fees = self
             .internal_rpc_client
-            .get_latest_blockhash()
+            .get_recent_prioritization_fees(&writable_accounts)
             .await
             .map_err(Box::new)?;
+        Ok(median_prioritization_fee(&fees))
+    }
 
-        let tx = self.signed_transaction_with_blockhash(latest_hash)?;
+    async fn signed_transaction_internal(&self) -> Result<VersionedTransaction, ClientError> {
+        let lookup_tables = self.lookup_tables_internal().await?;
+        let instructions = self
+            .transaction_instructions_internal(self.instructions(), &lookup_tables)
+            .await?;
+        let latest_hash = self.blockhash_internal().await?;
+
+        let tx =
+            self.signed_transaction_with_blockhash(&instructions, latest_hash, &lookup_tables)?;
         Ok(tx)
     }
 
+    async fn partially_signed_transaction_internal(
+        &self,
+    ) -> Result<VersionedTransaction, ClientError> {
+        let lookup_tables = self.lookup_tables_internal().await?;
+        let instructions = self
+            .transaction_instructions_internal(self.instructions(), &lookup_tables)
+            .await?;
+        let latest_hash = self.blockhash_internal().await?;
+
+        self.partially_signed_transaction_with_blockhash(&instructions, latest_hash, &lookup_tables)
+    }
+
+    async fn sign_only_internal(
+        &self,
+        encoding: TransactionEncoding,
+    ) -> Result<String, ClientError> {
+        let tx = self.partially_signed_transaction_internal().await?;
+        encode_transaction(&tx, encoding)
+    }
+
+    /// Returns the size of the serialized transaction with the given instructions, including the
+    /// compute budget and durable nonce instructions that are added when sending it.
+    fn transaction_size(
+        &self,
+        instructions: &[Instruction],
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<usize, ClientError> {
+        let mut instructions = instructions.to_vec();
+        if self.compute_budget.is_some() {
+            for (discriminator, ix) in [
+                (
+                    SET_COMPUTE_UNIT_LIMIT_DISCRIMINATOR,
+                    ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+                ),
+                (
+                    SET_COMPUTE_UNIT_PRICE_DISCRIMINATOR,
+                    ComputeBudgetInstruction::set_compute_unit_price(u64::MAX),
+                ),
+            ] {
+                if !has_compute_budget_ix(&instructions, discriminator) {
+                    instructions.push(ix);
+                }
+            }
+        }
+        self.prepend_nonce_ix(&mut instructions);
+
+        let message = self.message_with_blockhash(&instructions, Hash::default(), lookup_tables)?;
+        let tx = VersionedTransaction {
+            signatures: vec![Signature::default(); message.header().num_required_signatures.into()],
+            message,
+        };
+        bincode::serialized_size(&tx)
+            .map(|size| size as usize)
+            .map_err(|e| ClientError::BatchError(e.to_string()))
+    }
+
+    /// Splits the instructions into the fewest transactions that fit in a packet, keeping their
+    /// order and the atomic groups together. Returns the ranges of the instructions of each
+    /// transaction.
+    fn batches_with_lookup_tables(
+        &self,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<Vec<Range<usize>>, ClientError> {
+        let instructions = self.instructions();
+        let fits = |range: Range<usize>| {
+            self.transaction_size(&instructions[range], lookup_tables)
+                .is_ok_and(|size| size <= MAX_TRANSACTION_SIZE)
+        };
+
+        let mut batches = vec![];
+        let mut batch = 0..0;
+        let mut index = 0;
+        while index < instructions.len() {
+            let unit = self
+                .atomic_groups
+                .iter()
+                .find(|group| group.start == index && !group.is_empty())
+                .cloned()
+                .unwrap_or(index..index + 1);
+            index = unit.end;
+
+            // Packing greedily is optimal since the order of the instructions is kept
+            if fits(batch.start..unit.end) {
+                batch.end = unit.end;
+                continue;
+            }
+            if !fits(unit.clone()) {
+                return Err(ClientError::BatchError(format!(
+                    "instructions {unit:?} don't fit in a single transaction"
+                )));
+            }
+            if !batch.is_empty() {
+                batches.push(batch);
+            }
+            batch = unit;
+        }
+        if !batch.is_empty() {
+            batches.push(batch);
+        }
+
+        if self.nonce.is_some() && batches.len() > 1 {
+            return Err(ClientError::BatchError(
+                "a durable nonce can't be used by several transactions".to_string(),
+            ));
+        }
+        Ok(batches)
+    }
+
+    async fn batches_internal(&self) -> Result<Vec<Range<usize>>, ClientError> {
+        let lookup_tables = self.lookup_tables_internal().await?;
+        self.batches_with_lookup_tables(&lookup_tables)
+    }
+
+    async fn send_batched_internal(
+        &self,
+        config: BatchConfig,
+    ) -> Result<Vec<BatchOutcome>, ClientError> {
+        let lookup_tables = self.lookup_tables_internal().await?;
+        let batches = self.batches_with_lookup_tables(&lookup_tables)?;
+        let instructions = self.instructions();
+        let send = |range: Range<usize>| {
+            let batch_instructions = instructions[range.clone()].to_vec();
+            let lookup_tables = &lookup_tables;
+            async move {
+                BatchOutcome {
+                    result: self
+                        .send_instructions_internal(batch_instructions, lookup_tables, config.send)
+                        .await,
+                    instructions: range,
+                }
+            }
+        };
+
+        if config.concurrent {
+            return Ok(futures::future::join_all(batches.into_iter().map(send)).await);
+        }
+
+        let mut outcomes = vec![];
+        for range in batches {
+            let outcome = send(range).await;
+            let is_ok = outcome.is_ok();
+            outcomes.push(outcome);
+            if !is_ok {
+                break;
+            }
+        }
+        Ok(outcomes)
+    }
+
     async fn send_internal(&self) -> Result<Signature, ClientError> {
-        let latest_hash = self
+        self.send_with_config_internal(SendConfig::default())
+            .await?
+            .into_result()
+    }
+
+    async fn send_with_config_internal(
+        &self,
+        config: SendConfig,
+    ) -> Result<SendOutcome, ClientError> {
+        let lookup_tables = self.lookup_tables_internal().await?;
+        self.send_instructions_internal(self.instructions(), &lookup_tables, config)
+            .await
+    }
+
+    /// Sends a transaction with the given instructions, to which the compute budget and durable
+    /// nonce instructions are added.
+    async fn send_instructions_internal(
+        &self,
+        instructions: Vec<Instruction>,
+        lookup_tables: &[AddressLookupTableAccount],
+        config: SendConfig,
+    ) -> Result<SendOutcome, ClientError> {
+        let commitment = config.commitment.unwrap_or(self.options);
+        let config = SendConfig {
+            rpc_config: RpcSendTransactionConfig {
+                preflight_commitment: config
+                    .rpc_config
+                    .preflight_commitment
+                    .or(Some(commitment.commitment)),
+                ..config.rpc_config
+            },
+            ..config
+        };
+        let instructions = self
+            .transaction_instructions_internal(instructions, lookup_tables)
+            .await?;
+        let can_resign = self.nonce.is_none() && self.blockhash.is_none();
+
+        let mut resigns = 0;
+        loop {
+            let (blockhash, expiry) = if can_resign {
+                let (latest_hash, last_valid_block_height) = self
+                    .internal_rpc_client
+                    .get_latest_blockhash_with_commitment(commitment)
+                    .await
+                    .map_err(Box::new)?;
+                (latest_hash, Expiry::BlockHeight(last_valid_block_height))
+            } else {
+                let blockhash = self.blockhash_internal().await?;
+                match &self.nonce {
+                    Some(nonce) => (blockhash, Expiry::Nonce(nonce, blockhash)),
+                    None => (blockhash, Expiry::Blockhash(blockhash)),
+                }
+            };
+            let tx =
+                self.signed_transaction_with_blockhash(&instructions, blockhash, lookup_tables)?;
+            let signature = tx.signatures[0];
+
+            let status = self
+                .broadcast_until_expiry_internal(&tx, config, commitment, expiry)
+                .await?;
+            let error = match status.as_ref().and_then(|status| status.err.clone()) {
+                Some(err) => Some(self.landed_error_internal(&signature, err).await),
+                None => None,
+            };
+            let outcome = SendOutcome {
+                signature,
+                slot: status.as_ref().map(|status| status.slot),
+                expired: status.is_none(),
+                error,
+                resigns,
+            };
+            if !outcome.expired || !can_resign || resigns >= config.max_resigns {
+                return Ok(outcome);
+            }
+            resigns += 1;
+        }
+    }
+
+    /// Decodes the error of a transaction that landed, from its logs if they can be fetched.
+    ///
+    /// The logs are fetched with the `confirmed` commitment, so a transaction that has only been
+    /// processed may be decoded without its logs, in which case the error has no account name or
+    /// compared values.
+    async fn landed_error_internal(
+        &self,
+        signature: &Signature,
+        err: TransactionError,
+    ) -> ClientError {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        };
+        let logs = self
             .internal_rpc_client
-            .get_latest_blockhash()
+            .get_transaction_with_config(signature, config)
             .await
-            .map_err(Box::new)?;
-        let tx = self.signed_transaction_with_blockhash(latest_hash)?;
+            .ok()
+            .and_then(|tx| tx.transaction.meta)
+            .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages))
+            .unwrap_or_default();
+
+        match self.errors.decode(Some(&err), &logs) {
+            Some(decoded) => ClientError::Anchor(Box::new(decoded)),
+            None => ClientError::TransactionError(err),
+        }
+    }
 
+    /// Broadcasts the transaction at intervals until it's confirmed or expires, returning its
+    /// status, or `None` if it expired.
+    async fn broadcast_until_expiry_internal(
+        &self,
+        tx: &VersionedTransaction,
+        config: SendConfig,
+        commitment: CommitmentConfig,
+        expiry: Expiry<'_>,
+    ) -> Result<Option<TransactionStatus>, ClientError> {
+        let signature = tx.signatures[0];
         self.internal_rpc_client
-            .send_and_confirm_transaction(&tx)
+            .send_transaction_with_config(tx, config.rpc_config)
             .await
-            .map_err(|e| Box::new(e).into())
+            .map_err(|e| self.errors.decode_client_error(e))?;
+        let rebroadcast_config = RpcSendTransactionConfig {
+            skip_preflight: true,
+            ..config.rpc_config
+        };
+
+        let mut last_broadcast = Instant::now();
+        loop {
+            tokio::time::sleep(config.poll_interval).await;
+
+            // The expiry is checked before the status is fetched, so that a transaction that's
+            // not found once it expired can no longer land
+            let expired = self.is_expired_internal(expiry, commitment).await?;
+            let status = self
+                .internal_rpc_client
+                .get_signature_statuses(&[signature])
+                .await
+                .map_err(Box::new)?
+                .value
+                .remove(0);
+            match status {
+                Some(status) if status.satisfies_commitment(commitment) => {
+                    return Ok(Some(status));
+                }
+                // Landed but not confirmed yet
+                Some(_) => continue,
+                None if expired => return Ok(None),
+                None => {}
+            }
+
+            if last_broadcast.elapsed() >= config.rebroadcast_interval {
+                // Rebroadcast errors are ignored since the first broadcast succeeded
+                let _ = self
+                    .internal_rpc_client
+                    .send_transaction_with_config(tx, rebroadcast_config)
+                    .await;
+                last_broadcast = Instant::now();
+            }
+        }
     }
 
-    async fn send_with_spinner_and_config_internal(
+    /// Returns whether a transaction with the given expiry can no longer land.
+    async fn is_expired_internal(
         &self,
-        config: RpcSendTransactionConfig,
-    ) -> Result<Signature, ClientError> {
-        let latest_hash = self
+        expiry: Expiry<'_>,
+        commitment: CommitmentConfig,
+    ) -> Result<bool, ClientError> {
+        match expiry {
+            Expiry::BlockHeight(last_valid_block_height) => {
+                let block_height = self
+                    .internal_rpc_client
+                    .get_block_height_with_commitment(commitment)
+                    .await
+                    .map_err(Box::new)?;
+                Ok(block_height > last_valid_block_height)
+            }
+            Expiry::Blockhash(blockhash) => {
+                let is_valid = self
+                    .internal_rpc_client
+                    .is_blockhash_valid(&blockhash, commitment)
+                    .await
+                    .map_err(Box::new)?;
+                Ok(!is_valid)
+            }
+            Expiry::Nonce(nonce, blockhash) => Ok(self.nonce_internal(nonce).await? != blockhash),
+        }
+    }
+
+    async fn simulate_internal(&self) -> Result<Simulation, ClientError> {
+        let lookup_tables = self.lookup_tables_internal().await?;
+        self.simulate_instructions_internal(self.instructions(), &lookup_tables)
+            .await
+    }
+
+    async fn simulate_instructions_internal(
+        &self,
+        instructions: Vec<Instruction>,
+        lookup_tables: &[AddressLookupTableAccount],
+    ) -> Result<Simulation, ClientError> {
+        // The transaction isn't signed, so that simulating doesn't require the signers, and its
+        // blockhash is replaced by the latest one
+        let message = self.message_with_blockhash(
+            &self.simulation_instructions(instructions),
+            Hash::default(),
+            lookup_tables,
+        )?;
+        let tx = VersionedTransaction {
+            signatures: vec![
+                Signature::default();
+                message.header().num_required_signatures as usize
+            ],
+            message,
+        };
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.options),
+            ..RpcSimulateTransactionConfig::default()
+        };
+        let response = self
             .internal_rpc_client
-            .get_latest_blockhash()
+            .simulate_transaction_with_config(&tx, config)
             .await
             .map_err(Box::new)?;
-        let tx = self.signed_transaction_with_blockhash(latest_hash)?;
+
+        Simulation::new(self.program_id, response, &self.errors)
+    }
+
+    async fn view_internal<T: AnchorDeserialize>(&self) -> Result<T, ClientError> {
+        self.simulate_internal().await?.into_return_value()
+    }
+
+    async fn send_with_spinner_and_config_internal(
+        &self,
+        config: RpcSendTransactionConfig,
+    ) -> Result<Signature, ClientError> {
+        let tx = self.signed_transaction_internal().await?;
 
         self.internal_rpc_client
             .send_and_confirm_transaction_with_spinner_and_config(
@@ -680,7 +2381,7 @@ impl<C: Deref<Target = impl Signer> + Clone, S: AsSigner> RequestBuilder<'_, C,
                 config,
             )
             .await
-            .map_err(|e| Box::new(e).into())
+            .map_err(|e| self.errors.decode_client_error(e))
     }
 }
 
@@ -688,7 +2389,26 @@ fn parse_logs_response<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(
     logs: RpcResponse<RpcLogsResponse>,
     program_id_str: &str,
 ) -> Result<Vec<T>, ClientError> {
-    let mut logs = &logs.value.logs[..];
+    parse_logs(&logs.value.logs, program_id_str)
+}
+
+fn parse_logs<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(
+    logs: &[String],
+    program_id_str: &str,
+) -> Result<Vec<T>, ClientError> {
+    parse_logs_with(logs, program_id_str, |l| {
+        handle_program_log(program_id_str, l)
+    })
+}
+
+/// Parses the events of the given program from the transaction logs, with the program's logs
+/// handled by `handle_log`.
+fn parse_logs_with<T>(
+    logs: &[String],
+    program_id_str: &str,
+    handle_log: impl Fn(&str) -> Result<(Option<T>, Option<String>, bool), ClientError>,
+) -> Result<Vec<T>, ClientError> {
+    let mut logs = logs;
     let mut events: Vec<T> = Vec::new();
     if !logs.is_empty() {
         if let Ok(mut execution) = Execution::new(&mut logs) {
@@ -700,7 +2420,7 @@ fn parse_logs_response<T: anchor_lang::Event + anchor_lang::AnchorDeserialize>(
                 // Parse the log.
                 let (event, new_program, did_pop) = {
                     if program_id_str == execution.program() {
-                        handle_program_log(program_id_str, l)?
+                        handle_log(l)?
                     } else {
                         let (program, did_pop) = handle_system_log(program_id_str, l);
                         (None, program, did_pop)
@@ -754,6 +2474,58 @@ mod tests {
     pub struct MockEvent {}
 
     use super::*;
+
+    #[derive(Debug, AnchorSerialize, AnchorDeserialize)]
+    pub struct MockAccount {
+        pub value: u64,
+    }
+
+    impl Discriminator for MockAccount {
+        const DISCRIMINATOR: &'static [u8] = &[1, 2, 3, 4, 5, 6, 7, 8];
+    }
+
+    impl AccountDeserialize for MockAccount {
+        fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+            if !buf.starts_with(Self::DISCRIMINATOR) {
+                return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+            }
+            Self::try_deserialize_unchecked(buf)
+        }
+
+        fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+            Self::deserialize(&mut &buf[Self::DISCRIMINATOR.len()..]).map_err(Into::into)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    #[repr(C)]
+    pub struct MockZeroCopy {
+        pub value: u64,
+    }
+
+    unsafe impl bytemuck::Zeroable for MockZeroCopy {}
+    unsafe impl bytemuck::Pod for MockZeroCopy {}
+
+    impl Discriminator for MockZeroCopy {
+        const DISCRIMINATOR: &'static [u8] = &[8, 7, 6, 5, 4, 3, 2, 1];
+    }
+
+    impl ZeroCopy for MockZeroCopy {}
+
+    fn mock_ui_account(data: &[u8]) -> UiAccount {
+        UiAccount {
+            lamports: 1,
+            data: solana_account_decoder::UiAccountData::Binary(
+                bs58::encode(data).into_string(),
+                UiAccountEncoding::Base58,
+            ),
+            owner: Pubkey::default().to_string(),
+            executable: false,
+            rent_epoch: 0,
+            space: Some(data.len() as u64),
+        }
+    }
+
     #[test]
     fn new_execution() {
         let mut logs: &[String] =
@@ -781,6 +2553,793 @@ mod tests {
         assert!(!did_pop);
     }
 
+    #[test]
+    fn test_parse_anchor_error() {
+        let logs = [
+            "Program fake111111111111111111111111111111111111112 invoke [1]",
+            "Program log: Instruction: Initialize",
+            "Program log: AnchorError caused by account: my_account. Error Code: ConstraintHasOne. Error Number: 2001. Error Message: A has one constraint was violated.",
+            "Program log: Left:",
+            "Program log: 7Y8VDzehoewALqJfyxZYMgYCnMTCDhWuGfJKUvjYWATw",
+            "Program log: Right:",
+            "Program log: 7swsTUiQ6KUK4uFYquQKg4epFRsBnvbrTf2fZQCa2sTJ",
+            "Program fake111111111111111111111111111111111111112 consumed 1411 of 200000 compute units",
+            "Program fake111111111111111111111111111111111111112 failed: custom program error: 0x7d1",
+        ];
+        let logs: Vec<String> = logs.iter().map(|&l| l.to_string()).collect();
+
+        let error = parse_anchor_error(&logs).unwrap();
+        assert_eq!(error.error_name, "ConstraintHasOne");
+        assert_eq!(error.error_code_number, 2001);
+        assert_eq!(error.error_msg, "A has one constraint was violated");
+        assert!(
+            matches!(error.error_origin, Some(ErrorOrigin::AccountName(name)) if name == "my_account")
+        );
+        assert!(matches!(
+            error.compared_values,
+            Some(ComparedValues::Pubkeys((left, right)))
+                if left.to_string() == "7Y8VDzehoewALqJfyxZYMgYCnMTCDhWuGfJKUvjYWATw"
+                    && right.to_string() == "7swsTUiQ6KUK4uFYquQKg4epFRsBnvbrTf2fZQCa2sTJ"
+        ));
+    }
+
+    #[test]
+    fn test_parse_anchor_error_with_source_and_values() {
+        let logs = [
+            "Program log: AnchorError thrown in programs/errors/src/lib.rs:57. Error Code: RequireEqViolated. Error Number: 2501. Error Message: A require_eq expression was violated.",
+            "Program log: Left: 5241",
+            "Program log: Right: 124124124",
+        ];
+        let logs: Vec<String> = logs.iter().map(|&l| l.to_string()).collect();
+
+        let error = parse_anchor_error(&logs).unwrap();
+        assert_eq!(error.error_name, "RequireEqViolated");
+        assert_eq!(error.error_code_number, 2501);
+        assert!(error.error_origin.is_none());
+        assert!(matches!(
+            error.compared_values,
+            Some(ComparedValues::Values((left, right))) if left == "5241" && right == "124124124"
+        ));
+    }
+
+    #[test]
+    fn test_simulation_return_value() {
+        let program_id = Pubkey::new_unique();
+        let simulation = |program_id, data| Simulation {
+            slot: 0,
+            err: None,
+            decoded_error: None,
+            units_consumed: Some(1000),
+            logs: vec![],
+            return_data: Some(ReturnData { program_id, data }),
+            program_id: Pubkey::default(),
+        };
+
+        // Trailing zero bytes are truncated by the runtime
+        let value: (u64, u32) = simulation(Pubkey::default(), vec![5])
+            .into_return_value()
+            .unwrap();
+        assert_eq!(value, (5, 0));
+
+        // Return data set by another program
+        assert!(matches!(
+            simulation(program_id, vec![5]).into_return_value::<u64>(),
+            Err(ClientError::ReturnDataNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_simulation_decoded_error_keeps_logs() {
+        let logs = vec![
+            "Program log: AnchorError caused by account: vault. Error Code: ConstraintSeeds. Error Number: 2006. Error Message: A seeds constraint was violated.".to_string(),
+        ];
+        let err = TransactionError::InstructionError(
+            0,
+            solana_instruction::error::InstructionError::Custom(2006),
+        );
+        let simulation = Simulation {
+            slot: 0,
+            decoded_error: ErrorRegistry::new().decode(Some(&err), &logs),
+            err: Some(err),
+            units_consumed: None,
+            logs: logs.clone(),
+            return_data: None,
+            program_id: Pubkey::default(),
+        };
+
+        match simulation.into_result() {
+            Err(ClientError::Anchor(decoded)) => {
+                assert_eq!(decoded.name, "ConstraintSeeds");
+                assert_eq!(decoded.account_name.as_deref(), Some("vault"));
+                assert_eq!(decoded.logs, logs);
+            }
+            result => panic!("unexpected result: {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_has_compute_budget_ix() {
+        let instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_price(1),
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[2], vec![]),
+        ];
+        assert!(has_compute_budget_ix(
+            &instructions,
+            SET_COMPUTE_UNIT_PRICE_DISCRIMINATOR
+        ));
+        assert!(!has_compute_budget_ix(
+            &instructions,
+            SET_COMPUTE_UNIT_LIMIT_DISCRIMINATOR
+        ));
+    }
+
+    #[test]
+    fn test_median_prioritization_fee() {
+        let fees = |fees: &[u64]| -> Vec<RpcPrioritizationFee> {
+            fees.iter()
+                .map(|&prioritization_fee| RpcPrioritizationFee {
+                    slot: 0,
+                    prioritization_fee,
+                })
+                .collect()
+        };
+        assert_eq!(median_prioritization_fee(&[]), 0);
+        assert_eq!(median_prioritization_fee(&fees(&[3, 1, 2])), 2);
+        assert_eq!(median_prioritization_fee(&fees(&[4, 1, 3, 2])), 2);
+        assert_eq!(median_prioritization_fee(&fees(&[1, 2])), 1);
+        assert_eq!(
+            median_prioritization_fee(&fees(&[u64::MAX, u64::MAX])),
+            u64::MAX
+        );
+        assert_eq!(
+            median_prioritization_fee(&fees(&[u64::MAX, u64::MAX - 1])),
+            u64::MAX - 1
+        );
+    }
+
+    #[cfg_attr(feature = "async", allow(unused_variables))]
+    fn mock_request<'a>(
+        rt: &'a tokio::runtime::Runtime,
+        rpc: &'a AsyncRpcClient,
+        payer: Arc<solana_keypair::Keypair>,
+    ) -> RequestBuilder<'a, Arc<solana_keypair::Keypair>, Box<dyn solana_signer::Signer + 'a>> {
+        RequestBuilder {
+            cluster: String::new(),
+            program_id: Pubkey::new_unique(),
+            accounts: vec![],
+            options: CommitmentConfig::confirmed(),
+            instructions: vec![Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[1],
+                vec![],
+            )],
+            payer,
+            instruction_data: None,
+            signers: vec![],
+            lookup_tables: vec![],
+            lookup_table_addresses: vec![],
+            compute_budget: None,
+            errors: ErrorRegistry::default(),
+            atomic_groups: vec![],
+            nonce: None,
+            blockhash: None,
+            #[cfg(not(feature = "async"))]
+            handle: rt.handle(),
+            internal_rpc_client: rpc,
+            _phantom: PhantomData,
+        }
+    }
+
+    #[test]
+    fn test_send_with_config() {
+        use solana_rpc_client::mock_sender::MocksMap;
+        use solana_rpc_client_api::request::RpcRequest;
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let payer = Arc::new(solana_keypair::Keypair::new());
+        let config = SendConfig {
+            poll_interval: Duration::ZERO,
+            max_resigns: 2,
+            ..SendConfig::default()
+        };
+
+        // Confirmed
+        let rpc = AsyncRpcClient::new_mock("succeeds".to_string());
+        let request = mock_request(&rt, &rpc, payer.clone());
+        let outcome = rt
+            .block_on(request.send_with_config_internal(config))
+            .unwrap();
+        assert!(outcome.is_ok());
+        assert_eq!(outcome.slot, Some(1));
+        assert_eq!(outcome.resigns, 0);
+
+        // Failed
+        let rpc = AsyncRpcClient::new_mock("instruction_error".to_string());
+        let request = mock_request(&rt, &rpc, payer.clone());
+        let outcome = rt
+            .block_on(request.send_with_config_internal(config))
+            .unwrap();
+        assert!(!outcome.is_ok());
+        assert!(!outcome.expired);
+        assert!(matches!(
+            outcome.into_result(),
+            Err(ClientError::TransactionError(_))
+        ));
+
+        // Failed, with the error decoded from the logs of the landed transaction
+        let log = "Program log: AnchorError caused by account: vault. Error Code: ConstraintSeeds. Error Number: 2006. Error Message: A seeds constraint was violated.";
+        let tx = serde_json::json!({
+            "slot": 1,
+            "blockTime": null,
+            "transaction": ["", "base64"],
+            "meta": {
+                "err": null,
+                "status": { "Ok": null },
+                "fee": 0,
+                "preBalances": [],
+                "postBalances": [],
+                "logMessages": [log],
+            },
+        });
+        let mocks = [(RpcRequest::GetTransaction, tx)]
+            .into_iter()
+            .collect::<MocksMap>();
+        let rpc = AsyncRpcClient::new_mock_with_mocks_map("instruction_error", mocks);
+        let request = mock_request(&rt, &rpc, payer.clone());
+        let outcome = rt
+            .block_on(request.send_with_config_internal(config))
+            .unwrap();
+        match outcome.into_result() {
+            Err(ClientError::Anchor(decoded)) => {
+                assert_eq!(decoded.name, "ConstraintSeeds");
+                assert_eq!(decoded.account_name.as_deref(), Some("vault"));
+                assert_eq!(decoded.logs, vec![log.to_string()]);
+            }
+            result => panic!("unexpected result: {result:?}"),
+        }
+
+        // Expired and signed again until `max_resigns` is reached
+        let mocks = (0..3)
+            .map(|_| (RpcRequest::GetBlockHeight, serde_json::json!(2000)))
+            .collect::<MocksMap>();
+        let rpc = AsyncRpcClient::new_mock_with_mocks_map("sig_not_found", mocks);
+        let request = mock_request(&rt, &rpc, payer.clone());
+        let outcome = rt
+            .block_on(request.send_with_config_internal(config))
+            .unwrap();
+        assert!(outcome.expired);
+        assert_eq!(outcome.slot, None);
+        assert_eq!(outcome.resigns, 2);
+        assert!(matches!(
+            outcome.into_result(),
+            Err(ClientError::TransactionExpired(_))
+        ));
+
+        // A fixed blockhash expires once it's no longer valid, and is never signed again
+        let mocks = [true, false]
+            .map(|is_valid| {
+                let response = serde_json::json!({ "context": { "slot": 1 }, "value": is_valid });
+                (RpcRequest::IsBlockhashValid, response)
+            })
+            .into_iter()
+            .collect::<MocksMap>();
+        let rpc = AsyncRpcClient::new_mock_with_mocks_map("sig_not_found", mocks);
+        let request = mock_request(&rt, &rpc, payer).blockhash(Hash::new_unique());
+        let outcome = rt
+            .block_on(request.send_with_config_internal(config))
+            .unwrap();
+        assert!(outcome.expired);
+        assert_eq!(outcome.resigns, 0);
+    }
+
+    #[test]
+    fn test_nonce() {
+        use solana_nonce::state::{Data, State};
+        use solana_nonce::versions::Versions;
+        use solana_rpc_client::mock_sender::MocksMap;
+        use solana_rpc_client_api::request::RpcRequest;
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let payer = Arc::new(solana_keypair::Keypair::new());
+        let (nonce_account, authority) =
+            (Pubkey::new_unique(), solana_signer::Signer::pubkey(&*payer));
+        let durable_nonce = solana_nonce::state::DurableNonce::from_blockhash(&Hash::new_unique());
+        let data = bincode::serialize(&Versions::new(State::Initialized(Data::new(
+            authority,
+            durable_nonce,
+            5000,
+        ))))
+        .unwrap();
+        let nonce_response = |data: &[u8]| {
+            let response = serde_json::json!({
+                "context": { "slot": 1 },
+                "value": mock_ui_account(data),
+            });
+            (RpcRequest::GetAccountInfo, response)
+        };
+        let rpc_with_nonce = || {
+            let mocks = MocksMap::from_iter([nonce_response(&data)]);
+            AsyncRpcClient::new_mock_with_mocks_map("succeeds", mocks)
+        };
+
+        // The nonce is advanced before the compute budget instructions
+        let rpc = rpc_with_nonce();
+        let request = mock_request(&rt, &rpc, payer.clone())
+            .nonce(nonce_account, authority)
+            .compute_budget(ComputeBudgetConfig {
+                unit_limit_margin: 10,
+                unit_price: Some(5),
+            });
+        let instructions = rt
+            .block_on(request.transaction_instructions_internal(request.instructions.clone(), &[]))
+            .unwrap();
+        assert_eq!(
+            instructions[..3],
+            [
+                system_instruction::advance_nonce_account(&nonce_account, &authority),
+                ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+                ComputeBudgetInstruction::set_compute_unit_price(5),
+            ]
+        );
+        assert_eq!(instructions[3..], request.instructions[..]);
+        assert_eq!(
+            rt.block_on(request.blockhash_internal()).unwrap(),
+            *durable_nonce.as_hash()
+        );
+
+        // The transaction is broadcast until the nonce is advanced
+        let advanced_data = bincode::serialize(&Versions::new(State::Initialized(Data::new(
+            authority,
+            solana_nonce::state::DurableNonce::from_blockhash(&Hash::new_unique()),
+            5000,
+        ))))
+        .unwrap();
+        let mocks = MocksMap::from_iter([
+            nonce_response(&data),
+            nonce_response(&data),
+            nonce_response(&advanced_data),
+        ]);
+        let rpc = AsyncRpcClient::new_mock_with_mocks_map("sig_not_found", mocks);
+        let request = mock_request(&rt, &rpc, payer.clone()).nonce(nonce_account, authority);
+        let config = SendConfig {
+            poll_interval: Duration::ZERO,
+            ..SendConfig::default()
+        };
+        let outcome = rt
+            .block_on(request.send_with_config_internal(config))
+            .unwrap();
+        assert!(outcome.expired);
+        assert_eq!(outcome.resigns, 0);
+
+        // The authority of the nonce account must be the given one
+        let rpc = rpc_with_nonce();
+        let request = mock_request(&rt, &rpc, payer).nonce(nonce_account, Pubkey::new_unique());
+        assert!(matches!(
+            rt.block_on(request.blockhash_internal()),
+            Err(ClientError::OfflineTransactionError(_))
+        ));
+    }
+
+    #[test]
+    fn test_simulate_without_signers() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let rpc = AsyncRpcClient::new_mock("succeeds".to_string());
+        let payer = Arc::new(solana_keypair::Keypair::new());
+        let ix = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![AccountMeta::new(Pubkey::new_unique(), true)],
+        );
+
+        let request = mock_request(&rt, &rpc, payer).instruction(ix);
+        assert!(rt.block_on(request.simulate_internal()).is_ok());
+        assert!(matches!(
+            request.signed_transaction_with_blockhash(
+                &request.instructions(),
+                Hash::default(),
+                &[]
+            ),
+            Err(ClientError::SignerError(_))
+        ));
+    }
+
+    #[test]
+    fn test_batches() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let rpc = AsyncRpcClient::new_mock("succeeds".to_string());
+        let payer = Arc::new(solana_keypair::Keypair::new());
+        let program_id = Pubkey::new_unique();
+        let ix = |len| Instruction::new_with_bytes(program_id, &vec![0; len], vec![]);
+
+        // The mock request starts with a 1-byte instruction of another program
+        let request = mock_request(&rt, &rpc, payer.clone())
+            .instruction(ix(400))
+            .instruction(ix(400))
+            .atomic_instructions([ix(200), ix(200)])
+            .instruction(ix(400));
+        let batches = request.batches_with_lookup_tables(&[]).unwrap();
+        assert_eq!(batches, vec![0..3, 3..6]);
+
+        let outcomes = rt
+            .block_on(request.send_batched_internal(BatchConfig {
+                send: SendConfig {
+                    poll_interval: Duration::ZERO,
+                    ..SendConfig::default()
+                },
+                concurrent: false,
+            }))
+            .unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes.iter().all(BatchOutcome::is_ok));
+
+        // Instruction larger than a transaction
+        let request = mock_request(&rt, &rpc, payer).instruction(ix(MAX_TRANSACTION_SIZE));
+        assert!(matches!(
+            request.batches_with_lookup_tables(&[]),
+            Err(ClientError::BatchError(_))
+        ));
+    }
+
+    #[test]
+    fn test_lookup_tables() {
+        use solana_address_lookup_table_interface::state::LookupTableMeta;
+        use solana_rpc_client::mock_sender::MocksMap;
+        use solana_rpc_client_api::request::RpcRequest;
+
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let payer = Arc::new(solana_keypair::Keypair::new());
+        let program_id = Pubkey::new_unique();
+        let keys = (0..60).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let ix = |keys: &[Pubkey]| {
+            let metas = keys.iter().map(|key| AccountMeta::new(*key, false));
+            Instruction::new_with_bytes(program_id, &[], metas.collect())
+        };
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: keys.clone(),
+        };
+        let accounts_response = |data: &[u8]| {
+            let response = serde_json::json!({
+                "context": { "slot": 1 },
+                "value": [mock_ui_account(data)],
+            });
+            MocksMap::from_iter([(RpcRequest::GetMultipleAccounts, response)])
+        };
+
+        // The accounts of the table are loaded through it instead of being in the message
+        let rpc = AsyncRpcClient::new_mock("succeeds".to_string());
+        let request = mock_request(&rt, &rpc, payer.clone())
+            .instruction(ix(&keys[..2]))
+            .lookup_table(table.clone());
+        let lookup_tables = rt.block_on(request.lookup_tables_internal()).unwrap();
+        let message = request
+            .message_with_blockhash(&request.instructions(), Hash::default(), &lookup_tables)
+            .unwrap();
+        let VersionedMessage::V0(message) = message else {
+            panic!("expected a v0 message");
+        };
+        assert!(!message.account_keys.contains(&keys[0]));
+        assert!(!message.account_keys.contains(&keys[1]));
+        assert_eq!(message.address_table_lookups.len(), 1);
+        assert_eq!(message.address_table_lookups[0].account_key, table.key);
+        assert_eq!(message.address_table_lookups[0].writable_indexes, [0, 1]);
+        assert!(message.address_table_lookups[0].readonly_indexes.is_empty());
+
+        // Tables fetched by address are added after the supplied ones
+        let data = AddressLookupTable {
+            meta: LookupTableMeta::new(Pubkey::new_unique()),
+            addresses: keys.as_slice().into(),
+        }
+        .serialize_for_tests()
+        .unwrap();
+        let rpc = AsyncRpcClient::new_mock_with_mocks_map("succeeds", accounts_response(&data));
+        let address = Pubkey::new_unique();
+        let request = mock_request(&rt, &rpc, payer.clone())
+            .lookup_table(table.clone())
+            .lookup_table_address(address);
+        assert_eq!(
+            rt.block_on(request.lookup_tables_internal()).unwrap(),
+            [
+                table.clone(),
+                AddressLookupTableAccount {
+                    key: address,
+                    addresses: keys.clone(),
+                },
+            ]
+        );
+
+        // The fetched account must be a lookup table
+        let rpc = AsyncRpcClient::new_mock_with_mocks_map("succeeds", accounts_response(&[1, 2]));
+        let request = mock_request(&rt, &rpc, payer.clone()).lookup_table_address(address);
+        assert!(matches!(
+            rt.block_on(request.lookup_tables_internal()),
+            Err(ClientError::InvalidLookupTable(key)) if key == address
+        ));
+
+        // Instructions whose accounts don't fit in a legacy transaction share one with the table
+        let rpc = AsyncRpcClient::new_mock("succeeds".to_string());
+        let request = mock_request(&rt, &rpc, payer)
+            .instruction(ix(&keys[..20]))
+            .instruction(ix(&keys[20..40]))
+            .instruction(ix(&keys[40..]));
+        assert_eq!(
+            request.batches_with_lookup_tables(&[]).unwrap(),
+            vec![0..2, 2..3, 3..4]
+        );
+        assert_eq!(
+            request.batches_with_lookup_tables(&[table]).unwrap(),
+            vec![0..4]
+        );
+    }
+
+    #[test]
+    fn test_event_stream() {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let (tx, rx) = unbounded_channel();
+        let handle = rt.spawn(std::future::pending());
+        let mut stream = EventStream::<MockEvent> { rx, handle };
+
+        let ctx = EventContext {
+            signature: Signature::default(),
+            slot: 1,
+        };
+        tx.send(Ok((ctx.clone(), MockEvent {}))).unwrap();
+        tx.send(Err(ClientError::EventStreamGap {
+            last_context: Some(ctx),
+            reason: "Subscription closed".to_string(),
+        }))
+        .unwrap();
+
+        rt.block_on(async {
+            let (ctx, _) = stream.next().await.unwrap().unwrap();
+            assert_eq!(ctx.slot, 1);
+            assert!(matches!(
+                stream.next().await,
+                Some(Err(ClientError::EventStreamGap {
+                    last_context: Some(EventContext { slot: 1, .. }),
+                    ..
+                }))
+            ));
+        });
+
+        // Dropping the stream closes the channel of the subscription task
+        drop(stream);
+        assert!(tx.is_closed());
+    }
+
+    /// RPC sender that serves the given pages of signatures, each transaction emitting a
+    /// `MockEvent`, and records the `getSignaturesForAddress` requests.
+    struct BackfillSender {
+        pages: std::sync::Mutex<std::collections::VecDeque<Vec<(Signature, u64, bool)>>>,
+        requests: Arc<std::sync::Mutex<Vec<serde_json::Value>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl solana_rpc_client::rpc_sender::RpcSender for BackfillSender {
+        async fn send(
+            &self,
+            request: solana_rpc_client_api::request::RpcRequest,
+            params: serde_json::Value,
+        ) -> solana_rpc_client_api::client_error::Result<serde_json::Value> {
+            use anchor_lang::__private::base64::{engine::general_purpose::STANDARD, Engine};
+            use solana_rpc_client_api::request::RpcRequest;
+            use solana_rpc_client_api::response::RpcConfirmedTransactionStatusWithSignature;
+
+            match request {
+                RpcRequest::GetSignaturesForAddress => {
+                    self.requests.lock().unwrap().push(params[1].clone());
+                    let page = self.pages.lock().unwrap().pop_front().unwrap_or_default();
+                    let statuses = page
+                        .into_iter()
+                        .map(|(signature, slot, failed)| {
+                            RpcConfirmedTransactionStatusWithSignature {
+                                signature: signature.to_string(),
+                                slot,
+                                err: failed.then_some(TransactionError::AccountInUse.into()),
+                                memo: None,
+                                block_time: None,
+                                confirmation_status: None,
+                            }
+                        })
+                        .collect::<Vec<_>>();
+                    Ok(serde_json::to_value(statuses).unwrap())
+                }
+                RpcRequest::GetTransaction => {
+                    let program_id = Pubkey::default();
+                    let data = STANDARD.encode(anchor_lang::Event::data(&MockEvent {}));
+                    Ok(serde_json::json!({
+                        "slot": 0,
+                        "blockTime": null,
+                        "transaction": ["", "base64"],
+                        "meta": {
+                            "err": null,
+                            "status": { "Ok": null },
+                            "fee": 0,
+                            "preBalances": [],
+                            "postBalances": [],
+                            "logMessages": [
+                                format!("Program {program_id} invoke [1]"),
+                                format!("{PROGRAM_DATA}{data}"),
+                                format!("Program {program_id} success"),
+                            ],
+                        },
+                    }))
+                }
+                _ => Err(solana_rpc_client_api::request::RpcError::ForUser(format!(
+                    "Unexpected request: {request}"
+                ))
+                .into()),
+            }
+        }
+
+        fn get_transport_stats(&self) -> solana_rpc_client::rpc_sender::RpcTransportStats {
+            Default::default()
+        }
+
+        fn url(&self) -> String {
+            "backfill".to_string()
+        }
+    }
+
+    fn backfill(
+        pages: Vec<Vec<(Signature, u64, bool)>>,
+        config: BackfillConfig,
+    ) -> (Vec<EventContext>, Vec<serde_json::Value>) {
+        let requests = Arc::new(std::sync::Mutex::new(vec![]));
+        let sender = SharedSender(Arc::new(BackfillSender {
+            pages: std::sync::Mutex::new(pages.into()),
+            requests: requests.clone(),
+        }));
+        let rpc = AsyncRpcClient::new_sender(sender.clone(), Default::default());
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let program = super::Program {
+            program_id: Pubkey::default(),
+            cfg: Config {
+                cluster: Cluster::Localnet,
+                payer: Arc::new(solana_keypair::Keypair::new()),
+                options: None,
+            },
+            sub_client: Arc::new(RwLock::new(None)),
+            #[cfg(not(feature = "async"))]
+            rt: tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap(),
+            internal_rpc_client: rpc,
+            #[cfg(not(feature = "mock"))]
+            sender,
+            errors: ErrorRegistry::default(),
+        };
+
+        let events = rt
+            .block_on(
+                program
+                    .backfill_events_internal::<MockEvent>(config)
+                    .map_ok(|(ctx, _)| ctx)
+                    .try_collect(),
+            )
+            .unwrap();
+        let requests = requests.lock().unwrap().clone();
+        (events, requests)
+    }
+
+    #[test]
+    fn test_backfill_events() {
+        let [s1, s2, s3, s4, s5, until] = [1, 2, 3, 4, 5, 6].map(|n| Signature::from([n; 64]));
+        let pages = || {
+            vec![
+                vec![(s5, 5, false), (s4, 4, true), (s3, 3, false)],
+                vec![(s2, 2, false), (s1, 1, false)],
+            ]
+        };
+        let request = |before: Option<Signature>| {
+            serde_json::json!({
+                "before": before.map(|s| s.to_string()),
+                "until": until.to_string(),
+                "limit": MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
+                "commitment": "confirmed",
+                "minContextSlot": null,
+            })
+        };
+
+        // Paged from the newest transaction until there are no signatures left, skipping
+        // failed transactions and the ones after `max_slot`
+        let (events, requests) = backfill(
+            pages(),
+            BackfillConfig {
+                until: Some(until),
+                max_slot: Some(4),
+                concurrency: 2,
+                ..BackfillConfig::default()
+            },
+        );
+        let signatures = events.iter().map(|ctx| ctx.signature).collect::<Vec<_>>();
+        assert_eq!(signatures, [s3, s2, s1]);
+        assert_eq!(
+            events.iter().map(|ctx| ctx.slot).collect::<Vec<_>>(),
+            [3, 2, 1]
+        );
+        assert_eq!(
+            requests,
+            [request(None), request(Some(s3)), request(Some(s1))]
+        );
+
+        // Paging stops once a page reaches a slot before `min_slot`
+        let (events, requests) = backfill(
+            pages(),
+            BackfillConfig {
+                before: Some(s5),
+                until: Some(until),
+                min_slot: Some(2),
+                ..BackfillConfig::default()
+            },
+        );
+        let signatures = events.iter().map(|ctx| ctx.signature).collect::<Vec<_>>();
+        assert_eq!(signatures, [s5, s3, s2]);
+        assert_eq!(requests, [request(Some(s5)), request(Some(s3))]);
+
+        // Replayed from the oldest transaction once all the pages are fetched
+        let (events, requests) = backfill(
+            pages(),
+            BackfillConfig {
+                until: Some(until),
+                order: BackfillOrder::OldestFirst,
+                concurrency: 2,
+                ..BackfillConfig::default()
+            },
+        );
+        let signatures = events.iter().map(|ctx| ctx.signature).collect::<Vec<_>>();
+        assert_eq!(signatures, [s1, s2, s3, s5]);
+        assert_eq!(
+            requests,
+            [request(None), request(Some(s3)), request(Some(s1))]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_ui_account() {
+        let data = [MockAccount::DISCRIMINATOR, &42u64.to_le_bytes()].concat();
+        let account: MockAccount = deserialize_ui_account(&mock_ui_account(&data)).unwrap();
+        assert_eq!(account.value, 42);
+
+        // Closed account
+        assert!(deserialize_ui_account::<MockAccount>(&mock_ui_account(&[])).is_err());
+    }
+
+    #[test]
+    fn test_load_zero_copy() {
+        // Unaligned account data
+        let data = [&[0][..], MockZeroCopy::DISCRIMINATOR, &42u64.to_le_bytes()].concat();
+        let account = load_zero_copy::<MockZeroCopy>(&data[1..]).unwrap();
+        assert_eq!(account.value, 42);
+
+        assert!(load_zero_copy::<MockZeroCopy>(&data[1..5]).is_err());
+        assert!(load_zero_copy::<MockZeroCopy>(&data[..9]).is_err());
+        assert!(load_zero_copy::<MockZeroCopy>(&data[1..12]).is_err());
+        let wrong_discriminator = [MockAccount::DISCRIMINATOR, &42u64.to_le_bytes()].concat();
+        assert!(load_zero_copy::<MockZeroCopy>(&wrong_discriminator).is_err());
+    }
+
     #[test]
     fn test_parse_logs_response() -> Result<()> {
         // Mock logs received within an `RpcResponse`. These are based on a Jupiter transaction.
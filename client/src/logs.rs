@@ -0,0 +1,268 @@
+use crate::{ClientError, PROGRAM_DATA, PROGRAM_LOG};
+use anchor_lang::__private::base64;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::fmt;
+
+const PROGRAM_RETURN: &str = "Program return: ";
+pub(crate) const LOG_TRUNCATED: &str = "Log truncated";
+
+/// Outcome of a program invocation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvocationStatus {
+    Success,
+    /// The invocation failed with the given error message.
+    Failed(String),
+    /// The logs ended before the invocation returned, e.g. because they were truncated.
+    Incomplete,
+}
+
+/// Program invocation parsed from the transaction logs with [`parse_invocations`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    pub program_id: Pubkey,
+    /// Invocation depth, starting at 1 for the instructions of the transaction.
+    pub depth: usize,
+    /// Compute units consumed by the invocation, including its CPIs.
+    pub compute_units_consumed: Option<u64>,
+    /// Compute units that were available to the invocation.
+    pub compute_units_budget: Option<u64>,
+    /// Messages logged by the program with `msg!`, and the other runtime logs of the invocation.
+    pub logs: Vec<String>,
+    /// Data logged by the program with `sol_log_data`, e.g. events emitted with `emit!`. Each
+    /// entry holds the fields of one log.
+    pub data: Vec<Vec<Vec<u8>>>,
+    /// Data set by the program with `set_return_data`.
+    pub return_data: Option<Vec<u8>>,
+    pub status: InvocationStatus,
+    /// Programs invoked by this program, in invocation order.
+    pub inner: Vec<Invocation>,
+}
+
+impl Invocation {
+    fn new(program_id: Pubkey, depth: usize) -> Self {
+        Self {
+            program_id,
+            depth,
+            compute_units_consumed: None,
+            compute_units_budget: None,
+            logs: Vec::new(),
+            data: Vec::new(),
+            return_data: None,
+            status: InvocationStatus::Incomplete,
+            inner: Vec::new(),
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.status == InvocationStatus::Success
+    }
+
+    /// Returns this invocation and all the inner invocations, depth first.
+    pub fn iter(&self) -> impl Iterator<Item = &Invocation> {
+        let mut stack = vec![self];
+        std::iter::from_fn(move || {
+            let invocation = stack.pop()?;
+            stack.extend(invocation.inner.iter().rev());
+            Some(invocation)
+        })
+    }
+}
+
+/// Renders the invocation tree, with the inner invocations indented.
+impl fmt::Display for Invocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let indent = "  ".repeat(self.depth - 1);
+        write!(f, "{indent}{}", self.program_id)?;
+        match &self.status {
+            InvocationStatus::Success => write!(f, " success")?,
+            InvocationStatus::Failed(err) => write!(f, " failed: {err}")?,
+            InvocationStatus::Incomplete => write!(f, " incomplete")?,
+        }
+        if let (Some(consumed), Some(budget)) =
+            (self.compute_units_consumed, self.compute_units_budget)
+        {
+            write!(f, " ({consumed} of {budget} compute units)")?;
+        }
+        writeln!(f)?;
+        for log in &self.logs {
+            writeln!(f, "{indent}  > {log}")?;
+        }
+        self.inner.iter().try_for_each(|inner| inner.fmt(f))
+    }
+}
+
+/// Parses the program invocations of a transaction from its logs.
+///
+/// Returns one [`Invocation`] per instruction of the transaction, with the CPIs it made as inner
+/// invocations. Invocations that did not return before the end of the logs, e.g. because the
+/// logs were truncated, are [`InvocationStatus::Incomplete`].
+pub fn parse_invocations(logs: &[String]) -> Result<Vec<Invocation>, ClientError> {
+    let mut invocations = Vec::new();
+    let mut stack: Vec<Invocation> = Vec::new();
+
+    for log in logs {
+        if log == LOG_TRUNCATED {
+            break;
+        }
+
+        if let Some((program_id, depth)) = parse_invoke(log) {
+            if depth != stack.len() + 1 {
+                return Err(log_error(log));
+            }
+            stack.push(Invocation::new(parse_pubkey(program_id, log)?, depth));
+            continue;
+        }
+
+        let current = stack.last_mut().ok_or_else(|| log_error(log))?;
+        if let Some(msg) = log.strip_prefix(PROGRAM_LOG) {
+            current.logs.push(msg.to_string());
+        } else if let Some(data) = log.strip_prefix(PROGRAM_DATA) {
+            let fields = data
+                .split(' ')
+                .map(|field| STANDARD.decode(field))
+                .collect::<Result<_, _>>()
+                .map_err(|_| log_error(log))?;
+            current.data.push(fields);
+        } else if let Some(return_data) = log.strip_prefix(PROGRAM_RETURN) {
+            let (_, data) = return_data.split_once(' ').ok_or_else(|| log_error(log))?;
+            current.return_data = Some(STANDARD.decode(data).map_err(|_| log_error(log))?);
+        } else if let Some((consumed, budget)) = parse_consumed(&current.program_id, log) {
+            current.compute_units_consumed = Some(consumed);
+            current.compute_units_budget = Some(budget);
+        } else if let Some(status) = parse_status(&current.program_id, log) {
+            let mut invocation = stack.pop().expect("stack is not empty");
+            invocation.status = status;
+            match stack.last_mut() {
+                Some(parent) => parent.inner.push(invocation),
+                None => invocations.push(invocation),
+            }
+        } else {
+            current.logs.push(log.to_string());
+        }
+    }
+
+    // Attach the invocations that did not return to their parent
+    while let Some(invocation) = stack.pop() {
+        match stack.last_mut() {
+            Some(parent) => parent.inner.push(invocation),
+            None => invocations.push(invocation),
+        }
+    }
+
+    Ok(invocations)
+}
+
+/// Parses a `Program <id> invoke [<depth>]` log.
+pub(crate) fn parse_invoke(log: &str) -> Option<(&str, usize)> {
+    let (program_id, depth) = log
+        .strip_prefix("Program ")?
+        .strip_suffix(']')?
+        .split_once(" invoke [")?;
+    Some((program_id, depth.parse().ok()?))
+}
+
+/// Parses a `Program <id> consumed <consumed> of <budget> compute units` log.
+fn parse_consumed(program_id: &Pubkey, log: &str) -> Option<(u64, u64)> {
+    let (consumed, budget) = strip_program_prefix(program_id, log)?
+        .strip_prefix("consumed ")?
+        .strip_suffix(" compute units")?
+        .split_once(" of ")?;
+    Some((consumed.parse().ok()?, budget.parse().ok()?))
+}
+
+/// Parses a `Program <id> success` or `Program <id> failed: <error>` log.
+pub(crate) fn parse_status(program_id: &Pubkey, log: &str) -> Option<InvocationStatus> {
+    let status = strip_program_prefix(program_id, log)?;
+    if status == "success" {
+        Some(InvocationStatus::Success)
+    } else {
+        status
+            .strip_prefix("failed: ")
+            .map(|err| InvocationStatus::Failed(err.to_string()))
+    }
+}
+
+fn strip_program_prefix<'a>(program_id: &Pubkey, log: &'a str) -> Option<&'a str> {
+    log.strip_prefix("Program ")?
+        .strip_prefix(program_id.to_string().as_str())?
+        .strip_prefix(' ')
+}
+
+fn parse_pubkey(pubkey: &str, log: &str) -> Result<Pubkey, ClientError> {
+    pubkey.parse().map_err(|_| log_error(log))
+}
+
+fn log_error(log: &str) -> ClientError {
+    ClientError::LogParseError(log.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn logs(logs: &[&str]) -> Vec<String> {
+        logs.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_invocations() {
+        let program = "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS";
+        let system = "11111111111111111111111111111111";
+        let logs = logs(&[
+            "Program Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS invoke [1]",
+            "Program log: Instruction: Initialize",
+            "Program 11111111111111111111111111111111 invoke [2]",
+            "Program 11111111111111111111111111111111 success",
+            "Program data: AQI= Aw==",
+            "Program return: Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS BQ==",
+            "Program Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS consumed 5000 of 200000 compute units",
+            "Program Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS success",
+            "Program Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS invoke [1]",
+            "Program log: AnchorError occurred.",
+            "Program Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS consumed 1411 of 195000 compute units",
+            "Program Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS failed: custom program error: 0x7d1",
+        ]);
+        let invocations = parse_invocations(&logs).unwrap();
+        assert_eq!(invocations.len(), 2);
+
+        let first = &invocations[0];
+        assert_eq!(first.program_id.to_string(), program);
+        assert!(first.is_ok());
+        assert_eq!(first.logs, vec!["Instruction: Initialize"]);
+        assert_eq!(first.data, vec![vec![vec![1, 2], vec![3]]]);
+        assert_eq!(first.return_data, Some(vec![5]));
+        assert_eq!(first.compute_units_consumed, Some(5000));
+        assert_eq!(first.compute_units_budget, Some(200000));
+        assert_eq!(first.inner.len(), 1);
+        assert_eq!(first.inner[0].program_id.to_string(), system);
+        assert_eq!(first.inner[0].depth, 2);
+        assert!(first.inner[0].is_ok());
+        assert_eq!(first.iter().count(), 2);
+
+        let second = &invocations[1];
+        assert_eq!(
+            second.status,
+            InvocationStatus::Failed("custom program error: 0x7d1".to_string())
+        );
+        assert_eq!(second.compute_units_budget, Some(195000));
+    }
+
+    #[test]
+    fn test_parse_invocations_truncated() {
+        let logs = logs(&[
+            "Program Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS invoke [1]",
+            "Program 11111111111111111111111111111111 invoke [2]",
+            "Log truncated",
+        ]);
+        let invocations = parse_invocations(&logs).unwrap();
+        assert_eq!(invocations.len(), 1);
+        assert_eq!(invocations[0].status, InvocationStatus::Incomplete);
+        assert_eq!(invocations[0].inner[0].status, InvocationStatus::Incomplete);
+
+        // Invalid depth
+        let logs = logs[1..2].to_vec();
+        assert!(parse_invocations(&logs).is_err());
+    }
+}
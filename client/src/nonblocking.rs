@@ -1,15 +1,34 @@
+#[cfg(feature = "surfnet")]
+use crate::TimeTravel;
 use crate::{
-    AsSigner, ClientError, Config, EventContext, EventUnsubscriber, Program,
-    ProgramAccountsIterator, RequestBuilder,
+    AccountStream, AsSigner, BackfillConfig, BatchConfig, BatchOutcome, ClientError, Config,
+    DecodedValue, DynamicProgram, ErrorRegistry, EventContext, EventStream, EventStreamConfig,
+    EventUnsubscriber, Program, ProgramAccountsIterator, RequestBuilder, ResolvedAccounts,
+    SendConfig, SendOutcome, SharedSender, Simulation, TransactionEncoding, Transport,
+    TransportSender,
 };
-use anchor_lang::{prelude::Pubkey, AccountDeserialize, Discriminator};
+use anchor_lang::{
+    prelude::Pubkey, AccountDeserialize, AnchorDeserialize, AnchorSerialize, Discriminator,
+    ZeroCopy,
+};
+#[cfg(feature = "surfnet")]
+use anchor_lang::{AccountSerialize, Owner};
+use anchor_lang_idl::types::Idl;
+use futures::Stream;
+#[cfg(feature = "surfnet")]
+use solana_account::Account;
 use solana_commitment_config::CommitmentConfig;
 use solana_rpc_client::nonblocking::rpc_client::RpcClient as AsyncRpcClient;
+use solana_rpc_client::rpc_client::RpcClientConfig;
 use solana_rpc_client_api::{config::RpcSendTransactionConfig, filter::RpcFilterType};
 use solana_signature::Signature;
 use solana_signer::Signer;
-use solana_transaction::Transaction;
-use std::{marker::PhantomData, ops::Deref, sync::Arc};
+use solana_transaction::versioned::VersionedTransaction;
+use std::{
+    marker::PhantomData,
+    ops::{Deref, Range},
+    sync::Arc,
+};
 use tokio::sync::RwLock;
 
 impl<'a> EventUnsubscriber<'a> {
@@ -42,17 +61,57 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
         #[cfg(feature = "mock")] rpc_client: AsyncRpcClient,
     ) -> Result<Self, ClientError> {
         #[cfg(not(feature = "mock"))]
-        let rpc_client = {
-            let comm_config = cfg.options.unwrap_or_default();
-            let cluster_url = cfg.cluster.url().to_string();
-            AsyncRpcClient::new_with_commitment(cluster_url.clone(), comm_config)
-        };
+        let sender = SharedSender(Arc::new(cfg.cluster.http_sender()?));
+        #[cfg(not(feature = "mock"))]
+        let rpc_client = AsyncRpcClient::new_sender(
+            sender.clone(),
+            RpcClientConfig::with_commitment(cfg.options.unwrap_or_default()),
+        );
 
+        Self::new_with_rpc_client(
+            program_id,
+            cfg,
+            rpc_client,
+            #[cfg(not(feature = "mock"))]
+            sender,
+        )
+    }
+
+    /// Creates a program whose RPC requests are served by the given [`Transport`] instead of the
+    /// cluster.
+    pub fn new_with_transport(
+        program_id: Pubkey,
+        cfg: Config<C>,
+        transport: impl Transport,
+    ) -> Result<Self, ClientError> {
+        let sender = SharedSender(Arc::new(TransportSender(transport)));
+        let rpc_client = AsyncRpcClient::new_sender(
+            sender.clone(),
+            RpcClientConfig::with_commitment(cfg.options.unwrap_or_default()),
+        );
+        Self::new_with_rpc_client(
+            program_id,
+            cfg,
+            rpc_client,
+            #[cfg(not(feature = "mock"))]
+            sender,
+        )
+    }
+
+    fn new_with_rpc_client(
+        program_id: Pubkey,
+        cfg: Config<C>,
+        rpc_client: AsyncRpcClient,
+        #[cfg(not(feature = "mock"))] sender: SharedSender,
+    ) -> Result<Self, ClientError> {
         Ok(Self {
             program_id,
             cfg,
             sub_client: Arc::new(RwLock::new(None)),
             internal_rpc_client: rpc_client,
+            #[cfg(not(feature = "mock"))]
+            sender,
+            errors: ErrorRegistry::default(),
         })
     }
 
@@ -63,9 +122,9 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
     // using the mock feature likely already has a `RpcClient` instance at hand anyway.
     #[cfg(not(feature = "mock"))]
     pub fn rpc(&self) -> AsyncRpcClient {
-        AsyncRpcClient::new_with_commitment(
-            self.cfg.cluster.url().to_string(),
-            self.cfg.options.unwrap_or_default(),
+        AsyncRpcClient::new_sender(
+            self.sender.clone(),
+            RpcClientConfig::with_commitment(self.cfg.options.unwrap_or_default()),
         )
     }
 
@@ -78,6 +137,7 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
             self.cfg.options,
             &self.internal_rpc_client,
         )
+        .errors(self.errors.clone())
     }
 
     /// Returns the account at the given address.
@@ -85,6 +145,37 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
         self.account_internal(address).await
     }
 
+    /// Returns the account at the given address decoded into JSON with the IDL of the given
+    /// dynamic program.
+    pub async fn dynamic_account(
+        &self,
+        program: &DynamicProgram,
+        address: Pubkey,
+    ) -> Result<DecodedValue, ClientError> {
+        self.dynamic_account_internal(program, address).await
+    }
+
+    /// Returns the accounts at the given addresses, in the same order.
+    ///
+    /// The accounts are fetched with as few `getMultipleAccounts` requests as possible, and
+    /// missing accounts are returned as `None`.
+    pub async fn accounts_by_keys<T: AccountDeserialize>(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<Option<T>>, ClientError> {
+        self.accounts_by_keys_internal(addresses).await
+    }
+
+    /// Returns the zero-copy accounts at the given addresses, in the same order.
+    ///
+    /// See [`Self::accounts_by_keys`].
+    pub async fn zero_copy_accounts_by_keys<T: ZeroCopy>(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<Option<T>>, ClientError> {
+        self.zero_copy_accounts_by_keys_internal(addresses).await
+    }
+
     /// Returns all program accounts of the given type matching the given filters
     pub async fn accounts<T: AccountDeserialize + Discriminator>(
         &self,
@@ -102,6 +193,163 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
         self.accounts_lazy_internal(filters).await
     }
 
+    /// Subscribe to the changes of the account at the given address.
+    ///
+    /// See [`AccountStream`] for how the updates are delivered.
+    pub async fn subscribe_account<T: AccountDeserialize + Send + 'static>(
+        &self,
+        address: Pubkey,
+    ) -> Result<AccountStream<T>, ClientError> {
+        self.subscribe_account_internal(address).await
+    }
+
+    /// Subscribe to the changes of all program accounts of the given type matching the given
+    /// filters.
+    ///
+    /// See [`AccountStream`] for how the updates are delivered.
+    pub async fn subscribe_program_accounts<T>(
+        &self,
+        filters: Vec<RpcFilterType>,
+    ) -> Result<AccountStream<T>, ClientError>
+    where
+        T: AccountDeserialize + Discriminator + Send + 'static,
+    {
+        self.subscribe_program_accounts_internal(filters).await
+    }
+
+    /// Resolves the accounts of the given instruction from its IDL definition.
+    ///
+    /// The `known` accounts are used as is. The other accounts are resolved from their constant
+    /// `address`, their `pda` seeds (which may read the instruction arguments and the fields of
+    /// other accounts), or their `relations`. Signer accounts that can't be resolved, e.g. the
+    /// payer, must be known, and missing optional accounts are set to the program ID.
+    ///
+    /// `args` is the arguments struct of the instruction, e.g. `args::Initialize`.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// let accounts = program.resolve_accounts(
+    ///     &idl,
+    ///     "initialize",
+    ///     &[("mint", mint), ("payer", program.payer())],
+    ///     &args::Initialize { amount: 1 },
+    /// ).await?;
+    /// program
+    ///     .request()
+    ///     .accounts(accounts)
+    ///     .args(args::Initialize { amount: 1 })
+    ///     .send().await?;
+    /// ```
+    pub async fn resolve_accounts<A: AnchorSerialize>(
+        &self,
+        idl: &Idl,
+        instruction: &str,
+        known: &[(&str, Pubkey)],
+        args: &A,
+    ) -> Result<ResolvedAccounts, ClientError> {
+        self.resolve_accounts_internal(idl, instruction, known, args)
+            .await
+    }
+
+    /// Sends a transaction signed offline, e.g. merged with [`merge_signatures`](crate::merge_signatures),
+    /// and waits for its confirmation.
+    ///
+    /// Fails without sending the transaction if any of its signatures is missing.
+    pub async fn send_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> Result<Signature, ClientError> {
+        self.send_transaction_internal(tx).await
+    }
+
+    /// Sets the data of an account of a surfnet to the serialized `account`, owned by its program
+    /// and with a rent exempt balance.
+    #[cfg(feature = "surfnet")]
+    pub async fn surfnet_set_account<T: AccountSerialize + Owner>(
+        &self,
+        address: Pubkey,
+        account: &T,
+    ) -> Result<(), ClientError> {
+        self.surfnet_set_account_internal(address, account).await
+    }
+
+    /// Sets the state of an account of a surfnet.
+    #[cfg(feature = "surfnet")]
+    pub async fn surfnet_set_raw_account(
+        &self,
+        address: Pubkey,
+        account: &Account,
+    ) -> Result<(), ClientError> {
+        self.surfnet_set_raw_account_internal(address, account)
+            .await
+    }
+
+    /// Sets the balance of the associated token account of `owner` for `mint` on a surfnet,
+    /// creating the account if needed.
+    #[cfg(feature = "surfnet")]
+    pub async fn surfnet_set_token_balance(
+        &self,
+        owner: Pubkey,
+        mint: Pubkey,
+        amount: u64,
+        token_program: Pubkey,
+    ) -> Result<(), ClientError> {
+        self.surfnet_set_token_balance_internal(owner, mint, amount, token_program)
+            .await
+    }
+
+    /// Moves the clock of a surfnet to the given epoch, slot or timestamp.
+    #[cfg(feature = "surfnet")]
+    pub async fn surfnet_time_travel(&self, time_travel: TimeTravel) -> Result<(), ClientError> {
+        self.surfnet_time_travel_internal(time_travel).await
+    }
+
+    /// Moves the clock of a surfnet to the given slot.
+    #[cfg(feature = "surfnet")]
+    pub async fn surfnet_warp_to_slot(&self, slot: u64) -> Result<(), ClientError> {
+        self.surfnet_time_travel_internal(TimeTravel::AbsoluteSlot(slot))
+            .await
+    }
+
+    /// Resets an account of a surfnet to its original state, e.g. the state fetched from the
+    /// remote datasource, along with the accounts it owns if `include_owned_accounts` is set.
+    #[cfg(feature = "surfnet")]
+    pub async fn surfnet_reset_account(
+        &self,
+        address: Pubkey,
+        include_owned_accounts: bool,
+    ) -> Result<(), ClientError> {
+        self.surfnet_reset_account_internal(address, include_owned_accounts)
+            .await
+    }
+
+    /// Returns a stream of the events of type `T` emitted by this program in the transaction
+    /// history, including the ones emitted with `emit_cpi!`.
+    ///
+    /// The program's signatures are paged lazily with `getSignaturesForAddress` and the
+    /// transactions are fetched with bounded concurrency, see [`BackfillConfig`]. Failed
+    /// transactions are skipped. Transactions are returned from the newest to the oldest unless
+    /// [`BackfillOrder::OldestFirst`](crate::BackfillOrder::OldestFirst) is set, each with its events in execution order, and
+    /// stream ends after the first error.
+    pub fn backfill_events<T: anchor_lang::Event>(
+        &self,
+        config: BackfillConfig,
+    ) -> impl Stream<Item = Result<(EventContext, T), ClientError>> + '_ {
+        self.backfill_events_internal(config)
+    }
+
+    /// Fetches a confirmed transaction and returns the events of type `T` emitted by this
+    /// program, including the ones emitted with `emit_cpi!`.
+    ///
+    /// See [`parse_transaction_events`](crate::parse_transaction_events) for the event order.
+    pub async fn transaction_events<T: anchor_lang::Event>(
+        &self,
+        signature: &Signature,
+    ) -> Result<Vec<T>, ClientError> {
+        self.transaction_events_internal(signature).await
+    }
+
     /// Subscribe to program logs.
     ///
     /// Returns an [`EventUnsubscriber`] to unsubscribe and close connection gracefully.
@@ -117,6 +365,20 @@ impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
             _lifetime_marker: PhantomData,
         })
     }
+
+    /// Subscribe to the events of type `T` as a [`Stream`](futures::Stream) that resubscribes
+    /// automatically when the connection is interrupted.
+    ///
+    /// See [`EventStream`] for how interruptions are reported.
+    pub async fn event_stream<T>(
+        &self,
+        config: EventStreamConfig,
+    ) -> Result<EventStream<T>, ClientError>
+    where
+        T: anchor_lang::Event + anchor_lang::AnchorDeserialize + Send + 'static,
+    {
+        self.event_stream_internal(config).await
+    }
 }
 
 impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Arc<dyn ThreadSafeSigner>> {
@@ -136,6 +398,13 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Arc<dyn T
             instructions: Vec::new(),
             instruction_data: None,
             signers: Vec::new(),
+            lookup_tables: Vec::new(),
+            lookup_table_addresses: Vec::new(),
+            compute_budget: None,
+            errors: ErrorRegistry::default(),
+            atomic_groups: Vec::new(),
+            nonce: None,
+            blockhash: None,
             internal_rpc_client: rpc_client,
             _phantom: PhantomData,
         }
@@ -147,14 +416,71 @@ impl<'a, C: Deref<Target = impl Signer> + Clone> RequestBuilder<'a, C, Arc<dyn T
         self
     }
 
-    pub async fn signed_transaction(&self) -> Result<Transaction, ClientError> {
+    pub async fn signed_transaction(&self) -> Result<VersionedTransaction, ClientError> {
         self.signed_transaction_internal().await
     }
 
+    /// Builds and signs the transaction with the available signers, leaving the signatures of
+    /// the other signers empty.
+    ///
+    /// This is meant for transactions signed by several parties, usually with a durable nonce,
+    /// see [`Self::nonce`]. Signers that are not available can be passed as
+    /// [`NullSigner`](solana_signer::null_signer::NullSigner)s, including the payer.
+    pub async fn partially_signed_transaction(&self) -> Result<VersionedTransaction, ClientError> {
+        self.partially_signed_transaction_internal().await
+    }
+
+    /// Builds and partially signs the transaction like [`Self::partially_signed_transaction`],
+    /// and serializes it to be signed by the other parties.
+    pub async fn sign_only(&self, encoding: TransactionEncoding) -> Result<String, ClientError> {
+        self.sign_only_internal(encoding).await
+    }
+
+    /// Simulates the transaction without sending it.
+    ///
+    /// The transaction isn't signed, so the signers of the request aren't required.
+    pub async fn simulate(&self) -> Result<Simulation, ClientError> {
+        self.simulate_internal().await
+    }
+
+    /// Simulates the transaction and deserializes the data returned by the program as `T`.
+    ///
+    /// This is meant for instructions with a return value, allowing to read them without
+    /// sending a transaction.
+    pub async fn view<T: AnchorDeserialize>(&self) -> Result<T, ClientError> {
+        self.view_internal().await
+    }
+
     pub async fn send(self) -> Result<Signature, ClientError> {
         self.send_internal().await
     }
 
+    /// Sends the transaction with the given sender configuration and waits for its confirmation.
+    ///
+    /// The transaction is rebroadcast at intervals until it's confirmed or its blockhash expires,
+    /// and is optionally signed again with a new blockhash after it expired. Failed and expired
+    /// transactions are reported in the returned [`SendOutcome`] rather than as errors.
+    pub async fn send_with_config(self, config: SendConfig) -> Result<SendOutcome, ClientError> {
+        self.send_with_config_internal(config).await
+    }
+
+    /// Returns the ranges of the instructions of the transactions that [`Self::send_batched`]
+    /// splits the request into.
+    pub async fn batches(&self) -> Result<Vec<Range<usize>>, ClientError> {
+        self.batches_internal().await
+    }
+
+    /// Sends the instructions of the request in as many transactions as needed to fit them in
+    /// the packet size limit.
+    ///
+    /// Instructions are packed into the fewest transactions while keeping their order, and the
+    /// instructions added with [`Self::atomic_instructions`] are kept in the same transaction.
+    /// Each transaction is signed by the signers it requires and sent like
+    /// [`Self::send_with_config`], see [`BatchConfig`] for how failures are handled.
+    pub async fn send_batched(self, config: BatchConfig) -> Result<Vec<BatchOutcome>, ClientError> {
+        self.send_batched_internal(config).await
+    }
+
     pub async fn send_with_spinner_and_config(
         self,
         config: RpcSendTransactionConfig,
@@ -0,0 +1,195 @@
+use crate::ClientError;
+use anchor_lang::__private::base64;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use solana_signature::Signature;
+use solana_transaction::versioned::VersionedTransaction;
+use std::path::Path;
+
+/// Encoding of the transactions serialized for offline signing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransactionEncoding {
+    Base58,
+    #[default]
+    Base64,
+}
+
+/// Serializes a (partially) signed transaction, e.g. to pass it to the other signers.
+pub fn encode_transaction(
+    tx: &VersionedTransaction,
+    encoding: TransactionEncoding,
+) -> Result<String, ClientError> {
+    let bytes = bincode::serialize(tx).map_err(|e| offline_error(e.to_string()))?;
+    Ok(match encoding {
+        TransactionEncoding::Base58 => bs58::encode(bytes).into_string(),
+        TransactionEncoding::Base64 => STANDARD.encode(bytes),
+    })
+}
+
+/// Deserializes a transaction serialized with [`encode_transaction`].
+///
+/// Surrounding whitespace is ignored, so the transaction can be read as is from a file.
+pub fn decode_transaction(
+    encoded: &str,
+    encoding: TransactionEncoding,
+) -> Result<VersionedTransaction, ClientError> {
+    let encoded = encoded.trim();
+    let bytes = match encoding {
+        TransactionEncoding::Base58 => bs58::decode(encoded)
+            .into_vec()
+            .map_err(|e| offline_error(e.to_string()))?,
+        TransactionEncoding::Base64 => STANDARD
+            .decode(encoded)
+            .map_err(|e| offline_error(e.to_string()))?,
+    };
+    bincode::deserialize(&bytes).map_err(|e| offline_error(e.to_string()))
+}
+
+/// Merges the signatures of copies of the same transaction signed by different parties.
+///
+/// Every signature is verified against the message, and all the transactions must have the same
+/// message. Signatures that none of the transactions have are left empty, see
+/// [`missing_signers`].
+pub fn merge_signatures(
+    txs: impl IntoIterator<Item = VersionedTransaction>,
+) -> Result<VersionedTransaction, ClientError> {
+    let mut txs = txs.into_iter();
+    let mut merged = txs
+        .next()
+        .ok_or_else(|| offline_error("no transaction to merge".to_string()))?;
+    let message_data = merged.message.serialize();
+    let keys = merged.message.static_account_keys().to_vec();
+    verify_signatures(&merged.signatures, &keys, &message_data)?;
+
+    for tx in txs {
+        if tx.message.serialize() != message_data {
+            return Err(offline_error(
+                "transactions have different messages".to_string(),
+            ));
+        }
+        verify_signatures(&tx.signatures, &keys, &message_data)?;
+        for (merged_sig, sig) in merged.signatures.iter_mut().zip(tx.signatures) {
+            if *merged_sig == Signature::default() {
+                *merged_sig = sig;
+            }
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Reads and merges transactions serialized with [`encode_transaction`] from the given files.
+///
+/// See [`merge_signatures`].
+pub fn merge_signature_files(
+    paths: impl IntoIterator<Item = impl AsRef<Path>>,
+    encoding: TransactionEncoding,
+) -> Result<VersionedTransaction, ClientError> {
+    let txs = paths
+        .into_iter()
+        .map(|path| decode_transaction(&std::fs::read_to_string(path)?, encoding))
+        .collect::<Result<Vec<_>, _>>()?;
+    merge_signatures(txs)
+}
+
+/// Returns the signers whose signature is missing from the transaction.
+pub fn missing_signers(tx: &VersionedTransaction) -> Vec<Pubkey> {
+    tx.message
+        .static_account_keys()
+        .iter()
+        .zip(&tx.signatures)
+        .filter(|(_, sig)| **sig == Signature::default())
+        .map(|(key, _)| *key)
+        .collect()
+}
+
+fn verify_signatures(
+    signatures: &[Signature],
+    keys: &[Pubkey],
+    message_data: &[u8],
+) -> Result<(), ClientError> {
+    for (sig, key) in signatures.iter().zip(keys) {
+        if *sig != Signature::default() && !sig.verify(key.as_ref(), message_data) {
+            return Err(offline_error(format!("invalid signature for {key}")));
+        }
+    }
+    Ok(())
+}
+
+fn offline_error(msg: String) -> ClientError {
+    ClientError::OfflineTransactionError(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::system_instruction;
+    use solana_keypair::Keypair;
+    use solana_message::{Message, VersionedMessage};
+    use solana_program::hash::Hash;
+    use solana_signer::Signer;
+
+    /// Returns a transfer from `from` paid by `payer`, signed by `signer` only.
+    fn transfer(
+        payer: &Keypair,
+        from: &Keypair,
+        signer: &Keypair,
+        lamports: u64,
+    ) -> VersionedTransaction {
+        let ix = system_instruction::transfer(&from.pubkey(), &Pubkey::default(), lamports);
+        let message = VersionedMessage::Legacy(Message::new_with_blockhash(
+            &[ix],
+            Some(&payer.pubkey()),
+            &Hash::new_from_array([1; 32]),
+        ));
+        let message_data = message.serialize();
+        let signatures = message.static_account_keys()[..2]
+            .iter()
+            .map(|key| {
+                if *key == signer.pubkey() {
+                    signer.sign_message(&message_data)
+                } else {
+                    Signature::default()
+                }
+            })
+            .collect();
+        VersionedTransaction {
+            signatures,
+            message,
+        }
+    }
+
+    #[test]
+    fn test_encode_transaction() {
+        let (payer, from) = (Keypair::new(), Keypair::new());
+        let tx = transfer(&payer, &from, &payer, 1);
+        for encoding in [TransactionEncoding::Base58, TransactionEncoding::Base64] {
+            let encoded = encode_transaction(&tx, encoding).unwrap();
+            let decoded = decode_transaction(&format!("{encoded}\n"), encoding).unwrap();
+            assert_eq!(decoded, tx);
+        }
+    }
+
+    #[test]
+    fn test_merge_signatures() {
+        let (payer, from) = (Keypair::new(), Keypair::new());
+        let payer_tx = transfer(&payer, &from, &payer, 1);
+        let from_tx = transfer(&payer, &from, &from, 1);
+        assert_eq!(missing_signers(&payer_tx), vec![from.pubkey()]);
+
+        let merged = merge_signatures([payer_tx.clone(), from_tx.clone()]).unwrap();
+        assert!(missing_signers(&merged).is_empty());
+        let keys = merged.message.static_account_keys();
+        assert!(verify_signatures(&merged.signatures, keys, &merged.message.serialize()).is_ok());
+
+        // Invalid signature
+        let mut invalid_tx = from_tx;
+        invalid_tx.signatures.swap(0, 1);
+        assert!(merge_signatures([payer_tx.clone(), invalid_tx]).is_err());
+
+        // Different message
+        let other_tx = transfer(&payer, &from, &from, 2);
+        assert!(merge_signatures([payer_tx, other_tx]).is_err());
+    }
+}
@@ -0,0 +1,537 @@
+use crate::idl::{field_bytes, find_type, struct_fields, Field};
+use crate::ClientError;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::ToAccountMetas;
+use anchor_lang_idl::types::{
+    Idl, IdlInstructionAccount, IdlInstructionAccountItem, IdlSeed, IdlType,
+};
+use solana_commitment_config::CommitmentConfig;
+use solana_rpc_client::nonblocking::rpc_client::RpcClient as AsyncRpcClient;
+use std::collections::HashMap;
+
+/// Accounts of an instruction resolved from the IDL, in the order of the instruction's accounts.
+///
+/// Accounts of composite account structs are named with the path from the instruction's
+/// accounts struct, e.g. `nested.vault`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolvedAccounts {
+    accounts: Vec<(String, AccountMeta)>,
+}
+
+impl ResolvedAccounts {
+    /// Returns the address of the account with the given name.
+    pub fn get(&self, name: &str) -> Option<Pubkey> {
+        self.accounts
+            .iter()
+            .find(|(account_name, _)| account_name == name)
+            .map(|(_, meta)| meta.pubkey)
+    }
+
+    /// Returns the names and the metas of the accounts.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &AccountMeta)> {
+        self.accounts
+            .iter()
+            .map(|(name, meta)| (name.as_str(), meta))
+    }
+}
+
+impl ToAccountMetas for ResolvedAccounts {
+    fn to_account_metas(&self, _is_signer: Option<bool>) -> Vec<AccountMeta> {
+        self.accounts.iter().map(|(_, meta)| meta.clone()).collect()
+    }
+}
+
+/// An instruction account along with its path from the instruction's accounts struct.
+struct FlatAccount<'a> {
+    path: String,
+    /// Prefix of the paths of the accounts in the same accounts struct.
+    scope: String,
+    account: &'a IdlInstructionAccount,
+}
+
+fn flatten_accounts<'a>(
+    items: &'a [IdlInstructionAccountItem],
+    scope: &str,
+    accounts: &mut Vec<FlatAccount<'a>>,
+) {
+    for item in items {
+        match item {
+            IdlInstructionAccountItem::Single(account) => accounts.push(FlatAccount {
+                path: format!("{scope}{}", account.name),
+                scope: scope.to_owned(),
+                account,
+            }),
+            IdlInstructionAccountItem::Composite(composite) => flatten_accounts(
+                &composite.accounts,
+                &format!("{scope}{}.", composite.name),
+                accounts,
+            ),
+        }
+    }
+}
+
+/// Resolves the accounts of an instruction from the `address`, `pda` and `relations` fields of
+/// its IDL definition.
+pub(crate) struct AccountResolver<'a> {
+    rpc: &'a AsyncRpcClient,
+    idl: &'a Idl,
+    program_id: Pubkey,
+    args: Vec<Field<'a>>,
+    args_data: &'a [u8],
+    resolved: HashMap<String, Pubkey>,
+    fetched: HashMap<Pubkey, Option<Vec<u8>>>,
+}
+
+impl<'a> AccountResolver<'a> {
+    pub(crate) fn new(
+        rpc: &'a AsyncRpcClient,
+        idl: &'a Idl,
+        program_id: Pubkey,
+        args_data: &'a [u8],
+    ) -> Self {
+        Self {
+            rpc,
+            idl,
+            program_id,
+            args: vec![],
+            args_data,
+            resolved: HashMap::new(),
+            fetched: HashMap::new(),
+        }
+    }
+
+    /// Resolves the accounts of the given instruction.
+    ///
+    /// The `known` accounts are used as is, and missing optional accounts are set to the program
+    /// ID.
+    pub(crate) async fn resolve(
+        mut self,
+        instruction: &str,
+        known: &[(&str, Pubkey)],
+    ) -> Result<ResolvedAccounts, ClientError> {
+        let idl = self.idl;
+        let ix = idl
+            .instructions
+            .iter()
+            .find(|ix| ix.name == instruction)
+            .ok_or_else(|| {
+                resolution_error(format!("instruction `{instruction}` not found in the IDL"))
+            })?;
+        self.args = ix
+            .args
+            .iter()
+            .map(|f| Field {
+                name: f.name.clone(),
+                ty: &f.ty,
+                padding: 0,
+            })
+            .collect();
+
+        let mut accounts = vec![];
+        flatten_accounts(&ix.accounts, "", &mut accounts);
+
+        self.resolved
+            .extend(known.iter().map(|(name, key)| (name.to_string(), *key)));
+
+        // Accounts may depend on each other, so resolve until no more progress is made
+        loop {
+            let mut progress = false;
+            for acc in &accounts {
+                if self.resolved.contains_key(&acc.path) {
+                    continue;
+                }
+                if let Some(key) = self.resolve_account(acc).await? {
+                    self.resolved.insert(acc.path.clone(), key);
+                    progress = true;
+                }
+            }
+            if !progress {
+                break;
+            }
+        }
+
+        let mut unresolved = vec![];
+        let accounts = accounts
+            .iter()
+            .map(|acc| {
+                let meta = match self.resolved.get(&acc.path) {
+                    Some(key) => AccountMeta {
+                        pubkey: *key,
+                        is_signer: acc.account.signer,
+                        is_writable: acc.account.writable,
+                    },
+                    None if acc.account.optional => {
+                        AccountMeta::new_readonly(self.program_id, false)
+                    }
+                    None => {
+                        unresolved.push(acc.path.clone());
+                        AccountMeta::new_readonly(Pubkey::default(), false)
+                    }
+                };
+                (acc.path.clone(), meta)
+            })
+            .collect();
+        if !unresolved.is_empty() {
+            return Err(resolution_error(format!(
+                "unable to resolve {}",
+                unresolved.join(", ")
+            )));
+        }
+
+        Ok(ResolvedAccounts { accounts })
+    }
+
+    async fn resolve_account(
+        &mut self,
+        acc: &FlatAccount<'a>,
+    ) -> Result<Option<Pubkey>, ClientError> {
+        let account = acc.account;
+        if let Some(address) = &account.address {
+            return address.parse().map(Some).map_err(|_| {
+                resolution_error(format!("invalid address of `{}`: {address}", acc.path))
+            });
+        }
+
+        if let Some(pda) = &account.pda {
+            let mut seeds = Vec::with_capacity(pda.seeds.len());
+            for seed in &pda.seeds {
+                match self.seed_bytes(&acc.scope, seed).await? {
+                    Some(seed) => seeds.push(seed),
+                    None => return Ok(None),
+                }
+            }
+            let program_id = match &pda.program {
+                Some(seed) => match self.seed_bytes(&acc.scope, seed).await? {
+                    Some(program_id) => Pubkey::try_from(program_id.as_slice()).map_err(|_| {
+                        resolution_error(format!("invalid PDA program of `{}`", acc.path))
+                    })?,
+                    None => return Ok(None),
+                },
+                None => self.program_id,
+            };
+            let seeds = seeds.iter().map(Vec::as_slice).collect::<Vec<_>>();
+            return Ok(Some(Pubkey::find_program_address(&seeds, &program_id).0));
+        }
+
+        // `relations` lists the accounts that store this account's address in a field of the
+        // same name, e.g. with a `has_one` constraint
+        for relation in &account.relations {
+            let Some(key) = self.lookup(&acc.scope, relation) else {
+                continue;
+            };
+            if let Some(bytes) = self.account_field(key, None, &account.name).await? {
+                return Pubkey::try_from(bytes.as_slice())
+                    .map(Some)
+                    .map_err(|_| resolution_error(format!("invalid relation of `{}`", acc.path)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the value of the given seed, or `None` if it can't be resolved yet.
+    async fn seed_bytes(
+        &mut self,
+        scope: &str,
+        seed: &IdlSeed,
+    ) -> Result<Option<Vec<u8>>, ClientError> {
+        match seed {
+            IdlSeed::Const(seed) => Ok(Some(seed.value.clone())),
+            IdlSeed::Arg(seed) => {
+                let (bytes, ty) =
+                    field_bytes(self.idl, self.args.clone(), &seed.path, self.args_data)
+                        .ok_or_else(|| {
+                            resolution_error(format!("unable to read argument `{}`", seed.path))
+                        })?;
+                Ok(Some(seed_value(bytes, ty)))
+            }
+            IdlSeed::Account(seed) => {
+                let (name, field) = match seed.path.split_once('.') {
+                    Some((name, field)) => (name, Some(field)),
+                    None => (seed.path.as_str(), None),
+                };
+                let Some(key) = self.lookup(scope, name) else {
+                    return Ok(None);
+                };
+                match field {
+                    None => Ok(Some(key.to_bytes().to_vec())),
+                    Some(field) => {
+                        self.account_field(key, seed.account.as_deref(), field)
+                            .await
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the address of the account with the given name, in the given scope or at the top
+    /// level.
+    fn lookup(&self, scope: &str, name: &str) -> Option<Pubkey> {
+        self.resolved
+            .get(&format!("{scope}{name}"))
+            .or_else(|| self.resolved.get(name))
+            .copied()
+    }
+
+    /// Fetches the account at the given address and returns the seed value of the given field,
+    /// or `None` if the account doesn't exist.
+    ///
+    /// The account type is detected from the discriminator if `account_type` is not specified.
+    /// Fields of zero-copy accounts are read with the padding of their layout.
+    async fn account_field(
+        &mut self,
+        key: Pubkey,
+        account_type: Option<&str>,
+        field: &str,
+    ) -> Result<Option<Vec<u8>>, ClientError> {
+        if !self.fetched.contains_key(&key) {
+            let account = self
+                .rpc
+                .get_account_with_commitment(&key, CommitmentConfig::processed())
+                .await
+                .map_err(Box::new)?
+                .value;
+            self.fetched
+                .insert(key, account.map(|account| account.data));
+        }
+        let Some(data) = self.fetched[&key].as_deref() else {
+            return Ok(None);
+        };
+
+        let idl_account = self
+            .idl
+            .accounts
+            .iter()
+            .find(|acc| match account_type {
+                Some(account_type) => acc.name == account_type,
+                None => data.starts_with(&acc.discriminator),
+            })
+            .ok_or_else(|| resolution_error(format!("unknown account type of {key}")))?;
+        let (bytes, ty) = find_type(self.idl, &idl_account.name)
+            .and_then(|ty| struct_fields(self.idl, ty))
+            .and_then(|fields| {
+                let data = data.get(idl_account.discriminator.len()..)?;
+                field_bytes(self.idl, fields, field, data)
+            })
+            .ok_or_else(|| {
+                resolution_error(format!(
+                    "unable to read field `{field}` of `{}`",
+                    idl_account.name
+                ))
+            })?;
+
+        Ok(Some(seed_value(bytes, ty)))
+    }
+}
+
+fn resolution_error(msg: String) -> ClientError {
+    ClientError::AccountResolutionError(msg)
+}
+
+/// Converts a serialized value to its seed representation, e.g. strings are used without their
+/// length prefix.
+fn seed_value(bytes: &[u8], ty: &IdlType) -> Vec<u8> {
+    match ty {
+        IdlType::String | IdlType::Bytes => bytes[4..].to_vec(),
+        IdlType::Vec(ty) if matches!(**ty, IdlType::U8) => bytes[4..].to_vec(),
+        _ => bytes.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AnchorSerialize;
+
+    fn idl() -> Idl {
+        serde_json::from_value(serde_json::json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "initialize",
+                "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+                "accounts": [
+                    { "name": "authority", "writable": true, "signer": true },
+                    {
+                        "name": "vault",
+                        "writable": true,
+                        "pda": {
+                            "seeds": [
+                                { "kind": "const", "value": [118, 97, 117, 108, 116] },
+                                { "kind": "account", "path": "authority" },
+                                { "kind": "arg", "path": "name" },
+                                { "kind": "arg", "path": "config.id" }
+                            ]
+                        }
+                    },
+                    {
+                        "name": "nested",
+                        "accounts": [
+                            {
+                                "name": "vault",
+                                "pda": {
+                                    "seeds": [{ "kind": "account", "path": "authority" }],
+                                    "program": {
+                                        "kind": "const",
+                                        "value": [6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172, 28, 180, 133, 237, 95, 91, 55, 145, 58, 140, 245, 133, 126, 255, 0, 169]
+                                    }
+                                }
+                            },
+                            { "name": "authority" }
+                        ]
+                    },
+                    { "name": "maybe", "optional": true },
+                    { "name": "system_program", "address": "11111111111111111111111111111111" }
+                ],
+                "args": [
+                    { "name": "name", "type": "string" },
+                    { "name": "config", "type": { "defined": { "name": "Config" } } }
+                ]
+            }],
+            "types": [{
+                "name": "Config",
+                "type": { "kind": "struct", "fields": [{ "name": "id", "type": "u64" }] }
+            }]
+        }))
+        .unwrap()
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn args() -> Vec<u8> {
+        let mut args = vec![];
+        ("seed".to_string(), 7u64).serialize(&mut args).unwrap();
+        args
+    }
+
+    #[test]
+    fn test_resolve_accounts() {
+        let idl = idl();
+        let rpc = AsyncRpcClient::new_mock("succeeds".to_string());
+        let program_id = Pubkey::default();
+        let payer = Pubkey::new_unique();
+        let nested_authority = Pubkey::new_unique();
+        let args = args();
+
+        let accounts = block_on(AccountResolver::new(&rpc, &idl, program_id, &args).resolve(
+            "initialize",
+            &[("authority", payer), ("nested.authority", nested_authority)],
+        ))
+        .unwrap();
+
+        assert_eq!(accounts.get("authority"), Some(payer));
+        let (vault, _) = Pubkey::find_program_address(
+            &[b"vault", payer.as_ref(), b"seed", &7u64.to_le_bytes()],
+            &program_id,
+        );
+        assert_eq!(accounts.get("vault"), Some(vault));
+        let (nested_vault, _) = Pubkey::find_program_address(
+            &[nested_authority.as_ref()],
+            &"TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(accounts.get("nested.vault"), Some(nested_vault));
+        assert_eq!(accounts.get("maybe"), Some(program_id));
+
+        let metas = accounts.to_account_metas(None);
+        assert_eq!(metas.len(), 6);
+        assert!(metas[0].is_signer && metas[0].is_writable);
+        assert!(!metas[5].is_signer && !metas[5].is_writable);
+    }
+
+    #[test]
+    fn test_resolve_accounts_unresolved() {
+        let idl = idl();
+        let rpc = AsyncRpcClient::new_mock("succeeds".to_string());
+        let args = args();
+
+        // `nested.authority` is neither known nor derivable
+        let err = block_on(
+            AccountResolver::new(&rpc, &idl, Pubkey::default(), &args)
+                .resolve("initialize", &[("authority", Pubkey::new_unique())]),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("nested.authority"));
+
+        // Signers must be known unless they can be derived
+        let err = block_on(
+            AccountResolver::new(&rpc, &idl, Pubkey::default(), &args)
+                .resolve("initialize", &[("nested.authority", Pubkey::new_unique())]),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unable to resolve authority"));
+    }
+
+    #[test]
+    fn test_resolve_accounts_zero_copy_field() {
+        use solana_rpc_client::mock_sender::MocksMap;
+        use solana_rpc_client_api::request::RpcRequest;
+
+        let idl: Idl = serde_json::from_value(serde_json::json!({
+            "address": "11111111111111111111111111111111",
+            "metadata": { "name": "test", "version": "0.1.0", "spec": "0.1.0" },
+            "instructions": [{
+                "name": "withdraw",
+                "discriminator": [0, 0, 0, 0, 0, 0, 0, 0],
+                "accounts": [
+                    { "name": "state" },
+                    {
+                        "name": "vault",
+                        "pda": {
+                            "seeds": [{ "kind": "account", "path": "state.amount", "account": "State" }]
+                        }
+                    }
+                ],
+                "args": []
+            }],
+            "accounts": [{ "name": "State", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8] }],
+            "types": [{
+                "name": "State",
+                "serialization": "bytemuck",
+                "repr": { "kind": "c" },
+                "type": {
+                    "kind": "struct",
+                    "fields": [
+                        { "name": "bump", "type": "u8" },
+                        { "name": "amount", "type": "u64" }
+                    ]
+                }
+            }]
+        }))
+        .unwrap();
+
+        // `amount` is aligned to 8 bytes
+        let data = [&[1, 2, 3, 4, 5, 6, 7, 8], &[1; 8][..], &7u64.to_le_bytes()].concat();
+        let account = serde_json::json!({
+            "context": { "slot": 1 },
+            "value": {
+                "lamports": 1,
+                "data": [bs58::encode(&data).into_string(), "base58"],
+                "owner": "11111111111111111111111111111111",
+                "executable": false,
+                "rentEpoch": 0,
+                "space": data.len(),
+            },
+        });
+        let mocks = MocksMap::from_iter([(RpcRequest::GetAccountInfo, account)]);
+        let rpc = AsyncRpcClient::new_mock_with_mocks_map("succeeds", mocks);
+        let program_id = Pubkey::default();
+        let state = Pubkey::new_unique();
+
+        let accounts = block_on(
+            AccountResolver::new(&rpc, &idl, program_id, &[])
+                .resolve("withdraw", &[("state", state)]),
+        )
+        .unwrap();
+        let (vault, _) = Pubkey::find_program_address(&[&7u64.to_le_bytes()], &program_id);
+        assert_eq!(accounts.get("vault"), Some(vault));
+    }
+}
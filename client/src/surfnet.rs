@@ -0,0 +1,140 @@
+use crate::{ClientError, Program};
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang::{AccountSerialize, Owner};
+use serde::Serialize;
+use serde_json::{json, Value};
+use solana_account::Account;
+use solana_rpc_client_api::request::RpcRequest;
+use solana_signer::Signer;
+use std::ops::Deref;
+
+/// Point in time to move the clock of a surfnet to with `surfnet_time_travel`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TimeTravel {
+    AbsoluteEpoch(u64),
+    AbsoluteSlot(u64),
+    /// Unix timestamp in milliseconds.
+    AbsoluteTimestamp(u64),
+}
+
+impl<C: Deref<Target = impl Signer> + Clone> Program<C> {
+    pub(crate) async fn surfnet_set_account_internal<T: AccountSerialize + Owner>(
+        &self,
+        address: Pubkey,
+        account: &T,
+    ) -> Result<(), ClientError> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data)?;
+        let lamports = self
+            .internal_rpc_client
+            .get_minimum_balance_for_rent_exemption(data.len())
+            .await
+            .map_err(Box::new)?;
+        self.surfnet_set_raw_account_internal(
+            address,
+            &Account {
+                lamports,
+                data,
+                owner: T::owner(),
+                executable: false,
+                rent_epoch: 0,
+            },
+        )
+        .await
+    }
+
+    pub(crate) async fn surfnet_set_raw_account_internal(
+        &self,
+        address: Pubkey,
+        account: &Account,
+    ) -> Result<(), ClientError> {
+        self.surfnet_call("surfnet_setAccount", set_account_params(address, account))
+            .await
+    }
+
+    pub(crate) async fn surfnet_set_token_balance_internal(
+        &self,
+        owner: Pubkey,
+        mint: Pubkey,
+        amount: u64,
+        token_program: Pubkey,
+    ) -> Result<(), ClientError> {
+        let params = json!([
+            owner.to_string(),
+            mint.to_string(),
+            { "amount": amount },
+            token_program.to_string(),
+        ]);
+        self.surfnet_call("surfnet_setTokenAccount", params).await
+    }
+
+    pub(crate) async fn surfnet_time_travel_internal(
+        &self,
+        time_travel: TimeTravel,
+    ) -> Result<(), ClientError> {
+        self.surfnet_call("surfnet_timeTravel", json!([time_travel]))
+            .await
+    }
+
+    pub(crate) async fn surfnet_reset_account_internal(
+        &self,
+        address: Pubkey,
+        include_owned_accounts: bool,
+    ) -> Result<(), ClientError> {
+        let params = json!([
+            address.to_string(),
+            { "includeOwnedAccounts": include_owned_accounts },
+        ]);
+        self.surfnet_call("surfnet_resetAccount", params).await
+    }
+
+    async fn surfnet_call(&self, method: &'static str, params: Value) -> Result<(), ClientError> {
+        self.internal_rpc_client
+            .send::<Value>(RpcRequest::Custom { method }, params)
+            .await
+            .map_err(Box::new)?;
+        Ok(())
+    }
+}
+
+fn set_account_params(address: Pubkey, account: &Account) -> Value {
+    // Account data is hex encoded
+    let data: String = account.data.iter().map(|b| format!("{b:02x}")).collect();
+    json!([
+        address.to_string(),
+        {
+            "lamports": account.lamports,
+            "data": data,
+            "owner": account.owner.to_string(),
+            "executable": account.executable,
+            "rentEpoch": account.rent_epoch,
+        },
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params() {
+        let address = Pubkey::new_unique();
+        let account = Account {
+            lamports: 5,
+            data: vec![0, 15, 255],
+            owner: Pubkey::default(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        let params = set_account_params(address, &account);
+        assert_eq!(params[0], json!(address.to_string()));
+        assert_eq!(params[1]["data"], json!("000fff"));
+        assert_eq!(params[1]["lamports"], json!(5));
+
+        assert_eq!(
+            json!([TimeTravel::AbsoluteSlot(100)]),
+            json!([{ "absoluteSlot": 100 }])
+        );
+    }
+}
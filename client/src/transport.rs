@@ -0,0 +1,411 @@
+use crate::{decode_transaction, TransactionEncoding};
+use anchor_lang::solana_program::pubkey::Pubkey;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use solana_account::Account;
+use solana_account_decoder::{encode_ui_account, UiAccount, UiAccountEncoding};
+use solana_program::hash::Hash;
+use solana_rpc_client::rpc_sender::{RpcSender, RpcTransportStats};
+use solana_rpc_client_api::{
+    client_error::{ErrorKind, Result as ClientResult},
+    config::RpcProgramAccountsConfig,
+    filter::RpcFilterType,
+    request::RpcRequest,
+    response::{
+        Response as RpcResponse, RpcBlockhash, RpcKeyedAccount, RpcResponseContext,
+        RpcSimulateTransactionResult,
+    },
+};
+use solana_signature::Signature;
+use solana_transaction::versioned::VersionedTransaction;
+use solana_transaction_status_client_types::TransactionStatus;
+use std::sync::Arc;
+
+/// Backend of the RPC requests made by a [`Program`](crate::Program), e.g. an in-process SVM or
+/// recorded fixtures, to run without a validator.
+///
+/// Programs using a transport are created with
+/// [`Client::program_with_transport`](crate::Client::program_with_transport). Requests that are
+/// not covered by the transport, e.g. fetching historical transactions or subscriptions, are not
+/// supported by such programs.
+///
+/// The transport holds a single state, so the commitment of the requests is ignored. Methods are
+/// called from the client's async runtime, and should not block for long.
+pub trait Transport: Send + Sync + 'static {
+    /// Returns the account stored at the given address.
+    fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Option<Account>>;
+
+    /// Returns the accounts owned by the given program. The filters of the request are applied
+    /// by the client.
+    fn get_program_accounts(&self, program_id: &Pubkey) -> ClientResult<Vec<(Pubkey, Account)>>;
+
+    /// Processes the transaction and returns its signature.
+    ///
+    /// A transaction that fails can either be reported in its status, see
+    /// [`Self::get_signature_status`], or as an error like a failed preflight check.
+    fn send_transaction(&self, tx: &VersionedTransaction) -> ClientResult<Signature>;
+
+    /// Simulates the transaction without changing the state.
+    fn simulate_transaction(
+        &self,
+        tx: &VersionedTransaction,
+    ) -> ClientResult<RpcSimulateTransactionResult>;
+
+    /// Returns the latest blockhash and the last block height at which it's valid.
+    fn get_latest_blockhash(&self) -> ClientResult<(Hash, u64)>;
+
+    /// Returns the status of a processed transaction, or `None` if it's unknown.
+    fn get_signature_status(
+        &self,
+        signature: &Signature,
+    ) -> ClientResult<Option<TransactionStatus>>;
+
+    /// Returns the current slot.
+    fn get_slot(&self) -> ClientResult<u64>;
+
+    /// Returns the current block height, which the expiry of the blockhashes returned by
+    /// [`Self::get_latest_blockhash`] is compared against.
+    fn get_block_height(&self) -> ClientResult<u64>;
+}
+
+/// RPC sender shared by the RPC clients of a program, so that the clients returned by
+/// `Program::rpc` send their requests to the same cluster or transport as the program.
+#[derive(Clone)]
+pub(crate) struct SharedSender(pub(crate) Arc<dyn RpcSender + Send + Sync>);
+
+#[async_trait]
+impl RpcSender for SharedSender {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        self.0.send(request, params).await
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.0.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.0.url()
+    }
+}
+
+/// RPC sender that serves the requests of the RPC client with a [`Transport`].
+pub(crate) struct TransportSender<T>(pub(crate) T);
+
+#[async_trait]
+impl<T: Transport> RpcSender for TransportSender<T> {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        self.handle(request, &params)
+            .map_err(|e| e.into_with_request(request))
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        "transport".to_string()
+    }
+}
+
+impl<T: Transport> TransportSender<T> {
+    fn handle(&self, request: RpcRequest, params: &Value) -> ClientResult<Value> {
+        let param = |index: usize| params.get(index).cloned().unwrap_or(Value::Null);
+        let value = match request {
+            RpcRequest::GetAccountInfo => {
+                let pubkey = parse_param(param(0))?;
+                let account = self.0.get_account(&pubkey)?;
+                self.with_context(account.map(|account| encode_account(&pubkey, &account)))?
+            }
+            RpcRequest::GetMultipleAccounts => {
+                let pubkeys: Vec<String> = serde_json::from_value(param(0))?;
+                let accounts = pubkeys
+                    .into_iter()
+                    .map(|pubkey| {
+                        let pubkey = parse_param(Value::String(pubkey))?;
+                        let account = self.0.get_account(&pubkey)?;
+                        Ok(account.map(|account| encode_account(&pubkey, &account)))
+                    })
+                    .collect::<ClientResult<Vec<_>>>()?;
+                self.with_context(accounts)?
+            }
+            RpcRequest::GetProgramAccounts => {
+                let program_id = parse_param(param(0))?;
+                let config: Option<RpcProgramAccountsConfig> = serde_json::from_value(param(1))?;
+                let filters = config.and_then(|c| c.filters).unwrap_or_default();
+                let mut accounts = vec![];
+                for (pubkey, account) in self.0.get_program_accounts(&program_id)? {
+                    if filters_match(&filters, &account.data)? {
+                        accounts.push(RpcKeyedAccount {
+                            pubkey: pubkey.to_string(),
+                            account: encode_account(&pubkey, &account),
+                        });
+                    }
+                }
+                json!(accounts)
+            }
+            RpcRequest::SendTransaction => {
+                let signature = self.0.send_transaction(&transaction_param(params)?)?;
+                json!(signature.to_string())
+            }
+            RpcRequest::SimulateTransaction => {
+                let result = self.0.simulate_transaction(&transaction_param(params)?)?;
+                self.with_context(result)?
+            }
+            RpcRequest::GetLatestBlockhash => {
+                let (blockhash, last_valid_block_height) = self.0.get_latest_blockhash()?;
+                self.with_context(RpcBlockhash {
+                    blockhash: blockhash.to_string(),
+                    last_valid_block_height,
+                })?
+            }
+            RpcRequest::IsBlockhashValid => {
+                let blockhash: Hash = parse_param(param(0))?;
+                let (latest_blockhash, _) = self.0.get_latest_blockhash()?;
+                self.with_context(blockhash == latest_blockhash)?
+            }
+            RpcRequest::GetSignatureStatuses => {
+                let signatures: Vec<String> = serde_json::from_value(param(0))?;
+                let statuses = signatures
+                    .into_iter()
+                    .map(|signature| {
+                        self.0
+                            .get_signature_status(&parse_param(Value::String(signature))?)
+                    })
+                    .collect::<ClientResult<Vec<_>>>()?;
+                self.with_context(statuses)?
+            }
+            RpcRequest::GetSlot => json!(self.0.get_slot()?),
+            RpcRequest::GetBlockHeight => json!(self.0.get_block_height()?),
+            // No prioritization fees are paid without a cluster
+            RpcRequest::GetRecentPrioritizationFees => json!([]),
+            _ => return Err(custom_error(format!("Unsupported request: {request}"))),
+        };
+
+        Ok(value)
+    }
+
+    fn with_context<V: serde::Serialize>(&self, value: V) -> ClientResult<Value> {
+        let response = RpcResponse {
+            context: RpcResponseContext {
+                slot: self.0.get_slot()?,
+                api_version: None,
+            },
+            value,
+        };
+        Ok(serde_json::to_value(response)?)
+    }
+}
+
+fn custom_error(msg: String) -> solana_rpc_client_api::client_error::Error {
+    ErrorKind::Custom(msg).into()
+}
+
+fn parse_param<V: std::str::FromStr>(param: Value) -> ClientResult<V> {
+    param
+        .as_str()
+        .and_then(|param| param.parse().ok())
+        .ok_or_else(|| custom_error(format!("Invalid parameter: {param}")))
+}
+
+/// Decodes the transaction of `sendTransaction` and `simulateTransaction` requests.
+fn transaction_param(params: &Value) -> ClientResult<VersionedTransaction> {
+    let encoding = match params.get(1).and_then(|config| config.get("encoding")) {
+        Some(encoding) if encoding == "base58" => TransactionEncoding::Base58,
+        _ => TransactionEncoding::Base64,
+    };
+    let encoded = params
+        .get(0)
+        .and_then(Value::as_str)
+        .ok_or_else(|| custom_error("Missing transaction".to_string()))?;
+    decode_transaction(encoded, encoding).map_err(|e| custom_error(e.to_string()))
+}
+
+fn encode_account(pubkey: &Pubkey, account: &Account) -> UiAccount {
+    encode_ui_account(pubkey, account, UiAccountEncoding::Base64, None, None)
+}
+
+fn filters_match(filters: &[RpcFilterType], data: &[u8]) -> ClientResult<bool> {
+    for filter in filters {
+        let is_match = match filter {
+            RpcFilterType::DataSize(size) => data.len() as u64 == *size,
+            RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(data),
+            RpcFilterType::TokenAccountState => {
+                return Err(custom_error(
+                    "Unsupported filter: TokenAccountState".to_string(),
+                ))
+            }
+        };
+        if !is_match {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_commitment_config::CommitmentConfig;
+    use solana_keypair::Keypair;
+    use solana_message::{Message, VersionedMessage};
+    use solana_rpc_client::{
+        nonblocking::rpc_client::RpcClient as AsyncRpcClient, rpc_client::RpcClientConfig,
+    };
+    use solana_rpc_client_api::filter::Memcmp;
+    use solana_signer::Signer;
+    use solana_transaction_status_client_types::TransactionConfirmationStatus;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Transport that stores accounts and accepts every transaction without executing it.
+    #[derive(Default)]
+    struct InMemoryTransport {
+        accounts: HashMap<Pubkey, Account>,
+        signatures: Mutex<Vec<Signature>>,
+    }
+
+    impl Transport for InMemoryTransport {
+        fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Option<Account>> {
+            Ok(self.accounts.get(pubkey).cloned())
+        }
+
+        fn get_program_accounts(
+            &self,
+            program_id: &Pubkey,
+        ) -> ClientResult<Vec<(Pubkey, Account)>> {
+            Ok(self
+                .accounts
+                .iter()
+                .filter(|(_, account)| account.owner == *program_id)
+                .map(|(pubkey, account)| (*pubkey, account.clone()))
+                .collect())
+        }
+
+        fn send_transaction(&self, tx: &VersionedTransaction) -> ClientResult<Signature> {
+            self.signatures.lock().unwrap().push(tx.signatures[0]);
+            Ok(tx.signatures[0])
+        }
+
+        fn simulate_transaction(
+            &self,
+            _tx: &VersionedTransaction,
+        ) -> ClientResult<RpcSimulateTransactionResult> {
+            Err(custom_error("not implemented".to_string()))
+        }
+
+        fn get_latest_blockhash(&self) -> ClientResult<(Hash, u64)> {
+            Ok((Hash::new_from_array([1; 32]), 100))
+        }
+
+        fn get_signature_status(
+            &self,
+            signature: &Signature,
+        ) -> ClientResult<Option<TransactionStatus>> {
+            let processed = self.signatures.lock().unwrap().contains(signature);
+            Ok(processed.then_some(TransactionStatus {
+                slot: 1,
+                confirmations: None,
+                status: Ok(()),
+                err: None,
+                confirmation_status: Some(TransactionConfirmationStatus::Finalized),
+            }))
+        }
+
+        fn get_slot(&self) -> ClientResult<u64> {
+            Ok(1)
+        }
+
+        fn get_block_height(&self) -> ClientResult<u64> {
+            Ok(1)
+        }
+    }
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn test_transport_sender() {
+        let program_id = Pubkey::new_unique();
+        let account = |data: Vec<u8>| Account {
+            lamports: 1,
+            data,
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+        let (key_a, key_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let transport = InMemoryTransport {
+            accounts: HashMap::from([(key_a, account(vec![1, 2])), (key_b, account(vec![3]))]),
+            ..InMemoryTransport::default()
+        };
+        let rpc = AsyncRpcClient::new_sender(
+            TransportSender(transport),
+            RpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+        );
+
+        block_on(async {
+            assert_eq!(rpc.get_account(&key_a).await.unwrap().data, vec![1, 2]);
+            assert!(rpc.get_account(&Pubkey::new_unique()).await.is_err());
+
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    0,
+                    &[3],
+                ))]),
+                ..RpcProgramAccountsConfig::default()
+            };
+            let accounts = rpc
+                .get_program_ui_accounts_with_config(&program_id, config)
+                .await
+                .unwrap();
+            assert_eq!(accounts.len(), 1);
+            assert_eq!(accounts[0].0, key_b);
+
+            let payer = Keypair::new();
+            let message = Message::new_with_blockhash(
+                &[],
+                Some(&payer.pubkey()),
+                &rpc.get_latest_blockhash().await.unwrap(),
+            );
+            let tx = VersionedTransaction::try_new(VersionedMessage::Legacy(message), &[&payer])
+                .unwrap();
+            let signature = rpc.send_and_confirm_transaction(&tx).await.unwrap();
+            assert_eq!(signature, tx.signatures[0]);
+
+            // Requests that are not covered by the transport
+            assert!(rpc.get_transaction_count().await.is_err());
+        });
+    }
+
+    #[cfg(not(any(feature = "mock", feature = "async")))]
+    #[test]
+    fn test_program_rpc_uses_transport() {
+        let key = Pubkey::new_unique();
+        let transport = InMemoryTransport {
+            accounts: HashMap::from([(
+                key,
+                Account {
+                    lamports: 1,
+                    data: vec![1, 2],
+                    owner: Pubkey::new_unique(),
+                    executable: false,
+                    rent_epoch: 0,
+                },
+            )]),
+            ..InMemoryTransport::default()
+        };
+        let client = crate::Client::new(crate::Cluster::Localnet, Arc::new(Keypair::new()));
+        let program = client
+            .program_with_transport(Pubkey::new_unique(), transport)
+            .unwrap();
+
+        let rpc = program.rpc();
+        assert_eq!(rpc.url(), "transport");
+        assert_eq!(rpc.get_account(&key).unwrap().data, vec![1, 2]);
+    }
+}
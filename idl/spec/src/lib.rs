@@ -92,12 +92,31 @@ pub struct IdlInstructionAccount {
     pub pda: Option<IdlPda>,
     #[serde(default, skip_serializing_if = "is_default")]
     pub relations: Vec<String>,
+    /// Number of accounts, if the account is a list of accounts.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub len: Option<IdlAccountsLen>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct IdlInstructionAccounts {
     pub name: String,
     pub accounts: Vec<IdlInstructionAccountItem>,
+    /// Number of times the accounts are repeated, if the accounts are a list.
+    #[serde(default, skip_serializing_if = "is_default")]
+    pub len: Option<IdlAccountsLen>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum IdlAccountsLen {
+    /// Path to the instruction argument holding the length, e.g. `params.count`.
+    Arg(String),
+    /// Path to the account field holding the length, e.g. `config.count`.
+    Account(String),
+    /// Length expression that can't be resolved by clients.
+    Expr(String),
+    #[serde(untagged)]
+    Value(usize),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
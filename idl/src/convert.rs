@@ -529,10 +529,12 @@ mod legacy {
                         .transpose()
                         .unwrap_or_default(),
                     relations: acc.relations,
+                    len: None,
                 }),
                 IdlAccountItem::IdlAccounts(accs) => Self::Composite(t::IdlInstructionAccounts {
                     name: accs.name.to_snake_case(),
                     accounts: accs.accounts.into_iter().map(Into::into).collect(),
+                    len: None,
                 }),
             }
         }
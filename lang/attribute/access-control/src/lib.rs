@@ -79,3 +79,124 @@ pub fn access_control(
         }
     })
 }
+
+/// Rejects the decorated instruction handler when it's re-entered, before
+/// running it.
+///
+/// The kinds of invocations to reject are given as arguments:
+///
+/// - `reentrancy` (the default): rejects the instruction when it's invoked
+///   through a CPI while its program is already executing. The accounts struct
+///   must hold the instructions sysvar in a field marked with the
+///   `instructions_sysvar` constraint.
+/// - `cpi`: rejects the instruction when it's invoked through any CPI.
+///
+/// Rejected instructions fail with `ErrorCode::ReentrancyGuardReentered` and
+/// `ErrorCode::ReentrancyGuardCpi` respectively.
+///
+/// # Example
+///
+/// ```ignore
+/// use anchor_lang::prelude::*;
+///
+/// #[program]
+/// mod vault {
+///     use super::*;
+///
+///     #[reentrancy_guard]
+///     pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+///         // ...
+///     }
+///
+///     #[reentrancy_guard(cpi)]
+///     pub fn liquidate(ctx: Context<Liquidate>) -> Result<()> {
+///         // ...
+///     }
+/// }
+///
+/// #[derive(Accounts)]
+/// pub struct Withdraw<'info> {
+///     // ...
+///     #[account(instructions_sysvar)]
+///     pub instructions: UncheckedAccount<'info>,
+/// }
+/// ```
+///
+/// The runtime only allows a program to be re-entered through direct
+/// self-recursion, but the instructions sysvar only holds the
+/// transaction-level instructions. The `reentrancy` check therefore only allows
+/// a CPI made directly by another program's transaction-level instruction, and
+/// rejects deeper invocations, whose caller can't be determined, with
+/// `ErrorCode::ReentrancyGuardUnknownCaller`.
+#[proc_macro_attribute]
+pub fn reentrancy_guard(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(
+        args with syn::punctuated::Punctuated::<syn::Ident, syn::Token![,]>::parse_terminated
+    );
+    let mut cpi = false;
+    let mut reentrancy = args.is_empty();
+    for arg in &args {
+        match arg.to_string().as_str() {
+            "cpi" => cpi = true,
+            "reentrancy" => reentrancy = true,
+            _ => {
+                return syn::Error::new_spanned(arg, "expected `cpi` or `reentrancy`")
+                    .to_compile_error()
+                    .into()
+            }
+        }
+    }
+
+    let item_fn = parse_macro_input!(input as syn::ItemFn);
+
+    let mut guards = Vec::new();
+    if cpi {
+        guards.push(quote! {
+            anchor_lang::reentrancy::check_not_cpi()?;
+        });
+    }
+    if reentrancy {
+        let ctx = match item_fn.sig.inputs.first() {
+            Some(syn::FnArg::Typed(arg)) => match &*arg.pat {
+                syn::Pat::Ident(pat) => pat.ident.clone(),
+                pat => {
+                    return syn::Error::new_spanned(pat, "expected an identifier for the context")
+                        .to_compile_error()
+                        .into()
+                }
+            },
+            _ => {
+                return syn::Error::new_spanned(
+                    &item_fn.sig,
+                    "expected the context as the first argument",
+                )
+                .to_compile_error()
+                .into()
+            }
+        };
+        guards.push(quote! {
+            anchor_lang::reentrancy::check_not_reentrant(
+                #ctx.program_id,
+                &anchor_lang::__private::InstructionsSysvar::instructions_sysvar(&*#ctx.accounts),
+            )?;
+        });
+    }
+
+    let fn_attrs = item_fn.attrs;
+    let fn_vis = item_fn.vis;
+    let fn_sig = item_fn.sig;
+    let fn_stmts = item_fn.block.stmts;
+
+    proc_macro::TokenStream::from(quote! {
+        #(#fn_attrs)*
+        #fn_vis #fn_sig {
+
+            #(#guards)*
+
+            #(#fn_stmts)*
+        }
+    })
+}
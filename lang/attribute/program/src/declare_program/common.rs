@@ -426,6 +426,7 @@ pub fn get_all_instruction_accounts(idl: &Idl) -> Vec<IdlInstructionAccounts> {
                 all.push(IdlInstructionAccounts {
                     name,
                     accounts: accs.accounts.to_owned(),
+                    len: None,
                 })
             }
 
@@ -435,6 +436,7 @@ pub fn get_all_instruction_accounts(idl: &Idl) -> Vec<IdlInstructionAccounts> {
         .chain(idl.instructions.iter().map(|ix| IdlInstructionAccounts {
             name: ix.name.to_owned(),
             accounts: ix.accounts.to_owned(),
+            len: None,
         }))
         .collect()
 }
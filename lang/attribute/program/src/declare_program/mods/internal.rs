@@ -1,4 +1,4 @@
-use anchor_lang_idl::types::{Idl, IdlInstructionAccountItem};
+use anchor_lang_idl::types::{Idl, IdlAccountsLen, IdlInstructionAccountItem};
 use anchor_syn::{
     codegen::accounts::{__client_accounts, __cpi_client_accounts},
     parser::accounts,
@@ -122,26 +122,24 @@ fn gen_internal_accounts_common(
                 IdlInstructionAccountItem::Single(acc) => {
                     let name = format_ident!("{}", acc.name);
 
-                    let attrs = {
-                        let signer = acc.signer.then_some(quote!(signer));
-                        let mt = acc.writable.then_some(quote!(mut));
-
-                        match (signer, mt) {
-                            (None, None) => None,
-                            (Some(s), None) => Some(quote!(#s)),
-                            (None, Some(m)) => Some(quote!(#m)),
-                            (Some(s), Some(m)) => Some(quote!(#s, #m)),
-                        }
-                    };
+                    let attrs = [
+                        acc.signer.then_some(quote!(signer)),
+                        acc.writable.then_some(quote!(mut)),
+                        acc.len.as_ref().map(gen_len_attr),
+                    ]
+                    .into_iter()
+                    .flatten();
 
                     let acc_expr = if acc.optional {
                         quote! { Option<AccountInfo #generics> }
+                    } else if acc.len.is_some() {
+                        quote! { Vec<AccountInfo #generics> }
                     } else {
                         quote! { AccountInfo #generics }
                     };
 
                     quote! {
-                        #[account(#attrs)]
+                        #[account(#(#attrs),*)]
                         pub #name: #acc_expr
                     }
                 }
@@ -153,8 +151,16 @@ fn gen_internal_accounts_common(
                         .map(|a| format_ident!("{}", a.name.to_camel_case()))
                         .expect("Accounts must exist");
 
-                    quote! {
-                        pub #name: #ty_name #generics
+                    if let Some(len) = &accs.len {
+                        let len = gen_len_attr(len);
+                        quote! {
+                            #[account(#len)]
+                            pub #name: Vec<#ty_name #generics>
+                        }
+                    } else {
+                        quote! {
+                            pub #name: #ty_name #generics
+                        }
                     }
                 }
             });
@@ -175,3 +181,15 @@ fn gen_internal_accounts_common(
 
     quote! { #(#accounts)* }
 }
+
+/// Generates the `len` constraint of a list of accounts from its IDL length.
+fn gen_len_attr(len: &IdlAccountsLen) -> proc_macro2::TokenStream {
+    let len = match len {
+        IdlAccountsLen::Value(value) => quote!(#value),
+        IdlAccountsLen::Arg(expr) | IdlAccountsLen::Account(expr) | IdlAccountsLen::Expr(expr) => {
+            let expr = syn::parse_str::<syn::Expr>(expr).expect("Invalid accounts length");
+            quote!(#expr)
+        }
+    };
+    quote!(len = #len)
+}
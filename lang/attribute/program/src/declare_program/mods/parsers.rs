@@ -1,4 +1,6 @@
-use anchor_lang_idl::types::{Idl, IdlInstructionAccountItem, IdlInstructionAccounts};
+use anchor_lang_idl::types::{
+    Idl, IdlAccountsLen, IdlInstructionAccountItem, IdlInstructionAccounts,
+};
 use heck::CamelCase;
 use quote::{format_ident, quote};
 
@@ -84,6 +86,7 @@ fn gen_event(idl: &Idl) -> proc_macro2::TokenStream {
             }
         }
     });
+    let program_id = get_canonical_program_id();
 
     quote! {
         /// An enum that includes all events of the declared program as a tuple variant.
@@ -101,6 +104,32 @@ fn gen_event(idl: &Idl) -> proc_macro2::TokenStream {
             pub fn parse(data: &[u8]) -> Result<Self> {
                 Self::try_from(data)
             }
+
+            /// Parse an event based on the given instruction data of a self-CPI made by
+            /// `emit_cpi!`.
+            ///
+            /// This method returns an error if the data doesn't start with the event instruction
+            /// tag, or if [`Self::parse`] fails.
+            pub fn parse_cpi(data: &[u8]) -> Result<Self> {
+                data.strip_prefix(anchor_lang::event::EVENT_IX_TAG_LE)
+                    .ok_or_else(|| ProgramError::InvalidInstructionData.into())
+                    .and_then(Self::try_from)
+            }
+
+            /// Parse an event based on the given
+            /// [`anchor_lang::solana_program::instruction::Instruction`] of a self-CPI made by
+            /// `emit_cpi!`.
+            ///
+            /// This method checks the program ID in addition to the checks of [`Self::parse_cpi`].
+            pub fn parse_cpi_instruction(
+                ix: &anchor_lang::solana_program::instruction::Instruction,
+            ) -> Result<Self> {
+                if ix.program_id != #program_id {
+                    return Err(ProgramError::IncorrectProgramId.into())
+                }
+
+                Self::parse_cpi(&ix.data)
+            }
         }
 
         impl TryFrom<&[u8]> for Event {
@@ -155,8 +184,8 @@ fn gen_instruction(idl: &Idl) -> proc_macro2::TokenStream {
                             }
                         }
                     } else {
-                        quote! {
-                            #name: {
+                        let item = quote! {
+                            {
                                 let acc = accs.next().ok_or_else(|| ProgramError::NotEnoughAccountKeys)?;
                                 if acc.is_signer != #signer {
                                     return Err(ProgramError::InvalidAccountData.into());
@@ -167,6 +196,13 @@ fn gen_instruction(idl: &Idl) -> proc_macro2::TokenStream {
 
                                 acc.pubkey
                             }
+                        };
+                        match &acc.len {
+                            Some(len) => {
+                                let list = gen_list(len, &item);
+                                quote! { #name: #list }
+                            }
+                            None => quote! { #name: #item },
                         }
                     }
                 }
@@ -177,13 +213,52 @@ fn gen_instruction(idl: &Idl) -> proc_macro2::TokenStream {
                         .find(|a| a.accounts == accs.accounts)
                         .map(|a| gen_accounts(&a.name, &a.accounts, all_ix_accs))
                         .expect("Accounts must exist");
-                    quote! { #name: #accounts }
+                    match &accs.len {
+                        Some(len) => {
+                            let list = gen_list(len, &accounts);
+                            quote! { #name: #list }
+                        }
+                        None => quote! { #name: #accounts },
+                    }
                 }
             });
 
             quote! { client::accounts::#name { #(#fields,)* } }
         }
 
+        /// Generates a list of accounts whose elements are parsed with `item`.
+        ///
+        /// Only lengths that are constant or instruction arguments can be parsed, since the
+        /// other lengths are read from the accounts' data.
+        fn gen_list(
+            len: &IdlAccountsLen,
+            item: &proc_macro2::TokenStream,
+        ) -> proc_macro2::TokenStream {
+            let len = match len {
+                IdlAccountsLen::Value(len) => quote! { #len },
+                IdlAccountsLen::Arg(path) => {
+                    let path = path.split('.').map(|field| format_ident!("{}", field));
+                    quote! {
+                        usize::try_from(args.#(#path).*)
+                            .map_err(|_| ProgramError::InvalidInstructionData)?
+                    }
+                }
+                IdlAccountsLen::Account(_) | IdlAccountsLen::Expr(_) => quote! {
+                    Err::<usize, _>(ProgramError::InvalidArgument)?
+                },
+            };
+            quote! {
+                {
+                    let len = #len;
+                    let mut list = Vec::new();
+                    for _ in 0..len {
+                        list.push(#item);
+                    }
+                    list
+                }
+            }
+        }
+
         let all_ix_accs = get_all_instruction_accounts(idl);
         idl.instructions
             .iter()
@@ -192,12 +267,15 @@ fn gen_instruction(idl: &Idl) -> proc_macro2::TokenStream {
                 let accounts = gen_accounts(&ix.name, &ix.accounts, &all_ix_accs);
                 quote! {
                     if ix.data.starts_with(client::args::#name::DISCRIMINATOR) {
+                        // The arguments are parsed first since they may hold the length of the
+                        // lists of accounts
+                        let args = client::args::#name::try_from_slice(
+                            &ix.data[client::args::#name::DISCRIMINATOR.len()..]
+                        )?;
                         let mut accs = ix.accounts.to_owned().into_iter();
                         return Ok(Self::#name {
                             accounts: #accounts,
-                            args: client::args::#name::try_from_slice(
-                                &ix.data[client::args::#name::DISCRIMINATOR.len()..]
-                            )?
+                            args,
                         })
                     }
                 }
@@ -228,6 +306,8 @@ fn gen_instruction(idl: &Idl) -> proc_macro2::TokenStream {
             /// - All accounts have the correct signer and writable attributes
             /// - The instruction data can be deserialized
             ///
+            /// Lists of accounts whose length is read from an account can't be parsed.
+            ///
             /// It does **not** check whether:
             ///
             /// - There are more accounts than expected
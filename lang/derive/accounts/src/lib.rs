@@ -360,6 +360,55 @@ use syn::parse_macro_input;
 ///         </tr>
 ///         <tr>
 ///             <td>
+///                 <code>#[account(instructions_sysvar)]</code>
+///             </td>
+///             <td>
+///                 Checks the account is the instructions sysvar, and marks it as the
+///                 account used by the <code>#[reentrancy_guard]</code> attribute.<br>
+///                 Can't be combined with <code>address</code>.<br><br>
+///                 Example:
+///                 <pre><code>
+/// #[account(instructions_sysvar)]
+/// pub instructions: UncheckedAccount<'info>
+///                 </code></pre>
+///             </td>
+///         </tr>
+///         <tr>
+///             <td>
+///                 <code>#[account(skip_unchanged)]</code>
+///             </td>
+///             <td>
+///                 Only serializes the account on exit if the instruction modified it,
+///                 i.e. mutably dereferenced it or called <code>set_inner</code>.<br>
+///                 Must be used with <code>mut</code>. Enable the <code>skip-unchanged</code>
+///                 feature to apply this to all mutable accounts.<br><br>
+///                 Example:
+///                 <pre><code>
+/// #[account(mut, skip_unchanged)]
+/// pub data_account: Account<'info, MyData>
+///                 </code></pre>
+///             </td>
+///         </tr>
+///         <tr>
+///             <td>
+///                 <code>#[account(len = &lt;expr&gt;)]</code>
+///             </td>
+///             <td>
+///                 Deserializes a <code>Vec</code> of <code>expr</code> accounts, or nested
+///                 <code>Accounts</code> structs.<br>
+///                 The other constraints of the field are checked for each element, with the
+///                 field name referring to the element.<br><br>
+///                 Example:
+///                 <pre><code>
+/// #[instruction(count: u8)]
+/// ...
+/// #[account(mut, len = count, has_one = authority)]
+/// pub vaults: Vec<Account<'info, Vault>>
+///                 </code></pre>
+///             </td>
+///         </tr>
+///         <tr>
+///             <td>
 ///                 <code>#[account(rent_exempt = skip)]</code><br><br>
 ///                 <code>#[account(rent_exempt = enforce)]</code>
 ///             </td>
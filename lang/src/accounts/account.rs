@@ -227,6 +227,7 @@ use std::ops::{Deref, DerefMut};
 pub struct Account<'info, T: AccountSerialize + AccountDeserialize + Clone> {
     account: T,
     info: &'info AccountInfo<'info>,
+    dirty: bool,
 }
 
 impl<T: AccountSerialize + AccountDeserialize + Clone + fmt::Debug> fmt::Debug for Account<'_, T> {
@@ -246,7 +247,11 @@ impl<T: AccountSerialize + AccountDeserialize + Clone + fmt::Debug> Account<'_,
 
 impl<'a, T: AccountSerialize + AccountDeserialize + Clone> Account<'a, T> {
     pub(crate) fn new(info: &'a AccountInfo<'a>, account: T) -> Account<'a, T> {
-        Self { info, account }
+        Self {
+            info,
+            account,
+            dirty: false,
+        }
     }
 
     pub(crate) fn exit_with_expected_owner(
@@ -264,6 +269,30 @@ impl<'a, T: AccountSerialize + AccountDeserialize + Clone> Account<'a, T> {
         Ok(())
     }
 
+    pub(crate) fn exit_if_modified_with_expected_owner(
+        &self,
+        expected_owner: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Result<()> {
+        if self.dirty {
+            self.exit_with_expected_owner(expected_owner, program_id)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the inner account with data read from storage, e.g. on reload.
+    pub(crate) fn set_inner_unmodified(&mut self, inner: T) {
+        self.account = inner;
+        self.dirty = false;
+    }
+
+    /// Returns whether the inner account may have been modified, i.e. whether it
+    /// was mutably borrowed or replaced with [`set_inner`](Self::set_inner) since
+    /// it was loaded.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
     pub fn into_inner(self) -> T {
         self.account
     }
@@ -286,6 +315,7 @@ impl<'a, T: AccountSerialize + AccountDeserialize + Clone> Account<'a, T> {
     /// ```
     pub fn set_inner(&mut self, inner: T) {
         self.account = inner;
+        self.dirty = true;
     }
 }
 
@@ -302,7 +332,7 @@ impl<'a, T: AccountSerialize + AccountDeserialize + Owner + Clone> Account<'a, T
         }
 
         let mut data: &[u8] = &self.info.try_borrow_data()?;
-        self.account = T::try_deserialize(&mut data)?;
+        self.set_inner_unmodified(T::try_deserialize(&mut data)?);
         Ok(())
     }
 
@@ -365,6 +395,10 @@ impl<'info, T: AccountSerialize + AccountDeserialize + Owner + Clone> AccountsEx
     fn exit(&self, program_id: &Pubkey) -> Result<()> {
         self.exit_with_expected_owner(&T::owner(), program_id)
     }
+
+    fn exit_if_modified(&self, program_id: &Pubkey) -> Result<()> {
+        self.exit_if_modified_with_expected_owner(&T::owner(), program_id)
+    }
 }
 
 impl<'info, T: AccountSerialize + AccountDeserialize + Clone> AccountsClose<'info>
@@ -423,6 +457,7 @@ impl<T: AccountSerialize + AccountDeserialize + Clone> DerefMut for Account<'_,
             crate::solana_program::msg!("The given Account is not mutable");
             panic!();
         }
+        self.dirty = true;
         &mut self.account
     }
 }
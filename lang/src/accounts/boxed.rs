@@ -36,6 +36,10 @@ impl<'info, T: AccountsExit<'info>> AccountsExit<'info> for Box<T> {
     fn exit(&self, program_id: &Pubkey) -> Result<()> {
         T::exit(Deref::deref(self), program_id)
     }
+
+    fn exit_if_modified(&self, program_id: &Pubkey) -> Result<()> {
+        T::exit_if_modified(Deref::deref(self), program_id)
+    }
 }
 
 impl<'info, T: ToAccountInfos<'info>> ToAccountInfos<'info> for Box<T> {
@@ -205,6 +205,13 @@ impl<'a, T: AccountSerialize + AccountDeserialize + Clone> InterfaceAccount<'a,
     pub fn set_inner(&mut self, inner: T) {
         self.account.set_inner(inner);
     }
+
+    /// Returns whether the inner account may have been modified since it was loaded.
+    ///
+    /// See [`Account::is_dirty`].
+    pub fn is_dirty(&self) -> bool {
+        self.account.is_dirty()
+    }
 }
 
 impl<'a, T: AccountSerialize + AccountDeserialize + CheckOwner + Clone> InterfaceAccount<'a, T> {
@@ -240,7 +247,7 @@ impl<'a, T: AccountSerialize + AccountDeserialize + CheckOwner + Clone> Interfac
         T::check_owner(info.owner)?;
 
         // Re-deserialize fresh data into the inner account.
-        self.account.set_inner({
+        self.account.set_inner_unmodified({
             let mut data: &[u8] = &info.try_borrow_data()?;
             T::try_deserialize(&mut data)?
         });
@@ -275,6 +282,11 @@ impl<'info, T: AccountSerialize + AccountDeserialize + Owners + Clone> AccountsE
         self.account
             .exit_with_expected_owner(&self.owner, program_id)
     }
+
+    fn exit_if_modified(&self, program_id: &Pubkey) -> Result<()> {
+        self.account
+            .exit_if_modified_with_expected_owner(&self.owner, program_id)
+    }
 }
 
 impl<'info, T: AccountSerialize + AccountDeserialize + Clone> AccountsClose<'info>
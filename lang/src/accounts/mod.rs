@@ -13,6 +13,7 @@ pub mod signer;
 pub mod system_account;
 pub mod sysvar;
 pub mod unchecked_account;
+pub mod versioned;
 
 #[cfg(feature = "lazy-account")]
 pub mod lazy_account;
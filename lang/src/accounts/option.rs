@@ -81,4 +81,9 @@ impl<'info, T: AccountsExit<'info>> AccountsExit<'info> for Option<T> {
     fn exit(&self, program_id: &Pubkey) -> Result<()> {
         self.as_ref().map_or(Ok(()), |t| t.exit(program_id))
     }
+
+    fn exit_if_modified(&self, program_id: &Pubkey) -> Result<()> {
+        self.as_ref()
+            .map_or(Ok(()), |t| t.exit_if_modified(program_id))
+    }
 }
@@ -0,0 +1,490 @@
+//! Account container for accounts stored in any version of a chain of account types.
+
+use crate::error::{Error, ErrorCode};
+use crate::solana_program::account_info::AccountInfo;
+use crate::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use crate::solana_program::instruction::AccountMeta;
+use crate::solana_program::pubkey::Pubkey;
+use crate::solana_program::system_program;
+use crate::{
+    AccountDeserialize, AccountSerialize, Accounts, AccountsClose, AccountsExit, Discriminator,
+    Key, Owner, Result, ToAccountInfos, ToAccountMetas,
+};
+use solana_sysvar::{rent::Rent, Sysvar};
+use std::collections::BTreeSet;
+use std::ops::{Deref, DerefMut};
+
+/// Chain of account versions, from the oldest to the latest, used by [`Versioned`].
+///
+/// Implemented for tuples of 2 to 5 account types where each version implements `From` the
+/// previous one, e.g. `(V1, V2, V3)` with `impl From<V1> for V2` and `impl From<V2> for V3`.
+pub trait VersionChain {
+    /// Latest version of the account.
+    type Latest: AccountSerialize + AccountDeserialize + Owner;
+
+    /// Number of versions of the chain.
+    const VERSIONS: usize;
+
+    /// Deserializes the account data with the version detected from its discriminator, and
+    /// upgrades it to the latest version.
+    ///
+    /// Returns the upgraded account and the index of the stored version in the chain.
+    fn try_upgrade(data: &[u8]) -> Result<(Self::Latest, usize)>;
+}
+
+/// Upgrades `$account` of type `$version` through the following versions of the chain.
+macro_rules! upgrade {
+    ($account:expr; $version:ident) => {
+        $account
+    };
+    ($account:expr; $version:ident, $next:ident $(, $rest:ident)*) => {
+        upgrade!(<$next as From<$version>>::from($account); $next $(, $rest)*)
+    };
+}
+
+/// Deserializes `$data` with the first version whose discriminator matches, and upgrades it.
+macro_rules! try_versions {
+    ($data:ident, $index:expr;) => {};
+    ($data:ident, $index:expr; $version:ident $(, $rest:ident)*) => {
+        if $data.starts_with($version::DISCRIMINATOR) {
+            let account = $version::try_deserialize(&mut &$data[..])?;
+            return Ok((upgrade!(account; $version $(, $rest)*), $index));
+        }
+        try_versions!($data, $index + 1; $($rest),*);
+    };
+}
+
+macro_rules! impl_version_chain {
+    ($len:literal; $latest:ident; $($version:ident),+; $($next:ident: $prev:ident),+) => {
+        impl<$($version),+> VersionChain for ($($version,)+)
+        where
+            $($version: AccountDeserialize + Discriminator,)+
+            $latest: AccountSerialize + Owner,
+            $($next: From<$prev>,)+
+        {
+            type Latest = $latest;
+
+            const VERSIONS: usize = $len;
+
+            fn try_upgrade(data: &[u8]) -> Result<(Self::Latest, usize)> {
+                try_versions!(data, 0; $($version),+);
+                if [$($version::DISCRIMINATOR.len()),+].iter().all(|len| data.len() < *len) {
+                    return Err(ErrorCode::AccountDiscriminatorNotFound.into());
+                }
+                Err(ErrorCode::AccountDiscriminatorMismatch.into())
+            }
+        }
+    };
+}
+
+impl_version_chain!(2; V2; V1, V2; V2: V1);
+impl_version_chain!(3; V3; V1, V2, V3; V2: V1, V3: V2);
+impl_version_chain!(4; V4; V1, V2, V3, V4; V2: V1, V3: V2, V4: V3);
+impl_version_chain!(5; V5; V1, V2, V3, V4, V5; V2: V1, V3: V2, V4: V3, V5: V4);
+
+/// Wrapper around [`AccountInfo`](crate::solana_program::account_info::AccountInfo)
+/// for accounts that can be stored in any version of a chain of account types.
+///
+/// Unlike [`Migration`](crate::accounts::migration::Migration), which migrates accounts from
+/// one type to the next, `Versioned` accepts accounts stored in any version of the chain
+/// `V`, e.g. `(V1, V2, V3)`. The stored version is detected from the account discriminator,
+/// and the account is upgraded to the latest version with the `From` implementations between
+/// consecutive versions. The latest version is accessible via `Deref`, and it is always written
+/// back to the account on exit, so the field must be marked `mut`. Bytes after the serialized
+/// latest version are zeroed.
+///
+/// Use the `realloc` constraint to make room for the latest version and pay for its rent. If the
+/// latest version still doesn't fit in the account on exit, the account is resized, which fails
+/// unless the account already holds enough lamports to be rent exempt with its new size.
+///
+/// Checks:
+///
+/// - `Account.info.owner == Latest::owner()`
+/// - `!(Account.info.owner == SystemProgram && Account.info.lamports() == 0)`
+/// - The account discriminator is the discriminator of one of the versions
+///
+/// # Example
+/// ```ignore
+/// use anchor_lang::prelude::*;
+///
+/// #[program]
+/// pub mod my_program {
+///     use super::*;
+///
+///     pub fn update(ctx: Context<Update>, value: u64) -> Result<()> {
+///         msg!("Stored version: {}", ctx.accounts.my_account.stored_version());
+///         ctx.accounts.my_account.value = value;
+///         Ok(())
+///     }
+/// }
+///
+/// #[account]
+/// pub struct AccountV1 {
+///     pub value: u64,
+/// }
+///
+/// #[account]
+/// #[derive(InitSpace)]
+/// pub struct AccountV2 {
+///     pub value: u64,
+///     pub authority: Pubkey,
+/// }
+///
+/// impl From<AccountV1> for AccountV2 {
+///     fn from(v1: AccountV1) -> Self {
+///         Self { value: v1.value, authority: Pubkey::default() }
+///     }
+/// }
+///
+/// #[derive(Accounts)]
+/// pub struct Update<'info> {
+///     #[account(mut)]
+///     pub payer: Signer<'info>,
+///     #[account(
+///         mut,
+///         realloc = 8 + AccountV2::INIT_SPACE,
+///         realloc::payer = payer,
+///         realloc::zero = false
+///     )]
+///     pub my_account: Versioned<'info, (AccountV1, AccountV2)>,
+///     pub system_program: Program<'info, System>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Versioned<'info, V: VersionChain> {
+    info: &'info AccountInfo<'info>,
+    account: V::Latest,
+    stored_version: usize,
+}
+
+impl<'info, V: VersionChain> Versioned<'info, V> {
+    /// Returns the index of the version the account was stored in, starting at 0 for the oldest
+    /// version of the chain.
+    pub fn stored_version(&self) -> usize {
+        self.stored_version
+    }
+
+    /// Returns `true` if the account was stored in an older version than the latest one.
+    pub fn is_upgraded(&self) -> bool {
+        self.stored_version + 1 < V::VERSIONS
+    }
+
+    pub fn into_inner(self) -> V::Latest {
+        self.account
+    }
+
+    /// Deserializes the given `info` into a `Versioned`, upgrading the account to the latest
+    /// version.
+    #[inline(never)]
+    pub fn try_from(info: &'info AccountInfo<'info>) -> Result<Self> {
+        if info.owner == &system_program::ID && info.lamports() == 0 {
+            return Err(ErrorCode::AccountNotInitialized.into());
+        }
+
+        let owner = V::Latest::owner();
+        if info.owner != &owner {
+            return Err(Error::from(ErrorCode::AccountOwnedByWrongProgram)
+                .with_pubkeys((*info.owner, owner)));
+        }
+
+        let data: &[u8] = &info.try_borrow_data()?;
+        let (account, stored_version) = V::try_upgrade(data)?;
+        Ok(Self {
+            info,
+            account,
+            stored_version,
+        })
+    }
+}
+
+impl<'info, B, V: VersionChain> Accounts<'info, B> for Versioned<'info, V> {
+    #[inline(never)]
+    fn try_accounts(
+        _program_id: &Pubkey,
+        accounts: &mut &'info [AccountInfo<'info>],
+        _ix_data: &[u8],
+        _bumps: &mut B,
+        _reallocs: &mut BTreeSet<Pubkey>,
+    ) -> Result<Self> {
+        if accounts.is_empty() {
+            return Err(ErrorCode::AccountNotEnoughKeys.into());
+        }
+        let account = &accounts[0];
+        *accounts = &accounts[1..];
+        Self::try_from(account)
+    }
+}
+
+impl<'info, V: VersionChain> AccountsExit<'info> for Versioned<'info, V> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        // Only persist if the owner is the current program and the account is not closed.
+        if &V::Latest::owner() != program_id || crate::common::is_closed(self.info) {
+            return Ok(());
+        }
+
+        let mut serialized = Vec::new();
+        self.account.try_serialize(&mut serialized)?;
+        if serialized.len() > self.info.data_len() {
+            if serialized.len() - self.info.data_len() > MAX_PERMITTED_DATA_INCREASE {
+                return Err(ErrorCode::AccountReallocExceedsLimit.into());
+            }
+            let rent_minimum = Rent::get()?.minimum_balance(serialized.len());
+            if self.info.lamports() < rent_minimum {
+                return Err(Error::from(ErrorCode::ConstraintRentExempt)
+                    .with_values((self.info.lamports(), rent_minimum)));
+            }
+            self.info.resize(serialized.len())?;
+        }
+
+        let mut data = self.info.try_borrow_mut_data()?;
+        let (latest, rest) = data.split_at_mut(serialized.len());
+        latest.copy_from_slice(&serialized);
+        rest.fill(0);
+        Ok(())
+    }
+}
+
+impl<'info, V: VersionChain> AccountsClose<'info> for Versioned<'info, V> {
+    fn close(&self, sol_destination: AccountInfo<'info>) -> Result<()> {
+        crate::common::close(self.info.clone(), sol_destination)
+    }
+}
+
+impl<V: VersionChain> ToAccountMetas for Versioned<'_, V> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        let is_signer = is_signer.unwrap_or(self.info.is_signer);
+        let meta = match self.info.is_writable {
+            false => AccountMeta::new_readonly(*self.info.key, is_signer),
+            true => AccountMeta::new(*self.info.key, is_signer),
+        };
+        vec![meta]
+    }
+}
+
+impl<'info, V: VersionChain> ToAccountInfos<'info> for Versioned<'info, V> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![self.info.clone()]
+    }
+}
+
+impl<'info, V: VersionChain> AsRef<AccountInfo<'info>> for Versioned<'info, V> {
+    fn as_ref(&self) -> &AccountInfo<'info> {
+        self.info
+    }
+}
+
+impl<V: VersionChain> Key for Versioned<'_, V> {
+    fn key(&self) -> Pubkey {
+        *self.info.key
+    }
+}
+
+impl<V: VersionChain> Deref for Versioned<'_, V> {
+    type Target = V::Latest;
+
+    fn deref(&self) -> &Self::Target {
+        &self.account
+    }
+}
+
+impl<V: VersionChain> DerefMut for Versioned<'_, V> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.account
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnchorDeserialize, AnchorSerialize};
+
+    const TEST_OWNER: Pubkey = Pubkey::new_from_array([1u8; 32]);
+
+    macro_rules! test_account {
+        ($name:ident, $discriminator:expr) => {
+            impl Discriminator for $name {
+                const DISCRIMINATOR: &'static [u8] = &$discriminator;
+            }
+
+            impl Owner for $name {
+                fn owner() -> Pubkey {
+                    TEST_OWNER
+                }
+            }
+
+            impl AccountSerialize for $name {
+                fn try_serialize<W: std::io::Write>(&self, writer: &mut W) -> Result<()> {
+                    writer.write_all(Self::DISCRIMINATOR)?;
+                    AnchorSerialize::serialize(self, writer)?;
+                    Ok(())
+                }
+            }
+
+            impl AccountDeserialize for $name {
+                fn try_deserialize(buf: &mut &[u8]) -> Result<Self> {
+                    if !buf.starts_with(Self::DISCRIMINATOR) {
+                        return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+                    }
+                    Self::try_deserialize_unchecked(buf)
+                }
+
+                fn try_deserialize_unchecked(buf: &mut &[u8]) -> Result<Self> {
+                    let mut data = &buf[Self::DISCRIMINATOR.len()..];
+                    AnchorDeserialize::deserialize(&mut data)
+                        .map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+                }
+            }
+        };
+    }
+
+    #[derive(Debug, AnchorSerialize, AnchorDeserialize)]
+    struct AccountV1 {
+        value: u32,
+    }
+    test_account!(AccountV1, [1; 8]);
+
+    #[derive(Debug, AnchorSerialize, AnchorDeserialize)]
+    struct AccountV2 {
+        value: u64,
+    }
+    test_account!(AccountV2, [2; 8]);
+
+    impl From<AccountV1> for AccountV2 {
+        fn from(v1: AccountV1) -> Self {
+            Self {
+                value: v1.value.into(),
+            }
+        }
+    }
+
+    #[derive(Debug, AnchorSerialize, AnchorDeserialize)]
+    struct AccountV3 {
+        value: u64,
+        flag: bool,
+    }
+    test_account!(AccountV3, [3; 8]);
+
+    impl From<AccountV2> for AccountV3 {
+        fn from(v2: AccountV2) -> Self {
+            Self {
+                value: v2.value,
+                flag: true,
+            }
+        }
+    }
+
+    type Chain = (AccountV1, AccountV2, AccountV3);
+
+    fn serialize(account: &impl AccountSerialize) -> Vec<u8> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_upgrade_from_any_version() {
+        let (v3, version) = Chain::try_upgrade(&serialize(&AccountV1 { value: 7 })).unwrap();
+        assert_eq!((v3.value, v3.flag, version), (7, true, 0));
+
+        let (v3, version) = Chain::try_upgrade(&serialize(&AccountV2 { value: 8 })).unwrap();
+        assert_eq!((v3.value, v3.flag, version), (8, true, 1));
+
+        let data = serialize(&AccountV3 {
+            value: 9,
+            flag: false,
+        });
+        let (v3, version) = Chain::try_upgrade(&data).unwrap();
+        assert_eq!((v3.value, v3.flag, version), (9, false, 2));
+
+        assert_eq!(
+            Chain::try_upgrade(&[4; 16]).unwrap_err(),
+            ErrorCode::AccountDiscriminatorMismatch.into()
+        );
+        assert_eq!(
+            Chain::try_upgrade(&[1; 4]).unwrap_err(),
+            ErrorCode::AccountDiscriminatorNotFound.into()
+        );
+    }
+
+    #[derive(Debug, AnchorSerialize, AnchorDeserialize)]
+    struct ShortV1 {
+        value: u8,
+    }
+    test_account!(ShortV1, [1]);
+
+    #[derive(Debug, AnchorSerialize, AnchorDeserialize)]
+    struct ShortV2 {
+        value: u16,
+    }
+    test_account!(ShortV2, [2, 2]);
+
+    impl From<ShortV1> for ShortV2 {
+        fn from(v1: ShortV1) -> Self {
+            Self {
+                value: v1.value.into(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_upgrade_with_custom_discriminators() {
+        type ShortChain = (ShortV1, ShortV2);
+
+        let (v2, version) = ShortChain::try_upgrade(&[1, 7]).unwrap();
+        assert_eq!((v2.value, version), (7, 0));
+        let (v2, version) = ShortChain::try_upgrade(&[2, 2, 8, 0]).unwrap();
+        assert_eq!((v2.value, version), (8, 1));
+
+        assert_eq!(
+            ShortChain::try_upgrade(&[3, 0]).unwrap_err(),
+            ErrorCode::AccountDiscriminatorMismatch.into()
+        );
+        assert_eq!(
+            ShortChain::try_upgrade(&[]).unwrap_err(),
+            ErrorCode::AccountDiscriminatorNotFound.into()
+        );
+    }
+
+    #[test]
+    fn test_exit_writes_latest_version() {
+        let key = Pubkey::default();
+        let mut lamports = 100;
+        let mut data = serialize(&AccountV1 { value: 7 });
+        // Stale bytes after the stored version
+        data.resize(32, 0xff);
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &TEST_OWNER,
+            false,
+        );
+
+        let mut account: Versioned<Chain> = Versioned::try_from(&info).unwrap();
+        assert_eq!(account.stored_version(), 0);
+        assert!(account.is_upgraded());
+        account.value = 10;
+        account.exit(&TEST_OWNER).unwrap();
+        assert!(info.try_borrow_data().unwrap()[8 + 9..]
+            .iter()
+            .all(|byte| *byte == 0));
+
+        let account: Versioned<Chain> = Versioned::try_from(&info).unwrap();
+        assert_eq!(account.stored_version(), 2);
+        assert!(!account.is_upgraded());
+        assert_eq!((account.value, account.flag), (10, true));
+    }
+
+    #[test]
+    fn test_try_from_fails_with_wrong_owner() {
+        let key = Pubkey::default();
+        let owner = Pubkey::new_from_array([99u8; 32]);
+        let mut lamports = 100;
+        let mut data = serialize(&AccountV1 { value: 7 });
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &owner, false);
+
+        assert!(Versioned::<Chain>::try_from(&info).is_err());
+    }
+}
@@ -1,10 +1,16 @@
 //! Data structures that are used to provide non-argument inputs to program endpoints
 
+use crate::__private::DuplicateMutableKeys;
+use crate::error::{Error, ErrorCode};
 use crate::solana_program::account_info::AccountInfo;
 use crate::solana_program::instruction::AccountMeta;
 use crate::solana_program::pubkey::Pubkey;
-use crate::{Accounts, Bumps, ToAccountInfos, ToAccountMetas};
+use crate::{Accounts, AccountsExit, Bumps, Result, ToAccountInfos, ToAccountMetas};
+use std::cell::RefCell;
+use std::collections::BTreeSet;
 use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
 
 /// Provides non-argument inputs to the program.
 ///
@@ -34,6 +40,14 @@ pub struct Context<'a, 'b, 'c, 'info, T: Bumps> {
     /// pass them in as arguments.
     /// Type is the bumps struct generated by #[derive(Accounts)]
     pub bumps: T::Bumps,
+    /// Instruction data, used to deserialize the `#[instruction(..)]` arguments of remaining
+    /// accounts.
+    ix_data: &'a [u8],
+    /// Accounts reallocated during the instruction, used to reject duplicate reallocs.
+    reallocs: BTreeSet<Pubkey>,
+    /// Accounts structs deserialized from the remaining accounts, persisted after the
+    /// instruction returns.
+    remaining_exits: RemainingAccountsExits<'info>,
 }
 
 impl<T> fmt::Debug for Context<'_, '_, '_, '_, T>
@@ -65,6 +79,141 @@ where
             accounts,
             remaining_accounts,
             bumps,
+            ix_data: &[],
+            reallocs: BTreeSet::new(),
+            remaining_exits: RemainingAccountsExits::default(),
+        }
+    }
+
+    /// Sets the instruction data, so that [`try_remaining_accounts`](Self::try_remaining_accounts)
+    /// can deserialize the `#[instruction(..)]` arguments of the remaining accounts.
+    pub fn with_ix_data(mut self, ix_data: &'a [u8]) -> Self {
+        self.ix_data = ix_data;
+        self
+    }
+
+    /// Sets the accounts already reallocated while deserializing the accounts struct, so that
+    /// [`try_remaining_accounts`](Self::try_remaining_accounts) can reject duplicate reallocs.
+    pub fn with_reallocs(mut self, reallocs: BTreeSet<Pubkey>) -> Self {
+        self.reallocs = reallocs;
+        self
+    }
+
+    /// Sets where the accounts deserialized with
+    /// [`try_remaining_accounts`](Self::try_remaining_accounts) are registered, to be persisted
+    /// with [`RemainingAccountsExits::exit`] once the instruction returns.
+    pub fn with_remaining_exits(mut self, remaining_exits: RemainingAccountsExits<'info>) -> Self {
+        self.remaining_exits = remaining_exits;
+        self
+    }
+
+    /// Deserializes and validates the accounts struct `U` from the start of the remaining
+    /// accounts, and advances the remaining accounts past them.
+    ///
+    /// The returned accounts are persisted after the instruction returns, the same way as the
+    /// top-level accounts. Their mutable accounts can't be any of the mutable accounts of
+    /// [`accounts`](Self::accounts).
+    ///
+    /// # Example
+    /// ```ignore
+    /// pub fn swap<'c: 'info, 'info>(
+    ///     mut ctx: Context<'_, '_, 'c, 'info, Swap<'info>>,
+    ///     hops: u8,
+    /// ) -> Result<()> {
+    ///     for _ in 0..hops {
+    ///         let mut hop = ctx.try_remaining_accounts::<Hop>()?;
+    ///         hop.pool.volume += 1;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn try_remaining_accounts<U>(&mut self) -> Result<RemainingAccounts<'info, U>>
+    where
+        'c: 'info,
+        T: DuplicateMutableKeys,
+        U: Bumps + Accounts<'info, U::Bumps> + AccountsExit<'info> + DuplicateMutableKeys + 'info,
+        U::Bumps: Default,
+    {
+        let mut accounts: &'info [AccountInfo<'info>] = self.remaining_accounts;
+        let mut bumps = U::Bumps::default();
+        let parsed = U::try_accounts(
+            self.program_id,
+            &mut accounts,
+            self.ix_data,
+            &mut bumps,
+            &mut self.reallocs,
+        )?;
+
+        let mutable_keys = self.accounts.duplicate_mutable_keys();
+        for key in parsed.duplicate_mutable_keys() {
+            if mutable_keys.contains(&key) {
+                return Err(Error::from(ErrorCode::ConstraintDuplicateMutableAccount)
+                    .with_account_name(format!("{key} (remaining_accounts)")));
+            }
+        }
+
+        let consumed = self.remaining_accounts.len() - accounts.len();
+        self.remaining_accounts = &self.remaining_accounts[consumed..];
+        Ok(RemainingAccounts {
+            accounts: Some(parsed),
+            bumps,
+            exits: self.remaining_exits.clone(),
+        })
+    }
+}
+
+/// Accounts structs deserialized from remaining accounts with
+/// [`Context::try_remaining_accounts`], persisted by the instruction handler once the
+/// instruction returns.
+#[derive(Clone, Default)]
+pub struct RemainingAccountsExits<'info> {
+    accounts: Rc<RefCell<Vec<Box<dyn AccountsExit<'info> + 'info>>>>,
+}
+
+impl<'info> RemainingAccountsExits<'info> {
+    /// Persists the accounts with [`AccountsExit`], in the order they were deserialized.
+    pub fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.accounts
+            .borrow()
+            .iter()
+            .try_for_each(|accounts| accounts.exit(program_id))
+    }
+}
+
+/// Accounts struct deserialized from remaining accounts with
+/// [`Context::try_remaining_accounts`].
+///
+/// Dereferences to the accounts struct, which is registered on the context when dropped to be
+/// persisted once the instruction returns.
+pub struct RemainingAccounts<'info, T: Bumps + AccountsExit<'info> + 'info> {
+    accounts: Option<T>,
+    /// Bump seeds found during constraint validation.
+    pub bumps: T::Bumps,
+    exits: RemainingAccountsExits<'info>,
+}
+
+impl<'info, T: Bumps + AccountsExit<'info> + 'info> Deref for RemainingAccounts<'info, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.accounts
+            .as_ref()
+            .expect("Accounts are only taken on drop")
+    }
+}
+
+impl<'info, T: Bumps + AccountsExit<'info> + 'info> DerefMut for RemainingAccounts<'info, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.accounts
+            .as_mut()
+            .expect("Accounts are only taken on drop")
+    }
+}
+
+impl<'info, T: Bumps + AccountsExit<'info> + 'info> Drop for RemainingAccounts<'info, T> {
+    fn drop(&mut self) {
+        if let Some(accounts) = self.accounts.take() {
+            self.exits.accounts.borrow_mut().push(Box::new(accounts));
         }
     }
 }
@@ -173,33 +173,44 @@ pub enum ErrorCode {
     ConstraintDuplicateMutableAccount,
 
     // Signature verification errors
-    /// 2040 - Invalid Ed25519 program id for signature verification
+    /// 2041 - Invalid Ed25519 program id for signature verification
     #[msg("Invalid Ed25519 program id for signature verification")]
     Ed25519InvalidProgram,
-    /// 2041 - Invalid Secp256k1 program id for signature verification
+    /// 2042 - Invalid Secp256k1 program id for signature verification
     #[msg("Invalid Secp256k1 program id for signature verification")]
     Secp256k1InvalidProgram,
-    /// 2042 - Instruction unexpectedly had account metas
+    /// 2043 - Instruction unexpectedly had account metas
     #[msg("Instruction unexpectedly had account metas")]
     InstructionHasAccounts,
-    /// 2043 - Message length exceeds allowed maximum
+    /// 2044 - Message length exceeds allowed maximum
     #[msg("Message length exceeds allowed maximum")]
     MessageTooLong,
     /// 2045 - Invalid Secp256k1 recovery id (must be 0 or 1)
     #[msg("Invalid Secp256k1 recovery id")]
     InvalidRecoveryId,
-    /// 2047 - Signature verification failed
+    /// 2046 - Signature verification failed
     #[msg("Signature verification failed")]
     SignatureVerificationFailed,
 
     // Migration errors
-    /// 2048 - Account is already migrated
+    /// 2047 - Account is already migrated
     #[msg("Account is already migrated")]
     AccountAlreadyMigrated,
-    /// 2049 - Account must be migrated before exiting
+    /// 2048 - Account must be migrated before exiting
     #[msg("Account must be migrated before exiting")]
     AccountNotMigrated,
 
+    // Reentrancy errors
+    /// 2049 - Instruction cannot be invoked through a CPI
+    #[msg("Instruction cannot be invoked through a CPI")]
+    ReentrancyGuardCpi,
+    /// 2050 - Instruction cannot be invoked while its program is already executing
+    #[msg("Instruction cannot be invoked while its program is already executing")]
+    ReentrancyGuardReentered,
+    /// 2051 - Instruction cannot be invoked through a CPI whose caller can't be determined
+    #[msg("Instruction cannot be invoked through a CPI whose caller can't be determined")]
+    ReentrancyGuardUnknownCaller,
+
     // Require
     /// 2500 - A require expression was violated
     #[msg("A require expression was violated")]
@@ -497,7 +508,7 @@ impl From<ProgramError> for ProgramErrorWithOrigin {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ComparedValues {
     Values((String, String)),
     Pubkeys((Pubkey, Pubkey)),
@@ -43,6 +43,7 @@ pub mod error;
 pub mod event;
 #[doc(hidden)]
 pub mod idl;
+pub mod reentrancy;
 pub mod signature_verification;
 pub mod system_program;
 mod vec;
@@ -51,7 +52,7 @@ mod vec;
 mod lazy;
 
 pub use crate::bpf_upgradeable_state::*;
-pub use anchor_attribute_access_control::access_control;
+pub use anchor_attribute_access_control::{access_control, reentrancy_guard};
 pub use anchor_attribute_account::{account, declare_id, pubkey, zero_copy};
 pub use anchor_attribute_constant::constant;
 pub use anchor_attribute_error::*;
@@ -139,6 +140,7 @@ pub mod solana_program {
             pub use solana_instruction::{BorrowedAccountMeta, BorrowedInstruction};
             #[cfg(not(target_os = "solana"))]
             pub use solana_instructions_sysvar::construct_instructions_data;
+            pub use solana_sdk_ids::sysvar::instructions::{check_id, id, ID};
         }
     }
 }
@@ -221,6 +223,13 @@ pub trait AccountsExit<'info>: ToAccountMetas + ToAccountInfos<'info> {
         // no-op
         Ok(())
     }
+
+    /// Same as [`exit`](Self::exit), but skips persisting accounts that were not
+    /// modified by the instruction. Defaults to [`exit`](Self::exit) for types
+    /// that don't track modifications.
+    fn exit_if_modified(&self, program_id: &Pubkey) -> Result<()> {
+        self.exit(program_id)
+    }
 }
 
 /// The close procedure to initiate garabage collection of an account, allowing
@@ -488,10 +497,10 @@ pub mod prelude {
         accounts::interface_account::InterfaceAccount, accounts::migration::Migration,
         accounts::program::Program, accounts::signer::Signer,
         accounts::system_account::SystemAccount, accounts::sysvar::Sysvar,
-        accounts::unchecked_account::UncheckedAccount, constant, context::Context,
-        context::CpiContext, declare_id, declare_program, emit, err, error, event, instruction,
-        program, pubkey, require, require_eq, require_gt, require_gte, require_keys_eq,
-        require_keys_neq, require_neq,
+        accounts::unchecked_account::UncheckedAccount, accounts::versioned::Versioned, constant,
+        context::Context, context::CpiContext, declare_id, declare_program, emit, err, error,
+        event, instruction, program, pubkey, reentrancy_guard, require, require_eq, require_gt,
+        require_gte, require_keys_eq, require_keys_neq, require_neq,
         solana_program::bpf_loader_upgradeable::UpgradeableLoaderState, source,
         system_program::System, zero_copy, AccountDeserialize, AccountSerialize, Accounts,
         AccountsClose, AccountsExit, AnchorDeserialize, AnchorSerialize, Discriminator, Id,
@@ -536,8 +545,9 @@ pub mod __private {
     pub use base64;
     pub use bytemuck;
 
-    pub use crate::{bpf_writer::BpfWriter, common::is_closed};
+    pub use crate::{bpf_writer::BpfWriter, common::is_closed, vec::accounts_len};
 
+    use crate::solana_program::account_info::AccountInfo;
     use crate::solana_program::pubkey::Pubkey;
 
     // Used to calculate the maximum between two expressions.
@@ -547,6 +557,24 @@ pub mod __private {
         [a, b][(a < b) as usize]
     }
 
+    /// Keys of the mutable accounts of an accounts struct that are checked for duplicates, used
+    /// to check the elements of composite `Vec` accounts against the other accounts.
+    #[doc(hidden)]
+    pub trait DuplicateMutableKeys {
+        fn duplicate_mutable_keys(&self) -> Vec<Pubkey>;
+    }
+
+    /// Accounts structs holding the instructions sysvar, required by the
+    /// [`reentrancy_guard`](crate::reentrancy_guard) attribute.
+    #[doc(hidden)]
+    #[diagnostic::on_unimplemented(
+        message = "`{Self}` must hold the instructions sysvar to use `#[reentrancy_guard]`",
+        note = "mark the field holding the instructions sysvar with `#[account(instructions_sysvar)]`"
+    )]
+    pub trait InstructionsSysvar<'info> {
+        fn instructions_sysvar(&self) -> AccountInfo<'info>;
+    }
+
     // Very experimental trait.
     #[doc(hidden)]
     pub trait ZeroCopyAccessor<Ty> {
@@ -0,0 +1,108 @@
+//! Runtime checks used by the [`reentrancy_guard`](crate::reentrancy_guard) attribute.
+
+use crate::error::ErrorCode;
+use crate::prelude::*;
+use crate::solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+
+/// Returns an error if the current instruction was invoked through a CPI.
+pub fn check_not_cpi() -> Result<()> {
+    if get_stack_height() > TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Err(error!(ErrorCode::ReentrancyGuardCpi));
+    }
+    Ok(())
+}
+
+/// Returns an error if the current instruction was invoked through a CPI while `program_id` is
+/// already executing.
+///
+/// The runtime only allows a program to be re-entered through direct self-recursion. The
+/// instructions sysvar only holds the transaction-level instructions, so the immediate caller is
+/// only known for a direct CPI from the transaction-level instruction, which is rejected when it
+/// belongs to `program_id`. Deeper invocations are rejected with
+/// [`ErrorCode::ReentrancyGuardUnknownCaller`], since their caller can't be determined.
+pub fn check_not_reentrant(program_id: &Pubkey, ix_sysvar: &AccountInfo) -> Result<()> {
+    check_reentrancy(program_id, get_stack_height(), ix_sysvar)
+}
+
+fn check_reentrancy(
+    program_id: &Pubkey,
+    stack_height: usize,
+    ix_sysvar: &AccountInfo,
+) -> Result<()> {
+    if stack_height <= TRANSACTION_LEVEL_STACK_HEIGHT {
+        return Ok(());
+    }
+    if stack_height > TRANSACTION_LEVEL_STACK_HEIGHT + 1 {
+        return Err(error!(ErrorCode::ReentrancyGuardUnknownCaller));
+    }
+    let index = load_current_index_checked(ix_sysvar)?;
+    let ix = load_instruction_at_checked(index as usize, ix_sysvar)?;
+    if ix.program_id == *program_id {
+        return Err(error!(ErrorCode::ReentrancyGuardReentered));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_instruction::BorrowedInstruction;
+
+    fn ix_sysvar_data(program_id: &Pubkey) -> Vec<u8> {
+        solana_instructions_sysvar::construct_instructions_data(&[BorrowedInstruction {
+            program_id,
+            accounts: vec![],
+            data: &[],
+        }])
+    }
+
+    #[test]
+    fn reentrancy_is_detected() {
+        let program_id = Pubkey::new_unique();
+        let caller_id = Pubkey::new_unique();
+        let key = solana_sdk_ids::sysvar::instructions::ID;
+        let owner = solana_sdk_ids::sysvar::ID;
+        let mut lamports = 0;
+
+        let mut data = ix_sysvar_data(&program_id);
+        let ix_sysvar =
+            AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false);
+        assert!(check_reentrancy(&program_id, TRANSACTION_LEVEL_STACK_HEIGHT, &ix_sysvar).is_ok());
+        assert_eq!(
+            check_reentrancy(&program_id, TRANSACTION_LEVEL_STACK_HEIGHT + 1, &ix_sysvar)
+                .unwrap_err(),
+            ErrorCode::ReentrancyGuardReentered.into()
+        );
+
+        // Invoked by another program
+        let mut data = ix_sysvar_data(&caller_id);
+        let ix_sysvar =
+            AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false);
+        assert!(
+            check_reentrancy(&program_id, TRANSACTION_LEVEL_STACK_HEIGHT + 1, &ix_sysvar).is_ok()
+        );
+    }
+
+    #[test]
+    fn unknown_callers_are_rejected() {
+        let program_id = Pubkey::new_unique();
+        let caller_id = Pubkey::new_unique();
+        let key = solana_sdk_ids::sysvar::instructions::ID;
+        let owner = solana_sdk_ids::sysvar::ID;
+        let mut lamports = 0;
+
+        // The caller of X -> A -> A can't be determined from the transaction-level instruction
+        for program in [&program_id, &caller_id] {
+            let mut data = ix_sysvar_data(program);
+            let ix_sysvar =
+                AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false);
+            for stack_height in TRANSACTION_LEVEL_STACK_HEIGHT + 2..=5 {
+                assert_eq!(
+                    check_reentrancy(&program_id, stack_height, &ix_sysvar).unwrap_err(),
+                    ErrorCode::ReentrancyGuardUnknownCaller.into()
+                );
+            }
+        }
+    }
+}
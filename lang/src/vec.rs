@@ -1,9 +1,10 @@
 // Avoiding AccountInfo deprecated msg in anchor context
 #![allow(deprecated)]
+use crate::error::ErrorCode;
 use crate::solana_program::account_info::AccountInfo;
 use crate::solana_program::instruction::AccountMeta;
 use crate::solana_program::pubkey::Pubkey;
-use crate::{Accounts, Result, ToAccountInfos, ToAccountMetas};
+use crate::{Accounts, AccountsExit, Result, ToAccountInfos, ToAccountMetas};
 use std::collections::BTreeSet;
 
 impl<'info, T: ToAccountInfos<'info>> ToAccountInfos<'info> for Vec<T> {
@@ -37,6 +38,26 @@ impl<'info, B, T: Accounts<'info, B>> Accounts<'info, B> for Vec<T> {
     }
 }
 
+impl<'info, T: AccountsExit<'info>> AccountsExit<'info> for Vec<T> {
+    fn exit(&self, program_id: &Pubkey) -> Result<()> {
+        self.iter().try_for_each(|item| item.exit(program_id))
+    }
+
+    fn exit_if_modified(&self, program_id: &Pubkey) -> Result<()> {
+        self.iter()
+            .try_for_each(|item| item.exit_if_modified(program_id))
+    }
+}
+
+/// Converts the `len` constraint of a `Vec` accounts field to the number of accounts to
+/// deserialize.
+pub fn accounts_len<L: TryInto<usize>>(len: L) -> Result<usize> {
+    // A length that doesn't fit in `usize`, e.g. a negative one, can't be satisfied by the
+    // given accounts
+    len.try_into()
+        .map_err(|_| ErrorCode::AccountNotEnoughKeys.into())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::solana_program::pubkey::Pubkey;
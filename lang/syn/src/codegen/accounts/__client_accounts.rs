@@ -43,9 +43,16 @@ pub fn generate(
                 )
                 .parse()
                 .unwrap();
-                quote! {
-                    #docs
-                    pub #name: #symbol
+                if s.constraints.is_list() {
+                    quote! {
+                        #docs
+                        pub #name: Vec<#symbol>
+                    }
+                } else {
+                    quote! {
+                        #docs
+                        pub #name: #symbol
+                    }
                 }
             }
             AccountField::Field(f) => {
@@ -67,6 +74,11 @@ pub fn generate(
                         #docs
                         pub #name: Option<Pubkey>
                     }
+                } else if f.constraints.is_list() {
+                    quote! {
+                        #docs
+                        pub #name: Vec<Pubkey>
+                    }
                 } else {
                     quote! {
                         #docs
@@ -109,6 +121,12 @@ pub fn generate(
                             account_metas.push(anchor_lang::solana_program::instruction::AccountMeta::new_readonly(#program_id, false));
                         }
                     }
+                } else if f.constraints.is_list() {
+                    quote! {
+                        for #name in &self.#name {
+                            account_metas.push(#meta(*#name, #is_signer));
+                        }
+                    }
                 } else {
                     quote! {
                         account_metas.push(#meta(self.#name, #is_signer));
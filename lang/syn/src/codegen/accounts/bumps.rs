@@ -62,8 +62,17 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
                 }
                 AccountField::CompositeField(s) => {
                     let comp_bumps_struct = generate_bumps_name(&s.symbol);
-                    let bumps = quote!(pub #ident: #comp_bumps_struct);
-                    let bumps_default = quote!(#ident: #comp_bumps_struct::default());
+                    let (bumps, bumps_default) = if s.constraints.is_list() {
+                        (
+                            quote!(pub #ident: Vec<#comp_bumps_struct>),
+                            quote!(#ident: Vec::new()),
+                        )
+                    } else {
+                        (
+                            quote!(pub #ident: #comp_bumps_struct),
+                            quote!(#ident: #comp_bumps_struct::default()),
+                        )
+                    };
 
                     Some((bumps, bumps_default))
                 }
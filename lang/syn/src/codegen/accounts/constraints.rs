@@ -49,6 +49,16 @@ pub fn generate(f: &Field, accs: &AccountsStruct) -> proc_macro2::TokenStream {
         };
     }
 
+    // If the field is a list we do all the inner checks for each element.
+    if f.constraints.is_list() && !constraints.is_empty() {
+        let ident = &f.ident;
+        all_checks = quote! {
+            for #ident in #ident.iter() {
+                #all_checks
+            }
+        };
+    }
+
     quote! {
         #rent
         #all_checks
@@ -86,10 +96,13 @@ pub fn linearize(c_group: &ConstraintGroup) -> Vec<Constraint> {
         executable,
         close,
         address,
+        instructions_sysvar: _,
         associated_token,
         token_account,
         mint,
         realloc,
+        skip_unchanged: _,
+        len: _,
     } = c_group.clone();
 
     let mut constraints = Vec::new();
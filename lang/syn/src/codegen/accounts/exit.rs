@@ -52,6 +52,10 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
                                 self.#ident.exit(program_id)
                                     .map_err(|e| e.with_account_name(#name_str))?;
                             },
+                            _ if f.constraints.is_skip_unchanged() => quote! {
+                                anchor_lang::AccountsExit::exit_if_modified(&self.#ident, program_id)
+                                    .map_err(|e| e.with_account_name(#name_str))?;
+                            },
                             _ => quote! {
                                 anchor_lang::AccountsExit::exit(&self.#ident, program_id)
                                     .map_err(|e| e.with_account_name(#name_str))?;
@@ -1,5 +1,5 @@
 use crate::codegen::accounts::{bumps, constraints, generics, ParsedGenerics};
-use crate::{AccountField, AccountsStruct, Ty};
+use crate::{AccountField, AccountsStruct, CompositeField, Field, Ty};
 use quote::{quote, quote_spanned};
 use syn::Expr;
 
@@ -19,6 +19,25 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
         .iter()
         .map(|af: &AccountField| {
             match af {
+                AccountField::CompositeField(s) if s.constraints.is_list() => {
+                    let name = &s.ident;
+                    let ty = &s.raw_field.ty;
+                    let len = &s.constraints.len.as_ref().unwrap().len;
+                    quote! {
+                        #[cfg(feature = "anchor-debug")]
+                        ::anchor_lang::solana_program::log::sol_log(stringify!(#name));
+                        let #name: Vec<#ty> = {
+                            let __len = anchor_lang::__private::accounts_len(#len)?;
+                            let mut __list = Vec::with_capacity(__len.min(__accounts.len()));
+                            for _ in 0..__len {
+                                let mut __item_bumps = Default::default();
+                                __list.push(anchor_lang::Accounts::try_accounts(__program_id, __accounts, __ix_data, &mut __item_bumps, __reallocs)?);
+                                __bumps.#name.push(__item_bumps);
+                            }
+                            __list
+                        };
+                    }
+                }
                 AccountField::CompositeField(s) => {
                     let name = &s.ident;
                     let ty = &s.raw_field.ty;
@@ -65,6 +84,25 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
                                 *__accounts = &__accounts[1..];
                             }
                         }
+                    } else if f.constraints.is_list() {
+                        let ident = &f.ident;
+                        let name = f.ident.to_string();
+                        let typed_name = f.typed_ident();
+                        let len = &f.constraints.len.as_ref().unwrap().len;
+                        quote! {
+                            #[cfg(feature = "anchor-debug")]
+                            ::anchor_lang::solana_program::log::sol_log(stringify!(#typed_name));
+                            let #typed_name = {
+                                let __len = anchor_lang::__private::accounts_len(#len)
+                                    .map_err(|e| e.with_account_name(#name))?;
+                                let mut #ident = Vec::with_capacity(__len.min(__accounts.len()));
+                                for _ in 0..__len {
+                                    #ident.push(anchor_lang::Accounts::try_accounts(__program_id, __accounts, __ix_data, __bumps, __reallocs)
+                                        .map_err(|e| e.with_account_name(#name))?);
+                                }
+                                #ident
+                            };
+                        }
                     } else {
                         let name = f.ident.to_string();
                         let typed_name = f.typed_ident();
@@ -226,8 +264,23 @@ pub fn generate(accs: &AccountsStruct) -> proc_macro2::TokenStream {
         }
     };
 
+    let duplicate_mutable_keys = generate_duplicate_mutable_keys(accs);
+    let instructions_sysvar = instructions_sysvar_field(accs).map(|f| {
+        let field = &f.ident;
+        quote! {
+            #[automatically_derived]
+            impl<#combined_generics> anchor_lang::__private::InstructionsSysvar<#trait_generics> for #name<#struct_generics> #where_clause {
+                fn instructions_sysvar(&self) -> anchor_lang::solana_program::account_info::AccountInfo<#trait_generics> {
+                    anchor_lang::ToAccountInfo::to_account_info(&self.#field)
+                }
+            }
+        }
+    });
+
     quote! {
         #param_count_const
+        #duplicate_mutable_keys
+        #instructions_sysvar
         #[automatically_derived]
         impl<#combined_generics> anchor_lang::Accounts<#trait_generics, #bumps_struct_name> for #name<#struct_generics> #where_clause {
             #[inline(never)]
@@ -320,11 +373,9 @@ fn is_init(af: &AccountField) -> bool {
     }
 }
 
-// Generates duplicate mutable account validation logic
-fn generate_duplicate_mutable_checks(accs: &AccountsStruct) -> proc_macro2::TokenStream {
-    // Collect all mutable account fields without `dup` constraint, excluding UncheckedAccount, Signer, and init accounts.
-    let candidates: Vec<_> = accs
-        .fields
+// Collect all mutable account fields without `dup` constraint, excluding UncheckedAccount, Signer, and init accounts.
+fn duplicate_mutable_candidates(accs: &AccountsStruct) -> Vec<&Field> {
+    accs.fields
         .iter()
         .filter_map(|af| match af {
             AccountField::Field(f)
@@ -340,9 +391,75 @@ fn generate_duplicate_mutable_checks(accs: &AccountsStruct) -> proc_macro2::Toke
             }
             _ => None,
         })
-        .collect();
+        .collect()
+}
+
+// Composite `Vec` fields, whose elements are checked for duplicates against the other accounts.
+fn composite_list_fields(accs: &AccountsStruct) -> Vec<&CompositeField> {
+    accs.fields
+        .iter()
+        .filter_map(|af| match af {
+            AccountField::CompositeField(s) if s.constraints.is_list() => Some(s),
+            _ => None,
+        })
+        .collect()
+}
 
-    if candidates.is_empty() {
+// The field marked with the `instructions_sysvar` constraint, used by the `reentrancy_guard`
+// attribute.
+fn instructions_sysvar_field(accs: &AccountsStruct) -> Option<&Field> {
+    accs.fields.iter().find_map(|af| match af {
+        AccountField::Field(f) if f.constraints.instructions_sysvar.is_some() => Some(f),
+        _ => None,
+    })
+}
+
+// Generates the `DuplicateMutableKeys` implementation, returning the keys checked for
+// duplicates so that the elements of composite `Vec` fields can be checked by the parent struct.
+fn generate_duplicate_mutable_keys(accs: &AccountsStruct) -> proc_macro2::TokenStream {
+    let name = &accs.ident;
+    let field_keys = duplicate_mutable_candidates(accs).into_iter().map(|f| {
+        let name = &f.ident;
+        if f.constraints.is_list() {
+            quote! { __keys.extend(self.#name.iter().map(anchor_lang::Key::key)); }
+        } else if f.is_optional {
+            quote! {
+                if let Some(#name) = &self.#name {
+                    __keys.push(anchor_lang::Key::key(#name));
+                }
+            }
+        } else {
+            quote! { __keys.push(anchor_lang::Key::key(&self.#name)); }
+        }
+    });
+    let composite_keys = composite_list_fields(accs).into_iter().map(|s| {
+        let name = &s.ident;
+        quote! {
+            __keys.extend(self.#name.iter().flat_map(anchor_lang::__private::DuplicateMutableKeys::duplicate_mutable_keys));
+        }
+    });
+
+    let (impl_gen, ty_gen, where_clause) = accs.generics.split_for_impl();
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_gen anchor_lang::__private::DuplicateMutableKeys for #name #ty_gen #where_clause {
+            fn duplicate_mutable_keys(&self) -> Vec<anchor_lang::solana_program::pubkey::Pubkey> {
+                let mut __keys = Vec::new();
+                #(#field_keys)*
+                #(#composite_keys)*
+                __keys
+            }
+        }
+    }
+}
+
+// Generates duplicate mutable account validation logic
+fn generate_duplicate_mutable_checks(accs: &AccountsStruct) -> proc_macro2::TokenStream {
+    let candidates = duplicate_mutable_candidates(accs);
+    let composite_lists = composite_list_fields(accs);
+
+    if candidates.is_empty() && composite_lists.is_empty() {
         // No declared mutable accounts, but still need to check remaining_accounts
         return quote! {
             // Duplicate mutable account validation for remaining_accounts only
@@ -365,10 +482,18 @@ fn generate_duplicate_mutable_checks(accs: &AccountsStruct) -> proc_macro2::Toke
 
     let mut field_keys = Vec::with_capacity(candidates.len());
     let mut field_name_strs = Vec::with_capacity(candidates.len());
+    let mut list_keys = Vec::new();
+    let mut list_name_strs = Vec::new();
 
     for f in candidates.iter() {
         let name = &f.ident;
 
+        if f.constraints.is_list() {
+            list_keys.push(quote! { #name.iter().map(|f| f.key()) });
+            list_name_strs.push(quote! { stringify!(#name) });
+            continue;
+        }
+
         if f.is_optional {
             field_keys.push(quote! { #name.as_ref().map(|f| f.key()) });
         } else {
@@ -379,6 +504,14 @@ fn generate_duplicate_mutable_checks(accs: &AccountsStruct) -> proc_macro2::Toke
         field_name_strs.push(quote! { stringify!(#name) });
     }
 
+    for s in composite_lists {
+        let name = &s.ident;
+        list_keys.push(quote! {
+            #name.iter().flat_map(anchor_lang::__private::DuplicateMutableKeys::duplicate_mutable_keys)
+        });
+        list_name_strs.push(quote! { stringify!(#name) });
+    }
+
     quote! {
         // Duplicate mutable account validation - using HashSet
         {
@@ -395,6 +528,15 @@ fn generate_duplicate_mutable_checks(accs: &AccountsStruct) -> proc_macro2::Toke
                     }
                 }
             )*
+            #(
+                for key in #list_keys {
+                    if !__mutable_accounts.insert(key) {
+                        return Err(anchor_lang::error::Error::from(
+                            anchor_lang::error::ErrorCode::ConstraintDuplicateMutableAccount
+                        ).with_account_name(#list_name_strs));
+                    }
+                }
+            )*
 
             // This prevents duplicates from being passed via remaining_accounts
             for __remaining_account in __accounts.iter() {
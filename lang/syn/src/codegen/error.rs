@@ -47,6 +47,13 @@ pub fn generate(error: Error) -> proc_macro2::TokenStream {
         })
         .collect();
 
+    let variant_idents: Vec<&syn::Ident> = error
+        .raw_enum
+        .variants
+        .iter()
+        .map(|variant| &variant.ident)
+        .collect();
+
     let offset = match &error.args {
         None => quote! { anchor_lang::error::ERROR_CODE_OFFSET},
         Some(args) => {
@@ -67,6 +74,16 @@ pub fn generate(error: Error) -> proc_macro2::TokenStream {
                     #(#name_variant_dispatch),*
                 }
             }
+
+            /// Gets the [#enum_name] with the given error code number.
+            pub fn from_code(code: u32) -> Option<Self> {
+                #(
+                    if code == u32::from(#enum_name::#variant_idents) {
+                        return Some(#enum_name::#variant_idents);
+                    }
+                )*
+                None
+            }
         }
 
         impl From<#enum_name> for u32 {
@@ -118,6 +118,9 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
 
                     let mut __reallocs = std::collections::BTreeSet::new();
 
+                    // Accounts deserialized from the remaining accounts by the user defined handler.
+                    let __remaining_exits = anchor_lang::context::RemainingAccountsExits::default();
+
                     // Deserialize accounts.
                     let mut __remaining_accounts: &[AccountInfo] = __accounts;
                     let mut __accounts = #anchor::try_accounts(
@@ -135,7 +138,10 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                             &mut __accounts,
                             __remaining_accounts,
                             __bumps,
-                        ),
+                        )
+                        .with_ix_data(__ix_data)
+                        .with_reallocs(__reallocs)
+                        .with_remaining_exits(__remaining_exits.clone()),
                         #(#ix_arg_names),*
                     )?;
 
@@ -143,7 +149,8 @@ pub fn generate(program: &Program) -> proc_macro2::TokenStream {
                     #maybe_set_return_data
 
                     // Exit routine.
-                    __accounts.exit(__program_id)
+                    __accounts.exit(__program_id)?;
+                    __remaining_exits.exit(__program_id)
                 }
             }
         })
@@ -3,7 +3,9 @@ use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 
 use super::common::{get_idl_module_path, get_no_docs};
-use crate::{AccountField, AccountsStruct, ConstraintSeedsGroup, Field, InitKind, Ty};
+use crate::{
+    AccountField, AccountsStruct, ConstraintGroup, ConstraintSeedsGroup, Field, InitKind, Ty,
+};
 
 /// Generate the IDL build impl for the Accounts struct.
 pub fn gen_idl_build_impl_accounts_struct(accounts: &AccountsStruct) -> TokenStream {
@@ -28,6 +30,7 @@ pub fn gen_idl_build_impl_accounts_struct(accounts: &AccountsStruct) -> TokenStr
                     _ => acc.constraints.is_signer(),
                 };
                 let optional = acc.is_optional;
+                let len = get_len(&acc.constraints, accounts);
                 let docs = match &acc.docs {
                     Some(docs) if !no_docs => quote! { vec![#(#docs.into()),*] },
                     _ => quote! { vec![] },
@@ -62,6 +65,7 @@ pub fn gen_idl_build_impl_accounts_struct(accounts: &AccountsStruct) -> TokenStr
                     Ty::LazyAccount(ty) => Some(&ty.account_type_path),
                     Ty::AccountLoader(ty) => Some(&ty.account_type_path),
                     Ty::InterfaceAccount(ty) => Some(&ty.account_type_path),
+                    Ty::Versioned(ty) => Some(ty.latest()),
                     _ => None,
                 };
 
@@ -76,6 +80,7 @@ pub fn gen_idl_build_impl_accounts_struct(accounts: &AccountsStruct) -> TokenStr
                             address: #address,
                             pda: #pda,
                             relations: #relations,
+                            len: #len,
                         })
                     },
                     acc_type_path,
@@ -103,12 +108,14 @@ pub fn gen_idl_build_impl_accounts_struct(accounts: &AccountsStruct) -> TokenStr
                     )
                 };
                 let name = comp_f.ident.to_string();
+                let len = get_len(&comp_f.constraints, accounts);
 
                 (
                     quote! {
                         #idl::IdlInstructionAccountItem::Composite(#idl::IdlInstructionAccounts {
                             name: #name.into(),
                             accounts: <#ty>::__anchor_private_gen_idl_accounts(accounts, types),
+                            len: #len,
                         })
                     },
                     None,
@@ -142,6 +149,57 @@ pub fn gen_idl_build_impl_accounts_struct(accounts: &AccountsStruct) -> TokenStr
     }
 }
 
+/// Get the length of a list of accounts, as an instruction argument or account field path if
+/// possible.
+fn get_len(constraints: &ConstraintGroup, accounts: &AccountsStruct) -> TokenStream {
+    let idl = get_idl_module_path();
+    let Some(len) = constraints.len.as_ref().map(|c| &c.len) else {
+        return quote! { None };
+    };
+
+    // Ignore casts and parentheses e.g. `(params.count as usize)`
+    let mut expr = len;
+    loop {
+        match expr {
+            syn::Expr::Cast(cast) => expr = &cast.expr,
+            syn::Expr::Paren(paren) => expr = &paren.expr,
+            _ => break,
+        }
+    }
+
+    let len = match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(value),
+            ..
+        }) => value
+            .base10_parse::<usize>()
+            .ok()
+            .map(|value| quote! { #idl::IdlAccountsLen::Value(#value) }),
+        syn::Expr::Path(_) | syn::Expr::Field(_) => {
+            let path = expr.to_token_stream().to_string().replace(' ', "");
+            let name = path.split('.').next().unwrap_or_default();
+            if accounts
+                .instruction_args()
+                .unwrap_or_default()
+                .contains_key(name)
+            {
+                Some(quote! { #idl::IdlAccountsLen::Arg(#path.into()) })
+            } else if accounts.field_names().iter().any(|field| field == name) {
+                Some(quote! { #idl::IdlAccountsLen::Account(#path.into()) })
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+    .unwrap_or_else(|| {
+        let expr = len.to_token_stream().to_string();
+        quote! { #idl::IdlAccountsLen::Expr(#expr.into()) }
+    });
+
+    quote! { Some(#len) }
+}
+
 fn get_address(acc: &Field) -> TokenStream {
     match &acc.ty {
         Ty::Program(_) | Ty::Sysvar(_) => {
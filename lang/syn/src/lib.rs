@@ -362,6 +362,12 @@ impl Field {
                     #container_ty<'info, #from, #to>
                 }
             }
+            Ty::Versioned(ty) => {
+                let versions = &ty.version_type_paths;
+                quote! {
+                    #container_ty<'info, (#(#versions,)*)>
+                }
+            }
             _ => quote! {
                 #container_ty<#account_ty>
             },
@@ -370,6 +376,10 @@ impl Field {
             quote! {
                 Option<#inner_ty>
             }
+        } else if self.constraints.is_list() {
+            quote! {
+                Vec<#inner_ty>
+            }
         } else {
             quote! {
                 #inner_ty
@@ -494,6 +504,9 @@ impl Field {
             Ty::Migration(_) => quote! {
                 anchor_lang::accounts::migration::Migration
             },
+            Ty::Versioned(_) => quote! {
+                anchor_lang::accounts::versioned::Versioned
+            },
             Ty::Sysvar(_) => quote! { anchor_lang::accounts::sysvar::Sysvar },
             Ty::Program(_) => quote! { anchor_lang::accounts::program::Program },
             Ty::Interface(_) => quote! { anchor_lang::accounts::interface::Interface },
@@ -557,6 +570,12 @@ impl Field {
                     #from
                 }
             }
+            Ty::Versioned(ty) => {
+                let latest = ty.latest();
+                quote! {
+                    #latest
+                }
+            }
             Ty::Sysvar(ty) => match ty {
                 SysvarTy::Clock => quote! {Clock},
                 SysvarTy::Rent => quote! {Rent},
@@ -611,6 +630,7 @@ pub enum Ty {
     Account(AccountTy),
     LazyAccount(LazyAccountTy),
     Migration(MigrationTy),
+    Versioned(VersionedTy),
     Program(ProgramTy),
     Interface(InterfaceTy),
     InterfaceAccount(InterfaceAccountTy),
@@ -660,6 +680,20 @@ pub struct MigrationTy {
     pub to_type_path: TypePath,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct VersionedTy {
+    // Versioned<'info, (V1, V2, ...)> - the account types from the oldest to the latest version
+    pub version_type_paths: Vec<TypePath>,
+}
+
+impl VersionedTy {
+    pub fn latest(&self) -> &TypePath {
+        self.version_type_paths
+            .last()
+            .expect("Versioned has at least two versions")
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub struct InterfaceAccountTy {
     // The struct type of the account.
@@ -726,6 +760,7 @@ pub struct ConstraintGroup {
     pub rent_exempt: Option<ConstraintRentExempt>,
     pub seeds: Option<ConstraintSeedsGroup>,
     pub executable: Option<ConstraintExecutable>,
+    pub instructions_sysvar: Option<ConstraintInstructionsSysvar>,
     pub has_one: Vec<ConstraintHasOne>,
     pub raw: Vec<ConstraintRaw>,
     pub close: Option<ConstraintClose>,
@@ -734,6 +769,8 @@ pub struct ConstraintGroup {
     pub token_account: Option<ConstraintTokenAccountGroup>,
     pub mint: Option<ConstraintTokenMintGroup>,
     pub realloc: Option<ConstraintReallocGroup>,
+    pub skip_unchanged: Option<ConstraintSkipUnchanged>,
+    pub len: Option<ConstraintLen>,
 }
 
 impl ConstraintGroup {
@@ -756,6 +793,19 @@ impl ConstraintGroup {
     pub fn is_close(&self) -> bool {
         self.close.is_some()
     }
+
+    /// Whether the field is a `Vec` of accounts, with its length given by the `len` constraint.
+    pub fn is_list(&self) -> bool {
+        self.len.is_some()
+    }
+
+    /// Whether the account is only persisted on exit if it was modified, either
+    /// because of the `skip_unchanged` constraint or the `skip-unchanged` feature.
+    pub fn is_skip_unchanged(&self) -> bool {
+        let is_initialized_on_exit = self.init.is_some() || self.is_zeroed();
+        !is_initialized_on_exit
+            && (self.skip_unchanged.is_some() || cfg!(feature = "skip-unchanged"))
+    }
 }
 
 // A single account constraint *after* merging all tokens into a well formed
@@ -798,6 +848,9 @@ pub enum ConstraintToken {
     RentExempt(Context<ConstraintRentExempt>),
     Seeds(Context<ConstraintSeeds>),
     Executable(Context<ConstraintExecutable>),
+    InstructionsSysvar(Context<ConstraintInstructionsSysvar>),
+    SkipUnchanged(Context<ConstraintSkipUnchanged>),
+    Len(Context<ConstraintLen>),
     Close(Context<ConstraintClose>),
     Payer(Context<ConstraintPayer>),
     Space(Context<ConstraintSpace>),
@@ -1031,6 +1084,19 @@ pub struct ConstraintSeeds {
 #[derive(Debug, Clone)]
 pub struct ConstraintExecutable {}
 
+/// Marks the field holding the instructions sysvar, checked by address like the `address`
+/// constraint and used by the `reentrancy_guard` attribute.
+#[derive(Debug, Clone)]
+pub struct ConstraintInstructionsSysvar {}
+
+#[derive(Debug, Clone)]
+pub struct ConstraintSkipUnchanged {}
+
+#[derive(Debug, Clone)]
+pub struct ConstraintLen {
+    pub len: Expr,
+}
+
 #[derive(Debug, Clone)]
 pub struct ConstraintPayer {
     pub target: Expr,
@@ -50,6 +50,13 @@ pub fn parse_token(stream: ParseStream) -> ParseResult<ConstraintToken> {
         "executable" => {
             ConstraintToken::Executable(Context::new(ident.span(), ConstraintExecutable {}))
         }
+        "instructions_sysvar" => ConstraintToken::InstructionsSysvar(Context::new(
+            ident.span(),
+            ConstraintInstructionsSysvar {},
+        )),
+        "skip_unchanged" => {
+            ConstraintToken::SkipUnchanged(Context::new(ident.span(), ConstraintSkipUnchanged {}))
+        }
         "dup" => ConstraintToken::Dup(Context::new(ident.span(), ConstraintDup {})),
         "mint" => {
             stream.parse::<Token![:]>()?;
@@ -457,6 +464,12 @@ pub fn parse_token(stream: ParseStream) -> ParseResult<ConstraintToken> {
                         space: stream.parse()?,
                     },
                 )),
+                "len" => ConstraintToken::Len(Context::new(
+                    span,
+                    ConstraintLen {
+                        len: stream.parse()?,
+                    },
+                )),
                 "constraint" => ConstraintToken::Raw(Context::new(
                     span,
                     ConstraintRaw {
@@ -507,6 +520,9 @@ pub struct ConstraintGroupBuilder<'ty> {
     pub rent_exempt: Option<Context<ConstraintRentExempt>>,
     pub seeds: Option<Context<ConstraintSeeds>>,
     pub executable: Option<Context<ConstraintExecutable>>,
+    pub instructions_sysvar: Option<Context<ConstraintInstructionsSysvar>>,
+    pub skip_unchanged: Option<Context<ConstraintSkipUnchanged>>,
+    pub len: Option<Context<ConstraintLen>>,
     pub payer: Option<Context<ConstraintPayer>>,
     pub space: Option<Context<ConstraintSpace>>,
     pub close: Option<Context<ConstraintClose>>,
@@ -556,6 +572,9 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             rent_exempt: None,
             seeds: None,
             executable: None,
+            instructions_sysvar: None,
+            skip_unchanged: None,
+            len: None,
             payer: None,
             space: None,
             close: None,
@@ -717,6 +736,22 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             }
         }
 
+        // Skip unchanged.
+        if let Some(s) = &self.skip_unchanged {
+            if self.init.is_some() || self.zeroed.is_some() {
+                return Err(ParseError::new(
+                    s.span(),
+                    "skip_unchanged cannot be used with init or zero",
+                ));
+            }
+            if self.mutable.is_none() {
+                return Err(ParseError::new(
+                    s.span(),
+                    "mut must be provided with skip_unchanged",
+                ));
+            }
+        }
+
         // Seeds.
         if let Some(i) = &self.seeds {
             if self.init.is_some() && self.payer.is_none() {
@@ -769,6 +804,9 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             rent_exempt,
             seeds,
             executable,
+            instructions_sysvar,
+            skip_unchanged,
+            len,
             payer,
             space,
             close,
@@ -957,6 +995,14 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             }),
         };
 
+        // The instructions sysvar is checked by its address
+        let address = into_inner!(address).or_else(|| {
+            instructions_sysvar.as_ref().map(|_| ConstraintAddress {
+                address: syn::parse_quote!(anchor_lang::solana_program::sysvar::instructions::ID),
+                error: None,
+            })
+        });
+
         Ok(ConstraintGroup {
             init: init.as_ref().map(|i| Ok(ConstraintInitGroup {
                 if_needed: i.if_needed,
@@ -1024,8 +1070,11 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             owner: into_inner!(owner),
             rent_exempt: into_inner!(rent_exempt),
             executable: into_inner!(executable),
+            skip_unchanged: into_inner!(skip_unchanged),
+            len: into_inner!(len),
             close: into_inner!(close),
-            address: into_inner!(address),
+            address,
+            instructions_sysvar: into_inner!(instructions_sysvar),
             associated_token: if !is_init { associated_token } else { None },
             seeds,
             token_account: if !is_init {token_account} else {None},
@@ -1046,6 +1095,9 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             ConstraintToken::RentExempt(c) => self.add_rent_exempt(c),
             ConstraintToken::Seeds(c) => self.add_seeds(c),
             ConstraintToken::Executable(c) => self.add_executable(c),
+            ConstraintToken::InstructionsSysvar(c) => self.add_instructions_sysvar(c),
+            ConstraintToken::SkipUnchanged(c) => self.add_skip_unchanged(c),
+            ConstraintToken::Len(c) => self.add_len(c),
             ConstraintToken::Payer(c) => self.add_payer(c),
             ConstraintToken::Space(c) => self.add_space(c),
             ConstraintToken::Close(c) => self.add_close(c),
@@ -1196,10 +1248,11 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
             && !matches!(self.f_ty, Some(Ty::LazyAccount(_)))
             && !matches!(self.f_ty, Some(Ty::AccountLoader(_)))
             && !matches!(self.f_ty, Some(Ty::Migration(_)))
+            && !matches!(self.f_ty, Some(Ty::Versioned(_)))
         {
             return Err(ParseError::new(
                 c.span(),
-                "realloc must be on an Account, LazyAccount, AccountLoader, or Migration",
+                "realloc must be on an Account, LazyAccount, AccountLoader, Migration, or Versioned",
             ));
         }
         if self.mutable.is_none() {
@@ -1270,6 +1323,12 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
         if self.address.is_some() {
             return Err(ParseError::new(c.span(), "address already provided"));
         }
+        if self.instructions_sysvar.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "address cannot be used with instructions_sysvar",
+            ));
+        }
         self.address.replace(c);
         Ok(())
     }
@@ -1507,6 +1566,42 @@ impl<'ty> ConstraintGroupBuilder<'ty> {
         Ok(())
     }
 
+    fn add_instructions_sysvar(
+        &mut self,
+        c: Context<ConstraintInstructionsSysvar>,
+    ) -> ParseResult<()> {
+        if self.instructions_sysvar.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "instructions_sysvar already provided",
+            ));
+        }
+        if self.address.is_some() {
+            return Err(ParseError::new(
+                c.span(),
+                "instructions_sysvar cannot be used with address",
+            ));
+        }
+        self.instructions_sysvar.replace(c);
+        Ok(())
+    }
+
+    fn add_skip_unchanged(&mut self, c: Context<ConstraintSkipUnchanged>) -> ParseResult<()> {
+        if self.skip_unchanged.is_some() {
+            return Err(ParseError::new(c.span(), "skip_unchanged already provided"));
+        }
+        self.skip_unchanged.replace(c);
+        Ok(())
+    }
+
+    fn add_len(&mut self, c: Context<ConstraintLen>) -> ParseResult<()> {
+        if self.len.is_some() {
+            return Err(ParseError::new(c.span(), "len already provided"));
+        }
+        self.len.replace(c);
+        Ok(())
+    }
+
     fn add_payer(&mut self, c: Context<ConstraintPayer>) -> ParseResult<()> {
         if self.init.is_none() {
             return Err(ParseError::new(
@@ -300,16 +300,52 @@ fn constraints_cross_checks(fields: &[AccountField]) -> ParseResult<()> {
         }
     }
 
+    // INSTRUCTIONS SYSVAR
+    let mut instructions_sysvar_fields = fields.iter().filter(|f| {
+        matches!(f, AccountField::Field(field) if field.constraints.instructions_sysvar.is_some())
+    });
+    if let (Some(_), Some(field)) = (
+        instructions_sysvar_fields.next(),
+        instructions_sysvar_fields.next(),
+    ) {
+        return Err(ParseError::new(
+            field.ident().span(),
+            "instructions_sysvar can only be used on one account",
+        ));
+    }
+
     Ok(())
 }
 
 pub fn parse_account_field(f: &syn::Field) -> ParseResult<AccountField> {
+    // `Vec` fields are parsed as their element type, with the `len` constraint marking the list
+    let inner = vec_to_inner_field(f)?;
+    let is_list = inner.is_some();
+    let f = inner.as_ref().unwrap_or(f);
+    let account_field = parse_account_field_inner(f)?;
+    list_checks(&account_field, is_list, f)?;
+    Ok(account_field)
+}
+
+fn parse_account_field_inner(f: &syn::Field) -> ParseResult<AccountField> {
     let ident = f.ident.clone().unwrap();
     let docs = docs::parse(&f.attrs);
     let account_field = match is_field_primitive(f)? {
         true => {
             let (ty, is_optional) = parse_ty(f)?;
             let account_constraints = constraints::parse(f, Some(&ty))?;
+            if matches!(ty, Ty::Versioned(_)) && !account_constraints.is_mutable() {
+                return Err(ParseError::new(
+                    ident.span(),
+                    "Versioned accounts must be mutable since they are written back in the latest version",
+                ));
+            }
+            if is_optional && account_constraints.instructions_sysvar.is_some() {
+                return Err(ParseError::new(
+                    ident.span(),
+                    "instructions_sysvar cannot be used with optional accounts",
+                ));
+            }
             AccountField::Field(Field {
                 ident,
                 ty,
@@ -328,6 +364,12 @@ pub fn parse_account_field(f: &syn::Field) -> ParseResult<AccountField> {
                 ));
             }
             let account_constraints = constraints::parse(f, None)?;
+            if account_constraints.instructions_sysvar.is_some() {
+                return Err(ParseError::new(
+                    ident.span(),
+                    "instructions_sysvar cannot be used with composite accounts",
+                ));
+            }
             AccountField::CompositeField(CompositeField {
                 ident,
                 constraints: account_constraints,
@@ -340,6 +382,78 @@ pub fn parse_account_field(f: &syn::Field) -> ParseResult<AccountField> {
     Ok(account_field)
 }
 
+/// Returns the field with the element type if the field is a `Vec` of accounts.
+fn vec_to_inner_field(f: &syn::Field) -> ParseResult<Option<syn::Field>> {
+    let path = match &f.ty {
+        syn::Type::Path(ty_path)
+            if ty_path.path.segments.len() == 1 && ty_path.path.segments[0].ident == "Vec" =>
+        {
+            &ty_path.path
+        }
+        _ => return Ok(None),
+    };
+    let mut inner = f.clone();
+    inner.ty = syn::Type::Path(syn::TypePath {
+        qself: None,
+        path: option_to_inner_path(path)?,
+    });
+    Ok(Some(inner))
+}
+
+fn list_checks(account_field: &AccountField, is_list: bool, f: &syn::Field) -> ParseResult<()> {
+    let (constraints, field) = match account_field {
+        AccountField::Field(field) => (&field.constraints, Some(field)),
+        AccountField::CompositeField(field) => (&field.constraints, None),
+    };
+    match (is_list, constraints.is_list()) {
+        (true, false) => {
+            return Err(ParseError::new(
+                f.ty.span(),
+                "len must be provided for Vec accounts",
+            ))
+        }
+        (false, true) => {
+            return Err(ParseError::new(
+                f.ty.span(),
+                "len can only be used with Vec accounts",
+            ))
+        }
+        _ => {}
+    }
+
+    let Some(field) = field.filter(|_| is_list) else {
+        return Ok(());
+    };
+    if field.is_optional {
+        return Err(ParseError::new(
+            f.ty.span(),
+            "Vec accounts cannot be optional",
+        ));
+    }
+    if matches!(field.ty, Ty::LazyAccount(_)) {
+        return Err(ParseError::new(
+            f.ty.span(),
+            "LazyAccount cannot be used in Vec accounts",
+        ));
+    }
+    let c = &field.constraints;
+    let unsupported = [
+        (c.init.is_some(), "init"),
+        (c.is_zeroed(), "zero"),
+        (c.seeds.is_some(), "seeds"),
+        (c.is_close(), "close"),
+        (c.realloc.is_some(), "realloc"),
+        (c.instructions_sysvar.is_some(), "instructions_sysvar"),
+    ];
+    if let Some((_, name)) = unsupported.iter().find(|(used, _)| *used) {
+        return Err(ParseError::new(
+            f.ty.span(),
+            format!("{name} cannot be used with Vec accounts"),
+        ));
+    }
+    Ok(())
+}
+
 fn is_field_primitive(f: &syn::Field) -> ParseResult<bool> {
     let r = matches!(
         ident_string(f)?.0.as_str(),
@@ -350,6 +464,7 @@ fn is_field_primitive(f: &syn::Field) -> ParseResult<bool> {
             | "Account"
             | "LazyAccount"
             | "Migration"
+            | "Versioned"
             | "Program"
             | "Interface"
             | "InterfaceAccount"
@@ -370,6 +485,7 @@ fn parse_ty(f: &syn::Field) -> ParseResult<(Ty, bool)> {
         "Account" => Ty::Account(parse_account_ty(&path)?),
         "LazyAccount" => Ty::LazyAccount(parse_lazy_account_ty(&path)?),
         "Migration" => Ty::Migration(parse_migration_ty(&path)?),
+        "Versioned" => Ty::Versioned(parse_versioned_ty(&path)?),
         "Program" => Ty::Program(parse_program_ty(&path)?),
         "Interface" => Ty::Interface(parse_interface_ty(&path)?),
         "InterfaceAccount" => Ty::InterfaceAccount(parse_interface_account_ty(&path)?),
@@ -510,6 +626,43 @@ fn parse_migration_ty(path: &syn::Path) -> ParseResult<MigrationTy> {
     }
 }
 
+fn parse_versioned_ty(path: &syn::Path) -> ParseResult<VersionedTy> {
+    // Versioned<'info, (V1, V2, ...)>
+    let segments = &path.segments[0];
+    let versions = match &segments.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 2 => match &args.args[1] {
+            syn::GenericArgument::Type(syn::Type::Tuple(tuple)) => tuple,
+            arg => {
+                return Err(ParseError::new(
+                    arg.span(),
+                    "Versioned versions must be a tuple of account types",
+                ))
+            }
+        },
+        _ => {
+            return Err(ParseError::new(
+                segments.span(),
+                "Versioned requires two arguments: lifetime, and the tuple of versions",
+            ))
+        }
+    };
+    if versions.elems.len() < 2 {
+        return Err(ParseError::new(
+            versions.span(),
+            "Versioned requires at least two versions",
+        ));
+    }
+    let version_type_paths = versions
+        .elems
+        .iter()
+        .map(|ty| match ty {
+            syn::Type::Path(ty_path) => Ok(ty_path.clone()),
+            _ => Err(ParseError::new(ty.span(), "Version type must be a path")),
+        })
+        .collect::<ParseResult<_>>()?;
+    Ok(VersionedTy { version_type_paths })
+}
+
 fn parse_interface_account_ty(path: &syn::Path) -> ParseResult<InterfaceAccountTy> {
     let account_type_path = parse_account(path)?;
     let boxed = parser::tts_to_string(path)
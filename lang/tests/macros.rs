@@ -26,3 +26,33 @@ fn test_pubkey() {
     let want = Pubkey::from_str("A7yUYJNEVYRLE4QWsnc9rE9JRsm7DfqEmLscQVwkffAk");
     assert_eq!(want.unwrap(), pk::PUBKEY);
 }
+
+mod errors {
+    use anchor_lang::prelude::*;
+
+    #[error_code]
+    pub enum MyError {
+        #[msg("First error")]
+        First,
+        Second = 10,
+    }
+
+    // Programs can still implement conversions from error codes themselves
+    impl TryFrom<u32> for MyError {
+        type Error = u32;
+
+        fn try_from(code: u32) -> std::result::Result<Self, Self::Error> {
+            Self::from_code(code).ok_or(code)
+        }
+    }
+}
+
+#[test]
+fn test_error_code_from_code() {
+    use errors::MyError;
+
+    assert!(matches!(MyError::from_code(6000), Some(MyError::First)));
+    assert!(matches!(MyError::from_code(6010), Some(MyError::Second)));
+    assert!(MyError::from_code(6001).is_none());
+    assert!(matches!(MyError::try_from(6000), Ok(MyError::First)));
+}
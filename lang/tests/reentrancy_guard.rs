@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use std::collections::BTreeSet;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[derive(Accounts)]
+struct Withdraw<'info> {
+    authority: Signer<'info>,
+    #[account(instructions_sysvar)]
+    instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+struct Liquidate<'info> {
+    authority: Signer<'info>,
+}
+
+#[reentrancy_guard]
+fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+    ctx.accounts.authority.key();
+    Ok(())
+}
+
+#[reentrancy_guard(cpi)]
+fn liquidate(_ctx: Context<Liquidate>) -> Result<()> {
+    Ok(())
+}
+
+fn info<'a>(key: &'a Pubkey, lamports: &'a mut u64, data: &'a mut [u8]) -> AccountInfo<'a> {
+    AccountInfo::new(key, true, false, lamports, data, key, false)
+}
+
+#[test]
+fn reentrancy_guard_allows_transaction_level_instructions() {
+    let key = Pubkey::new_unique();
+    let sysvar_key = sysvar::instructions::ID;
+    let (mut lamports, mut sysvar_lamports) = (0, 0);
+    let (mut data, mut sysvar_data) = ([], []);
+    let sysvar_info = info(&sysvar_key, &mut sysvar_lamports, &mut sysvar_data);
+    let authority_info = info(&key, &mut lamports, &mut data);
+    let mut accounts = Withdraw {
+        authority: Signer::try_from(&authority_info).unwrap(),
+        instructions: UncheckedAccount::try_from(&sysvar_info),
+    };
+    let ctx = Context::new(&crate::ID, &mut accounts, &[], WithdrawBumps {});
+    assert!(withdraw(ctx).is_ok());
+
+    let mut accounts = Liquidate {
+        authority: Signer::try_from(&authority_info).unwrap(),
+    };
+    let ctx = Context::new(&crate::ID, &mut accounts, &[], LiquidateBumps {});
+    assert!(liquidate(ctx).is_ok());
+}
+
+fn try_withdraw(keys: [Pubkey; 2]) -> Result<()> {
+    let [(mut lamports, mut data), (mut sysvar_lamports, mut sysvar_data)] = [(0, []), (0, [])];
+    let infos = [
+        info(&keys[0], &mut lamports, &mut data),
+        info(&keys[1], &mut sysvar_lamports, &mut sysvar_data),
+    ];
+    Withdraw::try_accounts(
+        &crate::ID,
+        &mut &infos[..],
+        &[],
+        &mut WithdrawBumps {},
+        &mut BTreeSet::new(),
+    )?;
+    Ok(())
+}
+
+#[test]
+fn instructions_sysvar_is_checked_by_address() {
+    let key = Pubkey::new_unique();
+    assert!(try_withdraw([key, sysvar::instructions::ID]).is_ok());
+    assert_eq!(
+        try_withdraw([key, Pubkey::new_unique()]).unwrap_err(),
+        ErrorCode::ConstraintAddress.into()
+    );
+}
+
+#[test]
+fn reentrancy_guard_error_codes() {
+    assert_eq!(u32::from(ErrorCode::ReentrancyGuardCpi), 2049);
+    assert_eq!(u32::from(ErrorCode::ReentrancyGuardReentered), 2050);
+    assert_eq!(u32::from(ErrorCode::ReentrancyGuardUnknownCaller), 2051);
+}
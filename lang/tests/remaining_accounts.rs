@@ -0,0 +1,205 @@
+use anchor_lang::context::RemainingAccountsExits;
+use anchor_lang::prelude::*;
+use std::collections::BTreeSet;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account]
+struct Pool {
+    volume: u64,
+}
+
+#[derive(Accounts)]
+struct Swap<'info> {
+    user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+struct SwapFrom<'info> {
+    #[account(mut)]
+    pool: Account<'info, Pool>,
+}
+
+#[derive(Accounts)]
+struct Hop<'info> {
+    #[account(mut)]
+    pool: Account<'info, Pool>,
+    user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(min_volume: u64)]
+struct CheckedHop<'info> {
+    #[account(mut, constraint = pool.volume >= min_volume)]
+    pool: Account<'info, Pool>,
+}
+
+#[derive(Accounts)]
+struct Grow<'info> {
+    #[account(mut, realloc = 100, realloc::payer = user, realloc::zero = false)]
+    pool: Account<'info, Pool>,
+    #[account(mut)]
+    user: Signer<'info>,
+    system_program: Program<'info, System>,
+}
+
+fn read(info: &AccountInfo) -> u64 {
+    let data = info.try_borrow_data().unwrap();
+    Pool::try_deserialize(&mut &data[..]).unwrap().volume
+}
+
+// Remaining accounts must outlive `'info` to be deserialized
+fn leak_info(data: Vec<u8>, owner: Pubkey, is_signer: bool) -> AccountInfo<'static> {
+    leak_info_with_key(Pubkey::new_unique(), data, owner, is_signer, false)
+}
+
+fn leak_info_with_key(
+    key: Pubkey,
+    data: Vec<u8>,
+    owner: Pubkey,
+    is_signer: bool,
+    executable: bool,
+) -> AccountInfo<'static> {
+    AccountInfo::new(
+        Box::leak(Box::new(key)),
+        is_signer,
+        true,
+        Box::leak(Box::new(1)),
+        Box::leak(data.into_boxed_slice()),
+        Box::leak(Box::new(owner)),
+        executable,
+    )
+}
+
+fn pool(volume: u64) -> AccountInfo<'static> {
+    let mut data = Vec::new();
+    Pool { volume }.try_serialize(&mut data).unwrap();
+    leak_info(data, crate::ID, false)
+}
+
+fn signer() -> AccountInfo<'static> {
+    leak_info(Vec::new(), Pubkey::default(), true)
+}
+
+fn swap(infos: &'static [AccountInfo<'static>]) -> Swap<'static> {
+    Swap::try_accounts(
+        &crate::ID,
+        &mut &infos[..],
+        &[],
+        &mut SwapBumps {},
+        &mut BTreeSet::new(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn remaining_accounts_are_persisted_after_the_instruction() {
+    let user = signer();
+    let infos: &'static [AccountInfo<'static>] =
+        Vec::leak(vec![user.clone(), pool(1), user.clone(), pool(5), user]);
+
+    let mut accounts = swap(&infos[..1]);
+    let exits = RemainingAccountsExits::default();
+    let mut ctx = Context::new(&crate::ID, &mut accounts, &infos[1..], SwapBumps {})
+        .with_remaining_exits(exits.clone());
+
+    for _ in 0..2 {
+        let mut hop = ctx.try_remaining_accounts::<Hop>().unwrap();
+        hop.pool.volume += 1;
+    }
+    assert!(ctx.remaining_accounts.is_empty());
+
+    // Not enough accounts left
+    assert!(ctx.try_remaining_accounts::<Hop>().is_err());
+
+    // Only persisted once the instruction returns
+    assert_eq!(read(&infos[1]), 1);
+    assert_eq!(read(&infos[3]), 5);
+    exits.exit(&crate::ID).unwrap();
+    assert_eq!(read(&infos[1]), 2);
+    assert_eq!(read(&infos[3]), 6);
+}
+
+#[test]
+fn remaining_accounts_use_the_instruction_data() {
+    let user = signer();
+    let infos: &'static [AccountInfo<'static>] = Vec::leak(vec![user, pool(5)]);
+    let mut accounts = swap(&infos[..1]);
+
+    let ix_data = 5u64.to_le_bytes();
+    let mut ctx =
+        Context::new(&crate::ID, &mut accounts, &infos[1..], SwapBumps {}).with_ix_data(&ix_data);
+    assert!(ctx.try_remaining_accounts::<CheckedHop>().is_ok());
+
+    let ix_data = 6u64.to_le_bytes();
+    let mut ctx =
+        Context::new(&crate::ID, &mut accounts, &infos[1..], SwapBumps {}).with_ix_data(&ix_data);
+    assert_eq!(
+        ctx.try_remaining_accounts::<CheckedHop>().err().unwrap(),
+        ErrorCode::ConstraintRaw.into()
+    );
+
+    // Missing instruction data
+    let mut ctx = Context::new(&crate::ID, &mut accounts, &infos[1..], SwapBumps {});
+    assert_eq!(
+        ctx.try_remaining_accounts::<CheckedHop>().err().unwrap(),
+        ErrorCode::InstructionDidNotDeserialize.into()
+    );
+}
+
+#[test]
+fn remaining_accounts_reject_duplicate_mutable_accounts() {
+    let pool = pool(1);
+    let infos: &'static [AccountInfo<'static>] = Vec::leak(vec![pool.clone(), pool, signer()]);
+
+    let mut accounts = SwapFrom::try_accounts(
+        &crate::ID,
+        &mut &infos[..1],
+        &[],
+        &mut SwapFromBumps {},
+        &mut BTreeSet::new(),
+    )
+    .unwrap();
+    let mut ctx = Context::new(&crate::ID, &mut accounts, &infos[1..], SwapFromBumps {});
+    assert_eq!(
+        ctx.try_remaining_accounts::<Hop>().err().unwrap(),
+        ErrorCode::ConstraintDuplicateMutableAccount.into()
+    );
+}
+
+#[test]
+fn remaining_accounts_share_the_instruction_reallocs() {
+    let pool = pool(1);
+    let system_program = leak_info_with_key(
+        system_program::ID,
+        Vec::new(),
+        Pubkey::default(),
+        false,
+        true,
+    );
+    let user = signer();
+    let infos: &'static [AccountInfo<'static>] =
+        Vec::leak(vec![user.clone(), pool.clone(), user, system_program]);
+    let mut accounts = swap(&infos[..1]);
+
+    // The pool was already reallocated by the instruction's accounts
+    let mut ctx = Context::new(&crate::ID, &mut accounts, &infos[1..], SwapBumps {})
+        .with_reallocs(BTreeSet::from([*pool.key]));
+    assert_eq!(
+        ctx.try_remaining_accounts::<Grow>().err().unwrap(),
+        ErrorCode::AccountDuplicateReallocs.into()
+    );
+}
+
+#[test]
+fn remaining_accounts_check_constraints() {
+    let user = signer();
+    let infos: &'static [AccountInfo<'static>] = Vec::leak(vec![user, pool(1), pool(1)]);
+    let mut accounts = swap(&infos[..1]);
+
+    let mut ctx = Context::new(&crate::ID, &mut accounts, &infos[1..], SwapBumps {});
+    assert_eq!(
+        ctx.try_remaining_accounts::<Hop>().err().unwrap(),
+        ErrorCode::AccountNotSigner.into()
+    );
+}
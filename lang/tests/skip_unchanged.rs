@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+use std::collections::BTreeSet;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account]
+struct Counter {
+    count: u64,
+}
+
+#[derive(Accounts)]
+struct Update<'info> {
+    #[account(mut, skip_unchanged)]
+    skipped: Account<'info, Counter>,
+    #[account(mut)]
+    persisted: Account<'info, Counter>,
+}
+
+fn serialize(count: u64) -> Vec<u8> {
+    let mut data = Vec::new();
+    Counter { count }.try_serialize(&mut data).unwrap();
+    data
+}
+
+fn read(info: &AccountInfo) -> u64 {
+    let data = info.try_borrow_data().unwrap();
+    Counter::try_deserialize(&mut &data[..]).unwrap().count
+}
+
+// Simulates a CPI that writes to the account
+fn write(info: &AccountInfo, count: u64) {
+    info.try_borrow_mut_data()
+        .unwrap()
+        .copy_from_slice(&serialize(count));
+}
+
+#[test]
+fn unchanged_accounts_are_not_serialized_on_exit() {
+    let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let mut lamports = [1, 1];
+    let mut data = [serialize(1), serialize(1)];
+    let [skipped_lamports, persisted_lamports] = &mut lamports;
+    let [skipped_data, persisted_data] = &mut data;
+    let infos = &[
+        AccountInfo::new(
+            &keys[0],
+            false,
+            true,
+            skipped_lamports,
+            skipped_data,
+            &crate::ID,
+            false,
+        ),
+        AccountInfo::new(
+            &keys[1],
+            false,
+            true,
+            persisted_lamports,
+            persisted_data,
+            &crate::ID,
+            false,
+        ),
+    ];
+
+    let mut accounts = Update::try_accounts(
+        &crate::ID,
+        &mut &infos[..],
+        &[],
+        &mut UpdateBumps {},
+        &mut BTreeSet::new(),
+    )
+    .unwrap();
+    assert!(!accounts.skipped.is_dirty());
+
+    write(&infos[0], 2);
+    write(&infos[1], 2);
+    accounts.exit(&crate::ID).unwrap();
+    assert_eq!(read(&infos[0]), 2);
+    // Without the `skip-unchanged` feature, only opted in accounts are skipped
+    let persisted = if cfg!(feature = "skip-unchanged") {
+        2
+    } else {
+        1
+    };
+    assert_eq!(read(&infos[1]), persisted);
+
+    // Reloading doesn't mark the account as modified
+    accounts.skipped.reload().unwrap();
+    assert!(!accounts.skipped.is_dirty());
+
+    accounts.skipped.count += 1;
+    assert!(accounts.skipped.is_dirty());
+    accounts.exit(&crate::ID).unwrap();
+    assert_eq!(read(&infos[0]), 3);
+}
@@ -0,0 +1,190 @@
+use anchor_lang::prelude::*;
+use std::collections::BTreeSet;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account]
+struct Counter {
+    count: u64,
+    authority: Pubkey,
+}
+
+#[derive(Accounts)]
+struct Transfer<'info> {
+    #[account(mut)]
+    from: Account<'info, Counter>,
+    to: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(count: u8)]
+struct Batch<'info> {
+    authority: Signer<'info>,
+    #[account(mut, len = count, has_one = authority, constraint = counters.count < 10)]
+    counters: Vec<Account<'info, Counter>>,
+    #[account(len = 2)]
+    transfers: Vec<Transfer<'info>>,
+}
+
+struct TestAccount {
+    key: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    owner: Pubkey,
+    is_signer: bool,
+}
+
+impl TestAccount {
+    fn counter(count: u64, authority: Pubkey) -> Self {
+        let mut data = Vec::new();
+        Counter { count, authority }
+            .try_serialize(&mut data)
+            .unwrap();
+        Self {
+            key: Pubkey::new_unique(),
+            lamports: 1,
+            data,
+            owner: crate::ID,
+            is_signer: false,
+        }
+    }
+
+    fn signer() -> Self {
+        Self {
+            key: Pubkey::new_unique(),
+            lamports: 1,
+            data: Vec::new(),
+            owner: Pubkey::default(),
+            is_signer: true,
+        }
+    }
+
+    fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            self.is_signer,
+            true,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            false,
+        )
+    }
+}
+
+fn try_batch(accounts: &mut [TestAccount], count: u8) -> Result<Vec<u64>> {
+    let infos = accounts.iter_mut().map(|a| a.info()).collect::<Vec<_>>();
+    let mut bumps = BatchBumps::default();
+    let batch = Batch::try_accounts(
+        &crate::ID,
+        &mut &infos[..],
+        &[count],
+        &mut bumps,
+        &mut BTreeSet::new(),
+    )?;
+    assert_eq!(bumps.transfers.len(), 2);
+    assert_eq!(batch.transfers.len(), 2);
+    Ok(batch.counters.iter().map(|c| c.count).collect())
+}
+
+fn batch_accounts(counts: &[u64]) -> Vec<TestAccount> {
+    let authority = TestAccount::signer();
+    let authority_key = authority.key;
+    let mut accounts = vec![authority];
+    accounts.extend(
+        counts
+            .iter()
+            .map(|count| TestAccount::counter(*count, authority_key)),
+    );
+    for _ in 0..2 {
+        accounts.push(TestAccount::counter(0, Pubkey::default()));
+        accounts.push(TestAccount::signer());
+    }
+    accounts
+}
+
+#[test]
+fn vec_accounts_are_deserialized_with_len() {
+    let mut accounts = batch_accounts(&[1, 2, 3]);
+    assert_eq!(try_batch(&mut accounts, 3).unwrap(), vec![1, 2, 3]);
+
+    let mut accounts = batch_accounts(&[]);
+    assert_eq!(try_batch(&mut accounts, 0).unwrap(), Vec::<u64>::new());
+
+    // Not enough accounts
+    let mut accounts = batch_accounts(&[1]);
+    accounts.truncate(3);
+    assert!(try_batch(&mut accounts, 1).is_err());
+}
+
+#[test]
+fn vec_accounts_elements_are_checked() {
+    // Constraint
+    let mut accounts = batch_accounts(&[1, 10]);
+    assert_eq!(
+        try_batch(&mut accounts, 2).unwrap_err(),
+        ErrorCode::ConstraintRaw.into()
+    );
+
+    // `has_one`
+    let mut accounts = batch_accounts(&[1, 2]);
+    accounts[2] = TestAccount::counter(2, Pubkey::new_unique());
+    assert_eq!(
+        try_batch(&mut accounts, 2).unwrap_err(),
+        ErrorCode::ConstraintHasOne.into()
+    );
+
+    // Owner
+    let mut accounts = batch_accounts(&[1, 2]);
+    accounts[2].owner = Pubkey::new_unique();
+    assert_eq!(
+        try_batch(&mut accounts, 2).unwrap_err(),
+        ErrorCode::AccountOwnedByWrongProgram.into()
+    );
+
+    // Duplicate mutable accounts
+    let mut accounts = batch_accounts(&[1, 2]);
+    accounts[2].key = accounts[1].key;
+    assert_eq!(
+        try_batch(&mut accounts, 2).unwrap_err(),
+        ErrorCode::ConstraintDuplicateMutableAccount.into()
+    );
+
+    // Duplicate mutable accounts in the elements of a composite list
+    let mut accounts = batch_accounts(&[1, 2]);
+    accounts[5].key = accounts[3].key;
+    assert_eq!(
+        try_batch(&mut accounts, 2).unwrap_err(),
+        ErrorCode::ConstraintDuplicateMutableAccount.into()
+    );
+
+    // Duplicate mutable accounts in an element of a composite list and another field
+    let mut accounts = batch_accounts(&[1, 2]);
+    accounts[3].key = accounts[1].key;
+    assert_eq!(
+        try_batch(&mut accounts, 2).unwrap_err(),
+        ErrorCode::ConstraintDuplicateMutableAccount.into()
+    );
+}
+
+#[test]
+fn vec_accounts_client_metas() {
+    let authority = Pubkey::new_unique();
+    let counters = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+    let transfer = __client_accounts_transfer::Transfer {
+        from: Pubkey::new_unique(),
+        to: Pubkey::new_unique(),
+    };
+    let metas = __client_accounts_batch::Batch {
+        authority,
+        counters: counters.clone(),
+        transfers: vec![transfer],
+    }
+    .to_account_metas(None);
+
+    assert_eq!(metas.len(), 5);
+    assert_eq!(metas[1].pubkey, counters[0]);
+    assert_eq!(metas[2].pubkey, counters[1]);
+    assert!(metas[1].is_writable && metas[2].is_writable);
+    assert!(metas[4].is_signer);
+}
@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use std::collections::BTreeSet;
+
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+
+#[account]
+struct CounterV1 {
+    count: u32,
+}
+
+#[account]
+struct CounterV2 {
+    count: u64,
+}
+
+#[account]
+struct CounterV3 {
+    count: u64,
+    authority: Pubkey,
+}
+
+impl From<CounterV1> for CounterV2 {
+    fn from(v1: CounterV1) -> Self {
+        Self {
+            count: v1.count.into(),
+        }
+    }
+}
+
+impl From<CounterV2> for CounterV3 {
+    fn from(v2: CounterV2) -> Self {
+        Self {
+            count: v2.count,
+            authority: Pubkey::default(),
+        }
+    }
+}
+
+#[derive(Accounts)]
+struct Increment<'info> {
+    #[account(mut)]
+    counter: Versioned<'info, (CounterV1, CounterV2, CounterV3)>,
+}
+
+// Only needs to compile
+#[derive(Accounts)]
+struct Upgrade<'info> {
+    #[account(mut)]
+    payer: Signer<'info>,
+    #[account(
+        mut,
+        realloc = 8 + 8 + 32,
+        realloc::payer = payer,
+        realloc::zero = false
+    )]
+    counter: Versioned<'info, (CounterV1, CounterV2, CounterV3)>,
+    system_program: Program<'info, System>,
+}
+
+fn serialize(account: &impl AccountSerialize) -> Vec<u8> {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+    data
+}
+
+#[test]
+fn versioned_accounts_are_written_back_in_the_latest_version() {
+    for (data, stored_version) in [
+        (serialize(&CounterV1 { count: 1 }), 0),
+        (serialize(&CounterV2 { count: 1 }), 1),
+        (
+            serialize(&CounterV3 {
+                count: 1,
+                authority: Pubkey::default(),
+            }),
+            2,
+        ),
+    ] {
+        let key = Pubkey::new_unique();
+        let mut lamports = 1;
+        // Leave room for the latest version, `AccountInfo::resize` only works in the runtime
+        let mut data = data;
+        data.resize(8 + 8 + 32, 0);
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &crate::ID,
+            false,
+        );
+        let infos = &[info];
+
+        let mut accounts = Increment::try_accounts(
+            &crate::ID,
+            &mut &infos[..],
+            &[],
+            &mut IncrementBumps {},
+            &mut BTreeSet::new(),
+        )
+        .unwrap();
+        assert_eq!(accounts.counter.stored_version(), stored_version);
+        accounts.counter.count += 1;
+        accounts.exit(&crate::ID).unwrap();
+
+        let data = infos[0].try_borrow_data().unwrap();
+        let counter = CounterV3::try_deserialize(&mut &data[..]).unwrap();
+        assert_eq!(counter.count, 2);
+    }
+}
@@ -38,6 +38,39 @@ pub fn test_event_parser() {
     }
 }
 
+#[test]
+pub fn test_event_cpi_parser() {
+    use anchor_lang::event::EVENT_IX_TAG_LE;
+    use anchor_lang::solana_program::instruction::Instruction as SolanaInstruction;
+    use external::parsers::Event;
+
+    const DISC: &[u8] = external::events::MyEvent::DISCRIMINATOR;
+    let data = [EVENT_IX_TAG_LE, DISC, &[1, 0, 0, 0]].concat();
+
+    // Missing event instruction tag
+    assert!(Event::parse_cpi(&data[EVENT_IX_TAG_LE.len()..]).is_err());
+
+    // Incorrect program
+    assert!(
+        Event::parse_cpi_instruction(&SolanaInstruction::new_with_bytes(
+            system_program::ID,
+            &data,
+            vec![]
+        ))
+        .is_err()
+    );
+
+    // Correct program and valid data
+    match Event::parse_cpi_instruction(&SolanaInstruction::new_with_bytes(
+        external::ID,
+        &data,
+        vec![],
+    )) {
+        Ok(Event::MyEvent(my_event)) => assert_eq!(my_event.value, 1),
+        Err(e) => panic!("Expected Ok result, got error: {:?}", e),
+    }
+}
+
 #[test]
 pub fn test_instruction_parser() {
     use anchor_lang::solana_program::instruction::Instruction as SolanaInstruction;
@@ -276,6 +309,48 @@ pub fn test_instruction_parser() {
         Ok(_) => panic!("Expected UpdateWithOptional instruction variant"),
         Err(e) => panic!("Expected Ok result, got error: {:?}", e),
     };
+
+    // Test lists of accounts parsing
+    let my_accounts = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let expected_args = external::client::args::UpdateMany { count: 2, value: 5 };
+    let data = [
+        external::client::args::UpdateMany::DISCRIMINATOR,
+        &ser(&expected_args),
+    ]
+    .concat();
+    let mut metas = vec![
+        AccountMeta::new_readonly(authority, true),
+        AccountMeta::new(my_accounts[0], false),
+        AccountMeta::new(my_accounts[1], false),
+    ];
+    for _ in 0..2 {
+        metas.push(AccountMeta::new_readonly(authority, true));
+        metas.push(AccountMeta::new(my_account, false));
+    }
+    match Instruction::parse(&SolanaInstruction::new_with_bytes(
+        external::ID,
+        &data,
+        metas.clone(),
+    )) {
+        Ok(Instruction::UpdateMany { accounts, args }) => {
+            assert_eq!(accounts.authority, authority);
+            assert_eq!(accounts.my_accounts, my_accounts);
+            assert_eq!(accounts.updates.len(), 2);
+            assert_eq!(accounts.updates[1].my_account, my_account);
+            assert_eq!(args.count, expected_args.count);
+        }
+        Ok(_) => panic!("Expected UpdateMany instruction variant"),
+        Err(e) => panic!("Expected Ok result, got error: {:?}", e),
+    };
+
+    // Fewer accounts than the length of the list
+    metas.truncate(4);
+    assert!(Instruction::parse(&SolanaInstruction::new_with_bytes(
+        external::ID,
+        &data,
+        metas
+    ))
+    .is_err());
 }
 
 #[test]
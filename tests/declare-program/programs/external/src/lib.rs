@@ -117,6 +117,17 @@ pub mod external {
         ctx.accounts.my_account.field = value;
         Ok(())
     }
+
+    // Test lists of accounts parsing
+    pub fn update_many(ctx: Context<UpdateMany>, _count: u8, value: u32) -> Result<()> {
+        for my_account in ctx.accounts.my_accounts.iter_mut() {
+            my_account.field = value;
+        }
+        for update in ctx.accounts.updates.iter_mut() {
+            update.my_account.field = value;
+        }
+        Ok(())
+    }
 }
 
 #[error_code]
@@ -188,6 +199,16 @@ pub struct NonInstructionUpdate2<'info> {
     pub program: Program<'info, program::External>,
 }
 
+#[derive(Accounts)]
+#[instruction(count: u8)]
+pub struct UpdateMany<'info> {
+    pub authority: Signer<'info>,
+    #[account(mut, len = count)]
+    pub my_accounts: Vec<Account<'info, MyAccount>>,
+    #[account(len = 2)]
+    pub updates: Vec<Update<'info>>,
+}
+
 #[derive(Accounts)]
 pub struct UpdateWithOptional<'info> {
     pub authority: Signer<'info>,
@@ -60,13 +60,21 @@ export type IdlInstructionAccount = {
   address?: string;
   pda?: IdlPda;
   relations?: string[];
+  len?: IdlAccountsLen;
 };
 
 export type IdlInstructionAccounts = {
   name: string;
   accounts: IdlInstructionAccount[];
+  len?: IdlAccountsLen;
 };
 
+export type IdlAccountsLen =
+  | { arg: string }
+  | { account: string }
+  | { expr: string }
+  | number;
+
 export type IdlPda = {
   seeds: IdlSeed[];
   program?: IdlSeed;
@@ -28,7 +28,10 @@ import {
 } from "./namespace/methods";
 
 export type AccountsGeneric = {
-  [name: string]: PublicKey | AccountsGeneric;
+  [name: string]:
+    | PublicKey
+    | AccountsGeneric
+    | (PublicKey | AccountsGeneric)[];
 };
 
 export function isAccountsGeneric(
@@ -87,6 +90,7 @@ export class AccountsResolver<IDL extends Idl> {
       depth++;
       if (depth === 16) {
         const isResolvable = (acc: IdlInstructionAccountItem) => {
+          if (acc.len !== undefined) return false;
           if (!isCompositeAccounts(acc)) {
             return !!(acc.address || acc.pda || acc.relations);
           }
@@ -172,7 +176,16 @@ export class AccountsResolver<IDL extends Idl> {
       // Skip if the account isn't included (thus would be undefined)
       if (partialAccount === undefined) continue;
 
-      if (isPartialAccounts(partialAccount)) {
+      if (Array.isArray(partialAccount)) {
+        // is a list of accounts, resolve each item as a single account
+        const item = { ...accountItem, len: undefined };
+        nestedAccountsGeneric[accountName] = partialAccount.map(
+          (listItem) =>
+            this.resolveOptionalsHelper({ [accountName]: listItem }, [item])[
+              accountName
+            ] as PublicKey | AccountsGeneric
+        );
+      } else if (isPartialAccounts(partialAccount)) {
         // is compound accounts, recurse one level deeper
         if (isCompositeAccounts(accountItem)) {
           nestedAccountsGeneric[accountName] = this.resolveOptionalsHelper(
@@ -228,7 +241,10 @@ export class AccountsResolver<IDL extends Idl> {
   ): void {
     for (const i in accounts) {
       const accountOrAccounts = accounts[i];
-      if (isCompositeAccounts(accountOrAccounts)) {
+      if (
+        isCompositeAccounts(accountOrAccounts) &&
+        accountOrAccounts.len === undefined
+      ) {
         this.resolveEventCpi(accountOrAccounts.accounts, [
           ...path,
           accountOrAccounts.name,
@@ -271,6 +287,9 @@ export class AccountsResolver<IDL extends Idl> {
   ) {
     for (const accountOrAccounts of accounts) {
       const name = accountOrAccounts.name;
+      // Lists of accounts can only be given by the caller
+      if (accountOrAccounts.len !== undefined) continue;
+
       if (isCompositeAccounts(accountOrAccounts)) {
         this.resolveConst(accountOrAccounts.accounts, [...path, name]);
       } else {
@@ -303,6 +322,9 @@ export class AccountsResolver<IDL extends Idl> {
     let found = 0;
     for (const accountOrAccounts of accounts) {
       const name = accountOrAccounts.name;
+      // Lists of accounts can only be given by the caller
+      if (accountOrAccounts.len !== undefined) continue;
+
       if (isCompositeAccounts(accountOrAccounts)) {
         found += await this.resolvePdasAndRelations(
           accountOrAccounts.accounts,
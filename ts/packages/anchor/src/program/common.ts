@@ -44,7 +44,17 @@ export function validateAccounts(
   accounts: Accounts = {}
 ) {
   ixAccounts.forEach((acc) => {
-    if (isCompositeAccounts(acc)) {
+    if (acc.len !== undefined) {
+      const list = accounts[acc.name];
+      if (!Array.isArray(list)) {
+        throw new Error(`Account list \`${acc.name}\` not provided.`);
+      }
+
+      const item = { ...acc, len: undefined };
+      list.forEach((listItem) =>
+        validateAccounts([item], { [acc.name]: listItem } as Accounts)
+      );
+    } else if (isCompositeAccounts(acc)) {
       validateAccounts(acc.accounts, accounts[acc.name] as Accounts);
     } else {
       if (!accounts[acc.name]) {
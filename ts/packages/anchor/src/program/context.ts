@@ -6,6 +6,7 @@ import {
 } from "@solana/web3.js";
 import { Address } from "./common.js";
 import {
+  IdlAccountsLen,
   IdlInstructionAccountItem,
   IdlInstructionAccounts,
   IdlInstruction,
@@ -64,6 +65,9 @@ export type Context<A extends Accounts = Accounts> = {
  *
  * If multiple accounts are nested in the rust program, then they should be
  * nested here.
+ *
+ * Lists of accounts, i.e. `Vec` fields with a `len` constraint, are given as
+ * arrays.
  */
 export type Accounts<
   A extends IdlInstructionAccountItem = IdlInstructionAccountItem
@@ -71,7 +75,15 @@ export type Accounts<
   [N in A["name"]]: Account<A & { name: N }>;
 };
 
-type Account<A extends IdlInstructionAccountItem> =
+type Account<A extends IdlInstructionAccountItem> = A extends {
+  len: IdlAccountsLen;
+}
+  ? AccountItem<A>[]
+  : A extends { len?: undefined }
+  ? AccountItem<A>
+  : AccountItem<A> | AccountItem<A>[];
+
+type AccountItem<A extends IdlInstructionAccountItem> =
   A extends IdlInstructionAccounts
     ? Accounts<A["accounts"][number]>
     : A extends { optional: true }
@@ -86,6 +86,32 @@ export default class InstructionNamespaceFactory {
 
     return accounts
       .map((acc) => {
+        // Lists of accounts are given as arrays, each item is flattened in order
+        if (acc.len !== undefined) {
+          const list = ctx[acc.name];
+          if (!Array.isArray(list)) {
+            throw new Error(
+              `Wrong input type for account list "${
+                acc.name
+              }" in the instruction accounts object${
+                ixName !== undefined ? ' for instruction "' + ixName + '"' : ""
+              }. Expected an array.`
+            );
+          }
+
+          const item = { ...acc, len: undefined };
+          return list
+            .map((listItem) =>
+              InstructionNamespaceFactory.accountsArray(
+                { [acc.name]: listItem } as Accounts,
+                [item],
+                programId,
+                ixName
+              )
+            )
+            .flat();
+        }
+
         if (isCompositeAccounts(acc)) {
           const rpcAccs = ctx[acc.name] as Accounts;
           return InstructionNamespaceFactory.accountsArray(
@@ -9,6 +9,7 @@ import {
 } from "@solana/web3.js";
 import {
   Idl,
+  IdlAccountsLen,
   IdlInstructionAccount,
   IdlInstructionAccountItem,
   IdlInstructionAccounts,
@@ -115,6 +116,14 @@ export type PartialAccounts<
 
 type PartialAccount<
   A extends IdlInstructionAccountItem = IdlInstructionAccountItem
+> = A extends { len: IdlAccountsLen }
+  ? PartialAccountItem<A>[]
+  : A extends { len?: undefined }
+  ? PartialAccountItem<A>
+  : PartialAccountItem<A> | PartialAccountItem<A>[];
+
+type PartialAccountItem<
+  A extends IdlInstructionAccountItem = IdlInstructionAccountItem
 > = A extends IdlInstructionAccounts
   ? PartialAccounts<A["accounts"][number]>
   : A extends { optional: true }
@@ -145,7 +154,13 @@ export function flattenPartialAccounts<A extends IdlInstructionAccountItem>(
         );
       continue;
     }
-    toReturn[accountName] = isPartialAccounts(account)
+    toReturn[accountName] = Array.isArray(account)
+      ? account.map((listItem) =>
+          isPartialAccounts(listItem)
+            ? flattenPartialAccounts(listItem, true)
+            : translateAddress(listItem)
+        )
+      : isPartialAccounts(account)
       ? flattenPartialAccounts(account, true)
       : translateAddress(account);
   }
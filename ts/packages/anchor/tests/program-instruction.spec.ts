@@ -0,0 +1,133 @@
+import { PublicKey } from "@solana/web3.js";
+
+import { IdlInstructionAccountItem } from "../src/idl";
+import { validateAccounts } from "../src/program/common";
+import { Accounts } from "../src/program/context";
+import InstructionNamespaceFactory from "../src/program/namespace/instruction";
+import { flattenPartialAccounts } from "../src/program/namespace/methods";
+
+describe("program/namespace/instruction", () => {
+  const programId = new PublicKey(
+    "Test111111111111111111111111111111111111111"
+  );
+  const authority = PublicKey.unique();
+  const first = PublicKey.unique();
+  const second = PublicKey.unique();
+  const third = PublicKey.unique();
+  const fourth = PublicKey.unique();
+
+  const idlAccounts: IdlInstructionAccountItem[] = [
+    { name: "authority", signer: true },
+    { name: "myAccounts", writable: true, len: { arg: "count" } },
+    {
+      name: "pairs",
+      accounts: [{ name: "from", writable: true }, { name: "to" }],
+      len: 2,
+    },
+  ];
+
+  const accounts: Accounts = {
+    authority,
+    myAccounts: [first, second],
+    pairs: [
+      { from: first, to: second },
+      { from: third, to: fourth },
+    ],
+  };
+
+  describe("accountsArray", () => {
+    it("should flatten lists of accounts in order", () => {
+      const metas = InstructionNamespaceFactory.accountsArray(
+        accounts,
+        idlAccounts,
+        programId
+      );
+
+      expect(metas.map((meta) => meta.pubkey.toBase58())).toEqual(
+        [authority, first, second, first, second, third, fourth].map((key) =>
+          key.toBase58()
+        )
+      );
+      expect(metas.map((meta) => meta.isWritable)).toEqual([
+        false,
+        true,
+        true,
+        true,
+        false,
+        true,
+        false,
+      ]);
+      expect(metas.map((meta) => meta.isSigner)).toEqual([
+        true,
+        false,
+        false,
+        false,
+        false,
+        false,
+        false,
+      ]);
+    });
+
+    it("should flatten empty lists of accounts", () => {
+      const metas = InstructionNamespaceFactory.accountsArray(
+        { ...accounts, myAccounts: [], pairs: [] },
+        idlAccounts,
+        programId
+      );
+
+      expect(metas.map((meta) => meta.pubkey.toBase58())).toEqual([
+        authority.toBase58(),
+      ]);
+    });
+
+    it("should not accept a single account for a list", () => {
+      const func = () =>
+        InstructionNamespaceFactory.accountsArray(
+          { ...accounts, myAccounts: first },
+          idlAccounts,
+          programId,
+          "test"
+        );
+
+      expect(func).toThrow(
+        'Wrong input type for account list "myAccounts" in the instruction accounts object for instruction "test". Expected an array.'
+      );
+    });
+  });
+
+  describe("validateAccounts", () => {
+    it("should validate each item of a list of accounts", () => {
+      expect(() => validateAccounts(idlAccounts, accounts)).not.toThrow();
+
+      const missing: Accounts = {
+        ...accounts,
+        pairs: [{ from: first, to: second }, { from: third }],
+      };
+      expect(() => validateAccounts(idlAccounts, missing)).toThrow(
+        "Account `to` not provided."
+      );
+    });
+  });
+
+  describe("flattenPartialAccounts", () => {
+    it("should keep lists of accounts as arrays", () => {
+      const flattened = flattenPartialAccounts(
+        {
+          myAccounts: [first.toBase58(), second],
+          pairs: [{ from: third.toBase58(), to: fourth }],
+        },
+        false
+      );
+
+      const myAccounts = flattened.myAccounts as PublicKey[];
+      expect(myAccounts.map((key) => key.toBase58())).toEqual([
+        first.toBase58(),
+        second.toBase58(),
+      ]);
+
+      const [pair] = flattened.pairs as { from: PublicKey; to: PublicKey }[];
+      expect(pair.from.toBase58()).toEqual(third.toBase58());
+      expect(pair.to.toBase58()).toEqual(fourth.toBase58());
+    });
+  });
+});